@@ -4,8 +4,8 @@ use regex::bytes::Regex;
 use revm::{
     db::{CacheDB, EmptyDB},
     primitives::{
-        address, hex, keccak256, AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Output,
-        TransactTo, B256, U256,
+        address, hex, keccak256, AccountInfo, Address, AnalysisKind, Bytecode, Bytes,
+        ExecutionResult, Output, TransactTo, B256, U256,
     },
     Evm,
 };
@@ -44,21 +44,29 @@ fn main() {
         .with_db(db)
         .build();
 
-    let started = Instant::now();
-    let tx_result = evm.transact().unwrap().result;
-    let return_data = match tx_result {
-        ExecutionResult::Success {
-            output, gas_used, ..
-        } => {
-            println!("Gas used: {:?}", gas_used);
-            println!("Time elapsed: {:?}", started.elapsed());
-            match output {
-                Output::Call(value) => value,
-                _ => unreachable!("Unexpected output type"),
+    let mut return_data = Bytes::new();
+    for analysis in [AnalysisKind::Analyse, AnalysisKind::Decode] {
+        evm.cfg_mut().perf_analyse_created_bytecodes = analysis.clone();
+        let started = Instant::now();
+        let tx_result = evm.transact().unwrap().result;
+        let output = match tx_result {
+            ExecutionResult::Success {
+                output, gas_used, ..
+            } => {
+                println!("{analysis:?} gas used: {:?}", gas_used);
+                println!("{analysis:?} time elapsed: {:?}", started.elapsed());
+                match output {
+                    Output::Call(value) => value,
+                    _ => unreachable!("Unexpected output type"),
+                }
             }
+            _ => unreachable!("Execution failed: {:?}", tx_result),
+        };
+        if analysis == AnalysisKind::Decode {
+            assert_eq!(output, return_data, "Decoded execution output differs");
         }
-        _ => unreachable!("Execution failed: {:?}", tx_result),
-    };
+        return_data = output;
+    }
 
     // remove returndata offset and length from output
     let returndata_offset = 64;
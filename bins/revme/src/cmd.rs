@@ -1,6 +1,9 @@
+pub mod blocktest;
 pub mod bytecode;
+pub mod eofvalidation;
 pub mod evmrunner;
 pub mod format_kzg_setup;
+pub mod run_tx;
 pub mod statetest;
 
 use structopt::{clap::AppSettings, StructOpt};
@@ -11,6 +14,8 @@ use structopt::{clap::AppSettings, StructOpt};
 pub enum MainCmd {
     #[structopt(about = "Launch Ethereum state tests")]
     Statetest(statetest::Cmd),
+    #[structopt(about = "Launch Ethereum blockchain tests")]
+    Blocktest(blocktest::Cmd),
     #[structopt(
         about = "Format kzg settings from a trusted setup file (.txt) into binary format (.bin)"
     )]
@@ -21,6 +26,15 @@ pub enum MainCmd {
     Evm(evmrunner::Cmd),
     #[structopt(alias = "bc", about = "Prints the opcodes of an hex Bytecodes.")]
     Bytecode(bytecode::Cmd),
+    #[structopt(
+        alias = "t8n",
+        about = "Executes transactions against a pre-state, compatible with the geth `evm t8n` tool."
+    )]
+    RunTx(run_tx::Cmd),
+    #[structopt(
+        about = "Validates EOF containers given in hex or in EOFTests fixtures and prints the validation errors."
+    )]
+    EofValidate(eofvalidation::Cmd),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -31,18 +45,25 @@ pub enum Error {
     KzgErrors(#[from] format_kzg_setup::KzgErrors),
     #[error(transparent)]
     EvmRunnerErrors(#[from] evmrunner::Errors),
+    #[error(transparent)]
+    RunTxErrors(#[from] run_tx::Errors),
+    #[error(transparent)]
+    EofValidation(#[from] eofvalidation::Errors),
 }
 
 impl MainCmd {
     pub fn run(&self) -> Result<(), Error> {
         match self {
             Self::Statetest(cmd) => cmd.run().map_err(Into::into),
+            Self::Blocktest(cmd) => cmd.run().map_err(Into::into),
             Self::FormatKzgSetup(cmd) => cmd.run().map_err(Into::into),
             Self::Evm(cmd) => cmd.run().map_err(Into::into),
             Self::Bytecode(cmd) => {
                 cmd.run();
                 Ok(())
             }
+            Self::RunTx(cmd) => cmd.run().map_err(Into::into),
+            Self::EofValidate(cmd) => cmd.run().map_err(Into::into),
         }
     }
 }
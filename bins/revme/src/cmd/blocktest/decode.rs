@@ -0,0 +1,278 @@
+//! Decoding of the RLP encoded blocks, used for blocks that are expected to be invalid
+//! and are given only as RLP, and encoding of the block headers.
+
+use super::models::{Block, BlockHeader};
+use crate::cmd::{
+    run_tx::{
+        models::{Authorization, Transaction, Withdrawal},
+        transaction::{
+            EIP1559_TX_TYPE, EIP2930_TX_TYPE, EIP4844_TX_TYPE, EIP7702_TX_TYPE, LEGACY_TX_TYPE,
+        },
+    },
+    statetest::models::AccessListItem,
+};
+use alloy_rlp::{Decodable, Encodable, Error, Header, RlpDecodable};
+use revm::primitives::{
+    alloy_primitives::{Bloom, B64, U64},
+    keccak256, Address, Bytes, B256, U256,
+};
+
+#[derive(RlpDecodable)]
+struct RlpAccessListItem {
+    address: Address,
+    storage_keys: Vec<B256>,
+}
+
+#[derive(RlpDecodable)]
+struct RlpAuthorization {
+    chain_id: U256,
+    address: Address,
+    nonce: u64,
+    y_parity: u64,
+    r: U256,
+    s: U256,
+}
+
+#[derive(RlpDecodable)]
+struct RlpWithdrawal {
+    index: u64,
+    validator_index: u64,
+    address: Address,
+    amount: u64,
+}
+
+/// Decodes the block `[header, transactions, uncles, withdrawals]`.
+///
+/// Hash of the header is set to the hash of its encoding.
+pub fn decode_block(mut buf: &[u8]) -> Result<Block, Error> {
+    let mut fields = Header::decode_bytes(&mut buf, true)?;
+    if !buf.is_empty() {
+        return Err(Error::UnexpectedLength);
+    }
+    let header = decode_header(&mut fields)?;
+    let transactions = decode_list(&mut fields, decode_transaction)?;
+    let uncle_headers = decode_list(&mut fields, decode_header)?;
+    let withdrawals = if fields.is_empty() {
+        Vec::new()
+    } else {
+        Vec::<RlpWithdrawal>::decode(&mut fields)?
+            .into_iter()
+            .map(|withdrawal| Withdrawal {
+                index: withdrawal.index,
+                validator_index: withdrawal.validator_index,
+                address: withdrawal.address,
+                amount: withdrawal.amount,
+            })
+            .collect()
+    };
+    if !fields.is_empty() {
+        return Err(Error::UnexpectedLength);
+    }
+
+    Ok(Block {
+        block_header: Some(header),
+        transactions,
+        uncle_headers,
+        withdrawals,
+        expect_exception: None,
+        rlp: None,
+    })
+}
+
+/// Decodes the list with the given item decoder.
+fn decode_list<T>(
+    buf: &mut &[u8],
+    decode: impl Fn(&mut &[u8]) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    let mut items = Header::decode_bytes(buf, true)?;
+    let mut list = Vec::new();
+    while !items.is_empty() {
+        list.push(decode(&mut items)?);
+    }
+    Ok(list)
+}
+
+/// Decodes the field that is present only since some fork.
+fn decode_optional<T: Decodable>(buf: &mut &[u8]) -> Result<Option<T>, Error> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    T::decode(buf).map(Some)
+}
+
+fn decode_header(buf: &mut &[u8]) -> Result<BlockHeader, Error> {
+    let encoded = *buf;
+    let mut fields = Header::decode_bytes(buf, true)?;
+    let hash = keccak256(&encoded[..encoded.len() - buf.len()]);
+
+    let header = BlockHeader {
+        parent_hash: B256::decode(&mut fields)?,
+        uncle_hash: B256::decode(&mut fields)?,
+        coinbase: Address::decode(&mut fields)?,
+        state_root: B256::decode(&mut fields)?,
+        transactions_trie: B256::decode(&mut fields)?,
+        receipt_trie: B256::decode(&mut fields)?,
+        bloom: Bloom::decode(&mut fields)?,
+        difficulty: U256::decode(&mut fields)?,
+        number: U256::decode(&mut fields)?,
+        gas_limit: U256::decode(&mut fields)?,
+        gas_used: U256::decode(&mut fields)?,
+        timestamp: U256::decode(&mut fields)?,
+        extra_data: Bytes::decode(&mut fields)?,
+        mix_hash: B256::decode(&mut fields)?,
+        nonce: B64::decode(&mut fields)?,
+        hash: Some(hash),
+        base_fee_per_gas: decode_optional(&mut fields)?,
+        withdrawals_root: decode_optional(&mut fields)?,
+        blob_gas_used: decode_optional(&mut fields)?,
+        excess_blob_gas: decode_optional(&mut fields)?,
+        parent_beacon_block_root: decode_optional(&mut fields)?,
+        requests_hash: decode_optional(&mut fields)?,
+    };
+    if !fields.is_empty() {
+        return Err(Error::UnexpectedLength);
+    }
+    Ok(header)
+}
+
+/// Encodes the header, the fields of the later forks are encoded while they are present.
+pub fn encode_header(header: &BlockHeader) -> Vec<u8> {
+    let mut fields: Vec<&dyn Encodable> = vec![
+        &header.parent_hash,
+        &header.uncle_hash,
+        &header.coinbase,
+        &header.state_root,
+        &header.transactions_trie,
+        &header.receipt_trie,
+        &header.bloom,
+        &header.difficulty,
+        &header.number,
+        &header.gas_limit,
+        &header.gas_used,
+        &header.timestamp,
+        &header.extra_data,
+        &header.mix_hash,
+        &header.nonce,
+    ];
+    let optional: [Option<&dyn Encodable>; 6] = [
+        header.base_fee_per_gas.as_ref().map(|v| v as _),
+        header.withdrawals_root.as_ref().map(|v| v as _),
+        header.blob_gas_used.as_ref().map(|v| v as _),
+        header.excess_blob_gas.as_ref().map(|v| v as _),
+        header.parent_beacon_block_root.as_ref().map(|v| v as _),
+        header.requests_hash.as_ref().map(|v| v as _),
+    ];
+    fields.extend(optional.into_iter().map_while(|field| field));
+
+    let mut out = Vec::new();
+    Header {
+        list: true,
+        payload_length: fields.iter().map(|field| field.length()).sum(),
+    }
+    .encode(&mut out);
+    for field in fields {
+        field.encode(&mut out);
+    }
+    out
+}
+
+/// Decodes the legacy transaction list or the typed transaction envelope.
+fn decode_transaction(buf: &mut &[u8]) -> Result<Transaction, Error> {
+    if Header::decode(&mut &buf[..])?.list {
+        let mut fields = Header::decode_bytes(buf, true)?;
+        let tx = Transaction {
+            tx_type: Some(U64::from(LEGACY_TX_TYPE)),
+            nonce: u64::decode(&mut fields)?,
+            gas_price: Some(U256::decode(&mut fields)?),
+            gas: u64::decode(&mut fields)?,
+            to: decode_to(&mut fields)?,
+            value: U256::decode(&mut fields)?,
+            input: Bytes::decode(&mut fields)?,
+            v: U256::decode(&mut fields)?,
+            r: U256::decode(&mut fields)?,
+            s: U256::decode(&mut fields)?,
+            ..Default::default()
+        };
+        return if fields.is_empty() {
+            Ok(tx)
+        } else {
+            Err(Error::UnexpectedLength)
+        };
+    }
+
+    let mut envelope = Header::decode_bytes(buf, false)?;
+    let (&tx_type, rest) = envelope.split_first().ok_or(Error::InputTooShort)?;
+    envelope = rest;
+    let mut fields = Header::decode_bytes(&mut envelope, true)?;
+    if !envelope.is_empty() {
+        return Err(Error::UnexpectedLength);
+    }
+
+    let mut tx = Transaction {
+        tx_type: Some(U64::from(tx_type)),
+        chain_id: Some(U256::decode(&mut fields)?),
+        nonce: u64::decode(&mut fields)?,
+        ..Default::default()
+    };
+    match tx_type {
+        EIP2930_TX_TYPE => tx.gas_price = Some(U256::decode(&mut fields)?),
+        EIP1559_TX_TYPE | EIP4844_TX_TYPE | EIP7702_TX_TYPE => {
+            tx.max_priority_fee_per_gas = Some(U256::decode(&mut fields)?);
+            tx.max_fee_per_gas = Some(U256::decode(&mut fields)?);
+        }
+        _ => return Err(Error::Custom("unsupported transaction type")),
+    }
+    tx.gas = u64::decode(&mut fields)?;
+    tx.to = decode_to(&mut fields)?;
+    tx.value = U256::decode(&mut fields)?;
+    tx.input = Bytes::decode(&mut fields)?;
+    tx.access_list = Some(
+        Vec::<RlpAccessListItem>::decode(&mut fields)?
+            .into_iter()
+            .map(|item| AccessListItem {
+                address: item.address,
+                storage_keys: item.storage_keys,
+            })
+            .collect(),
+    );
+    match tx_type {
+        EIP4844_TX_TYPE => {
+            tx.max_fee_per_blob_gas = Some(U256::decode(&mut fields)?);
+            tx.blob_versioned_hashes = Vec::decode(&mut fields)?;
+        }
+        EIP7702_TX_TYPE => {
+            tx.authorization_list = Some(
+                Vec::<RlpAuthorization>::decode(&mut fields)?
+                    .into_iter()
+                    .map(|authorization| Authorization {
+                        chain_id: authorization.chain_id,
+                        address: authorization.address,
+                        nonce: authorization.nonce,
+                        y_parity: U64::from(authorization.y_parity),
+                        r: authorization.r,
+                        s: authorization.s,
+                    })
+                    .collect(),
+            )
+        }
+        _ => (),
+    }
+    // Typed transactions are signed with the y parity instead of `v`.
+    tx.v = U256::decode(&mut fields)?;
+    tx.r = U256::decode(&mut fields)?;
+    tx.s = U256::decode(&mut fields)?;
+    if !fields.is_empty() {
+        return Err(Error::UnexpectedLength);
+    }
+    Ok(tx)
+}
+
+/// Decodes the recipient, empty string is the contract creation.
+fn decode_to(buf: &mut &[u8]) -> Result<Option<Address>, Error> {
+    let to = Header::decode_bytes(buf, false)?;
+    match to.len() {
+        0 => Ok(None),
+        20 => Ok(Some(Address::from_slice(to))),
+        _ => Err(Error::UnexpectedLength),
+    }
+}
@@ -0,0 +1,73 @@
+use crate::cmd::{
+    run_tx::models::{Transaction, Withdrawal},
+    statetest::models::{AccountInfo, SpecName},
+};
+use revm::primitives::{
+    alloy_primitives::{Bloom, B64},
+    Address, Bytes, HashMap, B256, U256,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct BlockchainTestSuite(pub BTreeMap<String, BlockchainTest>);
+
+/// Blockchain test of the `BlockchainTests` fixtures.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTest {
+    pub blocks: Vec<Block>,
+    pub genesis_block_header: BlockHeader,
+    pub lastblockhash: B256,
+    pub network: SpecName,
+    pub pre: HashMap<Address, AccountInfo>,
+    pub post_state: Option<HashMap<Address, AccountInfo>>,
+    pub post_state_hash: Option<B256>,
+}
+
+/// Block of the blockchain test.
+///
+/// Blocks that are expected to be invalid may contain only the RLP encoded block.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    pub block_header: Option<BlockHeader>,
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+    #[serde(default)]
+    pub uncle_headers: Vec<BlockHeader>,
+    #[serde(default)]
+    pub withdrawals: Vec<Withdrawal>,
+    pub expect_exception: Option<String>,
+    pub rlp: Option<Bytes>,
+}
+
+/// Block header, fields of the later forks are `None` in the blocks of the earlier forks.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub parent_hash: B256,
+    pub uncle_hash: B256,
+    pub coinbase: Address,
+    pub state_root: B256,
+    pub transactions_trie: B256,
+    pub receipt_trie: B256,
+    pub bloom: Bloom,
+    pub difficulty: U256,
+    pub number: U256,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    pub mix_hash: B256,
+    pub nonce: B64,
+    /// Hash of the header given by the fixture, it is checked against the computed hash.
+    pub hash: Option<B256>,
+    pub base_fee_per_gas: Option<U256>,
+    pub withdrawals_root: Option<B256>,
+    pub blob_gas_used: Option<U256>,
+    pub excess_blob_gas: Option<U256>,
+    pub parent_beacon_block_root: Option<B256>,
+    #[serde(alias = "requestsRoot")]
+    pub requests_hash: Option<B256>,
+}
@@ -0,0 +1,801 @@
+use super::{
+    decode::{decode_block, encode_header},
+    models::{Block, BlockHeader, BlockchainTest, BlockchainTestSuite},
+};
+use crate::cmd::{
+    run_tx::{calc_base_fee, encode_receipt},
+    statetest::{
+        models::{AccountInfo, SpecName},
+        runner::{TestError, TestErrorKind},
+    },
+};
+use revm::{
+    db::{ordered_trie_root, state_merkle_trie_root, EmptyDB, PlainAccount},
+    inspector_handle_register,
+    inspectors::TracerEip3155,
+    primitives::{
+        calc_excess_blob_gas, keccak256, Address, BlockEnv, Bytecode, SpecId, B256, U256,
+    },
+    BlockExecutor, CacheState, Evm, State, Withdrawal,
+};
+use serde_json::json;
+use std::{
+    io::stderr,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// One ether in wei.
+const ETHER: u128 = 1_000_000_000_000_000_000;
+
+/// Base fee of the first London block, see [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+/// Multiplier of the gas target that gives the gas limit since London.
+const ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// Bound divisor of the gas limit change between the parent and the block.
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+
+/// Minimum gas limit of the block.
+const MIN_GAS_LIMIT: u64 = 5000;
+
+/// Returns true if the network of the test is not supported.
+fn skip_network(network: &SpecName) -> bool {
+    matches!(
+        network,
+        // DAO fork irregular state change is not supported.
+        SpecName::HomesteadToDaoAt5
+            | SpecName::ByzantiumToConstantinopleAt5
+            | SpecName::Constantinople
+            | SpecName::Unknown
+    )
+}
+
+/// Returns the specification of the block, handling the transition networks.
+fn spec_id_at(network: &SpecName, number: u64, timestamp: u64) -> SpecId {
+    match network {
+        SpecName::FrontierToHomesteadAt5 if number < 5 => SpecId::FRONTIER,
+        SpecName::HomesteadToEIP150At5 if number < 5 => SpecId::HOMESTEAD,
+        SpecName::EIP158ToByzantiumAt5 if number < 5 => SpecId::SPURIOUS_DRAGON,
+        SpecName::ByzantiumToConstantinopleFixAt5 if number < 5 => SpecId::BYZANTIUM,
+        SpecName::BerlinToLondonAt5 if number < 5 => SpecId::BERLIN,
+        SpecName::MergeToShanghaiAtTime15k if timestamp < 15_000 => SpecId::MERGE,
+        SpecName::ShanghaiToCancunAtTime15k if timestamp < 15_000 => SpecId::SHANGHAI,
+        network => network.to_spec_id(),
+    }
+}
+
+/// Returns the block reward of the miner, `None` after the Merge.
+fn block_reward(spec_id: SpecId) -> Option<u128> {
+    if spec_id.is_enabled_in(SpecId::MERGE) {
+        None
+    } else if spec_id.is_enabled_in(SpecId::PETERSBURG) {
+        Some(2 * ETHER)
+    } else if spec_id.is_enabled_in(SpecId::BYZANTIUM) {
+        Some(3 * ETHER)
+    } else {
+        Some(5 * ETHER)
+    }
+}
+
+/// Returns the hash of the header encoding, checked against the hash given by the fixture.
+fn header_hash(header: &BlockHeader) -> Result<B256, String> {
+    let hash = keccak256(encode_header(header));
+    match header.hash {
+        Some(expected) if expected != hash => Err(format!(
+            "block hash mismatch: got {hash}, expected {expected}"
+        )),
+        _ => Ok(hash),
+    }
+}
+
+/// Checks the header fields that are derived from the parent header.
+fn check_header(
+    network: &SpecName,
+    header: &BlockHeader,
+    parent: &BlockHeader,
+) -> Result<(), String> {
+    let number: u64 = header.number.saturating_to();
+    let spec_id = spec_id_at(network, number, header.timestamp.saturating_to());
+    let parent_spec_id = spec_id_at(
+        network,
+        parent.number.saturating_to(),
+        parent.timestamp.saturating_to(),
+    );
+    if header.number != parent.number + U256::from(1) {
+        return Err(format!(
+            "invalid block number {}, parent number is {}",
+            header.number, parent.number
+        ));
+    }
+    if header.timestamp <= parent.timestamp {
+        return Err(format!(
+            "timestamp {} is not greater than parent timestamp {}",
+            header.timestamp, parent.timestamp
+        ));
+    }
+    if header.gas_used > header.gas_limit {
+        return Err(format!(
+            "gas used {} exceeds gas limit {}",
+            header.gas_used, header.gas_limit
+        ));
+    }
+
+    // Gas target of the first London block is the gas limit of its parent.
+    let london_transition =
+        spec_id.is_enabled_in(SpecId::LONDON) && !parent_spec_id.is_enabled_in(SpecId::LONDON);
+    let parent_gas_limit: u64 = parent.gas_limit.saturating_to();
+    let expected_gas_limit = if london_transition {
+        parent_gas_limit.saturating_mul(ELASTICITY_MULTIPLIER)
+    } else {
+        parent_gas_limit
+    };
+    let gas_limit: u64 = header.gas_limit.saturating_to();
+    if gas_limit.abs_diff(expected_gas_limit) >= expected_gas_limit / GAS_LIMIT_BOUND_DIVISOR
+        || gas_limit < MIN_GAS_LIMIT
+    {
+        return Err(format!(
+            "invalid gas limit {gas_limit}, parent gas limit is {parent_gas_limit}"
+        ));
+    }
+
+    if spec_id.is_enabled_in(SpecId::LONDON) {
+        let expected_base_fee = if london_transition {
+            INITIAL_BASE_FEE
+        } else {
+            calc_base_fee(
+                parent.base_fee_per_gas.unwrap_or_default().saturating_to(),
+                parent.gas_used.saturating_to(),
+                parent_gas_limit,
+            )
+        };
+        if header.base_fee_per_gas != Some(U256::from(expected_base_fee)) {
+            return Err(format!(
+                "base fee mismatch: got {:?}, expected {expected_base_fee}",
+                header.base_fee_per_gas
+            ));
+        }
+    }
+    if spec_id.is_enabled_in(SpecId::CANCUN) {
+        let expected_excess_blob_gas = calc_excess_blob_gas(
+            parent.excess_blob_gas.unwrap_or_default().saturating_to(),
+            parent.blob_gas_used.unwrap_or_default().saturating_to(),
+        );
+        if header.excess_blob_gas != Some(U256::from(expected_excess_blob_gas)) {
+            return Err(format!(
+                "excess blob gas mismatch: got {:?}, expected {expected_excess_blob_gas}",
+                header.excess_blob_gas
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn block_env(header: &BlockHeader) -> BlockEnv {
+    let mut block = BlockEnv {
+        number: header.number,
+        coinbase: header.coinbase,
+        timestamp: header.timestamp,
+        gas_limit: header.gas_limit,
+        basefee: header.base_fee_per_gas.unwrap_or_default(),
+        difficulty: header.difficulty,
+        // after the Merge prevrandao replaces mix_hash field in block header.
+        prevrandao: Some(header.mix_hash),
+        parent_beacon_block_root: header.parent_beacon_block_root,
+        ..Default::default()
+    };
+    if let Some(excess_blob_gas) = header.excess_blob_gas {
+        block.set_blob_excess_gas_and_price(excess_blob_gas.saturating_to());
+    }
+    block
+}
+
+fn state_root<'a>(accounts: impl IntoIterator<Item = (&'a Address, &'a AccountInfo)>) -> B256 {
+    let accounts: Vec<_> = accounts
+        .into_iter()
+        .map(|(address, info)| (*address, plain_account(info.clone())))
+        .collect();
+    state_merkle_trie_root(
+        accounts
+            .iter()
+            .map(|(address, account)| (*address, account)),
+    )
+}
+
+fn plain_account(info: AccountInfo) -> PlainAccount {
+    PlainAccount {
+        info: revm::primitives::AccountInfo {
+            balance: info.balance,
+            code_hash: keccak256(&info.code),
+            code: Some(Bytecode::new_raw(info.code)),
+            nonce: info.nonce,
+        },
+        storage: info.storage.into_iter().collect(),
+    }
+}
+
+/// Executes the blocks of the test and checks the resulting state.
+///
+/// Blocks that are expected to be invalid are decoded from their RLP and executed, the test
+/// fails if such block is not rejected. State changes of the rejected blocks are discarded.
+/// Receipts roots of pre-Byzantium blocks are not checked, as their receipts contain
+/// intermediate state roots.
+pub fn execute_test<EXT>(
+    test: &BlockchainTest,
+    mut executor: BlockExecutor<'_, EXT, EmptyDB>,
+) -> Result<(), TestErrorKind> {
+    // for mainnet
+    executor.evm.cfg_mut().chain_id = 1;
+    let genesis = &test.genesis_block_header;
+    let genesis_number = genesis.number.saturating_to();
+    let genesis_hash = header_hash(genesis).map_err(|error| TestErrorKind::BlockExecution {
+        number: genesis_number,
+        error,
+    })?;
+    executor
+        .evm
+        .db_mut()
+        .block_hashes
+        .insert(genesis_number, genesis_hash);
+
+    let mut parent = genesis;
+    let mut last_block_hash = genesis_hash;
+    for block in &test.blocks {
+        if let Some(expected_exception) = &block.expect_exception {
+            let state = executor.evm.db();
+            let snapshot = (
+                state.cache.clone(),
+                state.transition_state.clone(),
+                state.bundle_state.clone(),
+                state.block_hashes.clone(),
+            );
+            // Block without the RLP can not be imported.
+            let rejected = match decode_block(block.rlp.as_ref().map_or(&[], |rlp| &rlp[..])) {
+                Ok(decoded) => execute_block(
+                    &test.network,
+                    &mut executor,
+                    &decoded,
+                    parent,
+                    last_block_hash,
+                )
+                .is_err(),
+                Err(_) => true,
+            };
+            let state = executor.evm.db_mut();
+            (
+                state.cache,
+                state.transition_state,
+                state.bundle_state,
+                state.block_hashes,
+            ) = snapshot;
+
+            if !rejected {
+                return Err(TestErrorKind::UnexpectedException {
+                    expected_exception: Some(expected_exception.clone()),
+                    got_exception: None,
+                });
+            }
+            continue;
+        }
+        if let Some(header) = &block.block_header {
+            last_block_hash =
+                execute_block(&test.network, &mut executor, block, parent, last_block_hash)?;
+            parent = header;
+        }
+    }
+
+    if last_block_hash != test.lastblockhash {
+        return Err(TestErrorKind::LastBlockHashMismatch {
+            got: last_block_hash,
+            expected: test.lastblockhash,
+        });
+    }
+    let expected_state_root = match (&test.post_state_hash, &test.post_state) {
+        (Some(hash), _) => *hash,
+        (None, Some(post_state)) => state_root(post_state),
+        (None, None) => return Ok(()),
+    };
+    let state_root = state_merkle_trie_root(executor.evm.db().cache.trie_account());
+    if state_root != expected_state_root {
+        return Err(TestErrorKind::StateRootMismatch {
+            got: state_root,
+            expected: expected_state_root,
+        });
+    }
+    Ok(())
+}
+
+/// Executes the block on top of the parent block, checks the header and returns the block hash.
+fn execute_block<EXT>(
+    network: &SpecName,
+    executor: &mut BlockExecutor<'_, EXT, EmptyDB>,
+    block: &Block,
+    parent: &BlockHeader,
+    parent_hash: B256,
+) -> Result<B256, TestErrorKind> {
+    let Some(header) = &block.block_header else {
+        return Ok(parent_hash);
+    };
+    let number = header.number.saturating_to::<u64>();
+    let spec_id = spec_id_at(network, number, header.timestamp.saturating_to());
+    executor.evm.modify_spec_id(spec_id);
+
+    let block_error = |error: String| TestErrorKind::BlockExecution { number, error };
+    let hash = header_hash(header).map_err(block_error)?;
+    if header.parent_hash != parent_hash {
+        return Err(block_error(format!(
+            "parent hash mismatch: got {parent_hash}, expected {}",
+            header.parent_hash
+        )));
+    }
+    check_header(network, header, parent).map_err(block_error)?;
+    let chain_id = executor.evm.cfg().chain_id;
+    let transactions_root = ordered_trie_root(
+        block
+            .transactions
+            .iter()
+            .map(|tx| tx.encode(chain_id))
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(block_error)?,
+    );
+    if transactions_root != header.transactions_trie {
+        return Err(block_error(format!(
+            "transactions root mismatch: got {transactions_root}, expected {}",
+            header.transactions_trie
+        )));
+    }
+    let transactions = block
+        .transactions
+        .iter()
+        .map(|tx| Ok(tx.to_tx_env(tx.sender(chain_id, spec_id)?, chain_id)))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(block_error)?;
+    let withdrawals: Vec<_> = block
+        .withdrawals
+        .iter()
+        .map(|withdrawal| Withdrawal {
+            index: withdrawal.index,
+            validator_index: withdrawal.validator_index,
+            address: withdrawal.address,
+            amount: withdrawal.amount,
+        })
+        .collect();
+
+    let output = executor
+        .execute_block(block_env(header), transactions, &withdrawals)
+        .map_err(|e| block_error(e.to_string()))?;
+
+    if let Some(reward) = block_reward(spec_id) {
+        let uncles_reward = block
+            .uncle_headers
+            .iter()
+            .map(|uncle| {
+                // Uncle has to be an ancestor of the block up to six generations back.
+                let depth = number
+                    .checked_sub(uncle.number.saturating_to())
+                    .filter(|depth| (1..=6).contains(depth))
+                    .ok_or_else(|| {
+                        block_error(format!("invalid uncle block number {}", uncle.number))
+                    })?;
+                Ok((uncle.coinbase, reward * (8 - depth as u128) / 8))
+            })
+            .collect::<Result<Vec<_>, TestErrorKind>>()?;
+        let miner_reward = reward + reward / 32 * block.uncle_headers.len() as u128;
+        executor
+            .evm
+            .db_mut()
+            .increment_balances(
+                uncles_reward
+                    .into_iter()
+                    .chain([(header.coinbase, miner_reward)]),
+            )
+            .map_err(|e| block_error(e.to_string()))?;
+    }
+
+    if output.logs_bloom != header.bloom {
+        return Err(block_error("logs bloom mismatch".to_string()));
+    }
+    if header
+        .blob_gas_used
+        .is_some_and(|blob_gas_used| blob_gas_used != U256::from(output.blob_gas_used))
+    {
+        return Err(block_error(format!(
+            "blob gas used mismatch: got {}",
+            output.blob_gas_used
+        )));
+    }
+    let expected_gas_used = header.gas_used.saturating_to();
+    if output.gas_used != expected_gas_used {
+        return Err(TestErrorKind::BlockGasUsedMismatch {
+            got: output.gas_used,
+            expected: expected_gas_used,
+        });
+    }
+    if spec_id.is_enabled_in(SpecId::BYZANTIUM) {
+        let receipts_root = ordered_trie_root(output.receipts.iter().zip(&block.transactions).map(
+            |(receipt, tx)| {
+                encode_receipt(
+                    // Type is validated by the sender recovery.
+                    tx.tx_type().unwrap_or_default(),
+                    receipt.success,
+                    receipt.cumulative_gas_used,
+                    &receipt.logs_bloom,
+                    &receipt.logs,
+                )
+            },
+        ));
+        if receipts_root != header.receipt_trie {
+            return Err(TestErrorKind::ReceiptsRootMismatch {
+                got: receipts_root,
+                expected: header.receipt_trie,
+            });
+        }
+    }
+    let state_root = state_merkle_trie_root(executor.evm.db().cache.trie_account());
+    if state_root != header.state_root {
+        return Err(TestErrorKind::StateRootMismatch {
+            got: state_root,
+            expected: header.state_root,
+        });
+    }
+
+    executor.evm.db_mut().block_hashes.insert(number, hash);
+    Ok(hash)
+}
+
+/// Executes all tests of the `BlockchainTests` file.
+pub fn execute_test_suite(
+    path: &Path,
+    elapsed: &Arc<Mutex<Duration>>,
+    trace: bool,
+    print_outcome: bool,
+) -> Result<(), TestError> {
+    let suite: BlockchainTestSuite = std::fs::read_to_string(path)
+        .map_err(TestErrorKind::from)
+        .and_then(|s| serde_json::from_str(&s).map_err(TestErrorKind::from))
+        .map_err(|kind| TestError {
+            name: path.to_string_lossy().into_owned(),
+            kind,
+        })?;
+
+    for (name, test) in suite.0 {
+        if skip_network(&test.network) {
+            continue;
+        }
+
+        let mut cache_state = CacheState::new(false);
+        for (address, info) in &test.pre {
+            let account = plain_account(info.clone());
+            cache_state.insert_account_with_storage(*address, account.info, account.storage);
+        }
+        let state = State::builder()
+            .with_cached_prestate(cache_state)
+            .with_bundle_update()
+            .build();
+
+        let timer = Instant::now();
+        let result = if trace {
+            let evm = Evm::builder()
+                .with_db(state)
+                .with_external_context(TracerEip3155::new(Box::new(stderr())).without_summary())
+                .append_handler_register(inspector_handle_register)
+                .build();
+            execute_test(&test, BlockExecutor::new(evm))
+        } else {
+            let evm = Evm::builder().with_db(state).build();
+            execute_test(&test, BlockExecutor::new(evm))
+        };
+        *elapsed.lock().unwrap() += timer.elapsed();
+
+        if print_outcome {
+            let error = result.as_ref().err().map(ToString::to_string);
+            let outcome = json!({
+                "name": name,
+                "pass": result.is_ok(),
+                "network": format!("{:?}", test.network),
+                "error": error,
+            });
+            println!("{outcome}");
+        }
+        result.map_err(|kind| TestError { name, kind })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::EMPTY_ROOT_HASH,
+        primitives::{
+            alloy_primitives::{Bloom, B64},
+            Bytes,
+        },
+    };
+
+    const SENDER: &str = "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b";
+    const ZERO: B256 = B256::ZERO;
+    /// Root of the transfer transaction of the test block.
+    const TRANSACTIONS_ROOT: &str =
+        "0x103159536e452bc35f31d26d1625200c3fca3358107944b2a586d62fd455ba0f";
+
+    fn test_json(state_root: &str, invalid_block_rlp: &str) -> String {
+        format!(
+            r#"{{
+            "transfer": {{
+                "network": "Cancun",
+                "genesisBlockHeader": {{
+                    "parentHash": "{ZERO}",
+                    "uncleHash": "{ZERO}",
+                    "coinbase": "0x0000000000000000000000000000000000000000",
+                    "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "transactionsTrie": "{EMPTY_ROOT_HASH}",
+                    "receiptTrie": "{EMPTY_ROOT_HASH}",
+                    "bloom": "0x{bloom}",
+                    "difficulty": "0x00",
+                    "number": "0x00",
+                    "gasLimit": "0x016345785d8a0000",
+                    "gasUsed": "0x00",
+                    "timestamp": "0x00",
+                    "extraData": "0x",
+                    "mixHash": "{ZERO}",
+                    "nonce": "0x0000000000000000",
+                    "baseFeePerGas": "0x07"
+                }},
+                "blocks": [
+                    {{
+                        "blockHeader": {{
+                            "parentHash": "{ZERO}",
+                            "uncleHash": "{ZERO}",
+                            "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                            "stateRoot": "{state_root}",
+                            "transactionsTrie": "{TRANSACTIONS_ROOT}",
+                            "receiptTrie": "0x056b23fbba480696b65fe5a59b8f2148a1299103c4f57df839233af2cf4ca2d2",
+                            "bloom": "0x{bloom}",
+                            "difficulty": "0x00",
+                            "number": "0x01",
+                            "gasLimit": "0x016345785d8a0000",
+                            "gasUsed": "0x5208",
+                            "timestamp": "0x0c",
+                            "extraData": "0x",
+                            "mixHash": "{ZERO}",
+                            "nonce": "0x0000000000000000",
+                            "baseFeePerGas": "0x07",
+                            "withdrawalsRoot": "{EMPTY_ROOT_HASH}",
+                            "blobGasUsed": "0x00",
+                            "excessBlobGas": "0x00",
+                            "parentBeaconBlockRoot": "{ZERO}"
+                        }},
+                        "transactions": [
+                            {{
+                                "type": "0x00",
+                                "nonce": "0x00",
+                                "gasPrice": "0x0a",
+                                "gasLimit": "0x5208",
+                                "to": "0x1000000000000000000000000000000000000000",
+                                "value": "0x01",
+                                "data": "0x",
+                                "v": "0x1b",
+                                "r": "0x01",
+                                "s": "0x01",
+                                "sender": "{SENDER}"
+                            }}
+                        ],
+                        "uncleHeaders": [],
+                        "withdrawals": [
+                            {{
+                                "index": "0x00",
+                                "validatorIndex": "0x00",
+                                "address": "0x3000000000000000000000000000000000000000",
+                                "amount": "0x01"
+                            }}
+                        ]
+                    }},
+                    {{
+                        "expectException": "TransactionException.INTRINSIC_GAS_TOO_LOW",
+                        "rlp": "{invalid_block_rlp}"
+                    }}
+                ],
+                "lastblockhash": "{ZERO}",
+                "pre": {{
+                    "{SENDER}": {{
+                        "balance": "0x3635c9adc5dea00000",
+                        "code": "0x",
+                        "nonce": "0x00",
+                        "storage": {{}}
+                    }}
+                }},
+                "postState": {{
+                    "{SENDER}": {{
+                        "balance": "0x3635c9adc5de9ccbaf",
+                        "code": "0x",
+                        "nonce": "0x01",
+                        "storage": {{}}
+                    }},
+                    "0x1000000000000000000000000000000000000000": {{
+                        "balance": "0x01",
+                        "code": "0x",
+                        "nonce": "0x00",
+                        "storage": {{}}
+                    }},
+                    "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba": {{
+                        "balance": "0xf618",
+                        "code": "0x",
+                        "nonce": "0x00",
+                        "storage": {{}}
+                    }},
+                    "0x3000000000000000000000000000000000000000": {{
+                        "balance": "0x3b9aca00",
+                        "code": "0x",
+                        "nonce": "0x00",
+                        "storage": {{}}
+                    }}
+                }}
+            }}
+        }}"#,
+            bloom = "00".repeat(256),
+        )
+    }
+
+    /// Parses the test and links its block to the genesis block with the computed hashes.
+    fn parse(state_root: &str, invalid_block_rlp: &str) -> BlockchainTest {
+        let mut suite: BlockchainTestSuite =
+            serde_json::from_str(&test_json(state_root, invalid_block_rlp)).unwrap();
+        let mut test = suite.0.remove("transfer").unwrap();
+        let header = test.blocks[0].block_header.as_mut().unwrap();
+        header.parent_hash = header_hash(&test.genesis_block_header).unwrap();
+        test.lastblockhash = header_hash(header).unwrap();
+        test
+    }
+
+    fn run(state_root: &str, invalid_block_rlp: &str) -> Result<(), TestErrorKind> {
+        execute(&parse(state_root, invalid_block_rlp))
+    }
+
+    fn execute(test: &BlockchainTest) -> Result<(), TestErrorKind> {
+        let mut cache_state = CacheState::new(false);
+        for (address, info) in &test.pre {
+            let account = plain_account(info.clone());
+            cache_state.insert_account_with_storage(*address, account.info, account.storage);
+        }
+        let state = State::builder()
+            .with_cached_prestate(cache_state)
+            .with_bundle_update()
+            .build();
+        execute_test(
+            test,
+            BlockExecutor::new(Evm::builder().with_db(state).build()),
+        )
+    }
+
+    #[test]
+    fn execute_blocks() {
+        let test = parse(&B256::ZERO.to_string(), "0x00");
+        let expected_state_root = state_root(test.post_state.as_ref().unwrap());
+
+        run(&expected_state_root.to_string(), "0x00").unwrap();
+
+        assert!(matches!(
+            run(&B256::ZERO.to_string(), "0x00"),
+            Err(TestErrorKind::StateRootMismatch { expected, got })
+                if expected == B256::ZERO && got == expected_state_root
+        ));
+    }
+
+    /// Encodes the empty block on top of the block of the test.
+    fn empty_block_rlp(parent_hash: B256, state_root: B256, gas_used: u64) -> String {
+        let header = BlockHeader {
+            parent_hash,
+            uncle_hash: B256::ZERO,
+            coinbase: Address::ZERO,
+            state_root,
+            transactions_trie: EMPTY_ROOT_HASH,
+            receipt_trie: EMPTY_ROOT_HASH,
+            bloom: Bloom::ZERO,
+            difficulty: U256::ZERO,
+            number: U256::from(2),
+            gas_limit: U256::from(0x016345785d8a0000u64),
+            gas_used: U256::from(gas_used),
+            timestamp: U256::from(0x18),
+            extra_data: Bytes::new(),
+            mix_hash: B256::ZERO,
+            nonce: B64::ZERO,
+            hash: None,
+            base_fee_per_gas: Some(U256::from(7)),
+            withdrawals_root: Some(EMPTY_ROOT_HASH),
+            blob_gas_used: Some(U256::ZERO),
+            excess_blob_gas: Some(U256::ZERO),
+            parent_beacon_block_root: Some(B256::ZERO),
+            requests_hash: None,
+        };
+        let encoded_header = encode_header(&header);
+
+        // Empty transactions, uncles and withdrawals.
+        let empty_list = alloy_rlp::EMPTY_LIST_CODE;
+        let mut block = Vec::new();
+        alloy_rlp::Header {
+            list: true,
+            payload_length: encoded_header.len() + 3,
+        }
+        .encode(&mut block);
+        block.extend(encoded_header);
+        block.extend([empty_list; 3]);
+        Bytes::from(block).to_string()
+    }
+
+    #[test]
+    fn invalid_blocks() {
+        let test = parse(&B256::ZERO.to_string(), "0x00");
+        let expected_state_root = state_root(test.post_state.as_ref().unwrap());
+        let parent_hash = parse(&expected_state_root.to_string(), "0x00").lastblockhash;
+        let run =
+            |invalid_block_rlp: &str| run(&expected_state_root.to_string(), invalid_block_rlp);
+
+        run(&empty_block_rlp(parent_hash, expected_state_root, 1)).unwrap();
+        run(&empty_block_rlp(parent_hash, B256::ZERO, 0)).unwrap();
+        run(&empty_block_rlp(B256::ZERO, expected_state_root, 0)).unwrap();
+        assert!(matches!(
+            run(&empty_block_rlp(parent_hash, expected_state_root, 0)),
+            Err(TestErrorKind::UnexpectedException {
+                expected_exception: Some(_),
+                got_exception: None,
+            })
+        ));
+    }
+
+    #[test]
+    fn header_checks() {
+        let expected_state_root = state_root(
+            parse(&B256::ZERO.to_string(), "0x00")
+                .post_state
+                .as_ref()
+                .unwrap(),
+        )
+        .to_string();
+        let run_with = |modify: fn(&mut BlockHeader)| {
+            let mut test = parse(&expected_state_root, "0x00");
+            modify(test.blocks[0].block_header.as_mut().unwrap());
+            execute(&test)
+        };
+        let is_block_error = |result: Result<(), TestErrorKind>, message: &str| {
+            matches!(result, Err(TestErrorKind::BlockExecution { number: 1, error })
+                if error.starts_with(message))
+        };
+
+        assert!(is_block_error(
+            run_with(|header| header.hash = Some(B256::ZERO)),
+            "block hash mismatch"
+        ));
+        assert!(is_block_error(
+            run_with(|header| header.timestamp = U256::ZERO),
+            "timestamp"
+        ));
+        assert!(is_block_error(
+            run_with(|header| header.gas_limit *= U256::from(2)),
+            "invalid gas limit"
+        ));
+        assert!(is_block_error(
+            run_with(|header| header.base_fee_per_gas = Some(U256::from(8))),
+            "base fee mismatch"
+        ));
+        assert!(is_block_error(
+            run_with(|header| header.excess_blob_gas = Some(U256::from(1))),
+            "excess blob gas mismatch"
+        ));
+        assert!(is_block_error(
+            run_with(|header| header.transactions_trie = EMPTY_ROOT_HASH),
+            "transactions root mismatch"
+        ));
+    }
+
+    #[test]
+    fn transition_networks() {
+        let network = SpecName::ShanghaiToCancunAtTime15k;
+        assert_eq!(spec_id_at(&network, 1, 14_999), SpecId::SHANGHAI);
+        assert_eq!(spec_id_at(&network, 2, 15_000), SpecId::CANCUN);
+        assert_eq!(block_reward(SpecId::LONDON), Some(2 * ETHER));
+        assert_eq!(block_reward(SpecId::MERGE), None);
+    }
+}
@@ -1,7 +1,11 @@
 use revm::{
-    interpreter::opcode::eof_printer::print_eof_code,
-    primitives::{Bytes, Eof},
+    interpreter::opcode::{
+        eof_assembly::{assemble_eof, print_eof},
+        eof_printer::print_eof_code,
+    },
+    primitives::{hex, Bytes, Eof},
 };
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 /// Statetest command
@@ -9,14 +13,35 @@ use structopt::StructOpt;
 pub struct Cmd {
     /// EOF bytecode in hex format. It bytes start with 0xFE it will be interpreted as a EOF.
     /// Otherwise, it will be interpreted as a EOF bytecode.
-    #[structopt(required = true)]
-    bytes: String,
+    ///
+    /// EOF containers are printed in the assembly format accepted by `--assemble`.
+    #[structopt(required_unless = "assemble")]
+    bytes: Option<String>,
+    /// Path to the file with EOF container in the assembly format.
+    /// Prints the assembled container in hex format.
+    #[structopt(long, conflicts_with = "bytes")]
+    assemble: Option<PathBuf>,
 }
 
 impl Cmd {
     /// Run statetest command.
     pub fn run(&self) {
-        let trimmed = self.bytes.trim_start_matches("0x");
+        if let Some(path) = &self.assemble {
+            let Ok(source) = std::fs::read_to_string(path) else {
+                eprintln!("Can't read assembly file");
+                return;
+            };
+            match assemble_eof(&source) {
+                Ok(eof) => println!("{}", hex::encode_prefixed(eof.raw)),
+                Err(e) => eprintln!("Invalid EOF assembly: {e}"),
+            }
+            return;
+        }
+        let trimmed = self
+            .bytes
+            .as_deref()
+            .unwrap_or_default()
+            .trim_start_matches("0x");
         let Ok(bytes) = hex::decode(trimmed) else {
             eprintln!("Invalid hex string");
             return;
@@ -31,7 +56,7 @@ impl Cmd {
                 eprintln!("Invalid EOF bytecode");
                 return;
             };
-            println!("{:#?}", eof);
+            print!("{}", print_eof(&eof));
         } else {
             print_eof_code(&bytes)
         }
@@ -0,0 +1,277 @@
+mod test_suite;
+
+pub use test_suite::{TestResult, TestSuite, TestUnit, TestVector};
+
+use crate::cmd::statetest::runner::find_all_json_tests;
+use revm::{
+    interpreter::analysis::{validate_raw_eof, EofError, EofValidationError},
+    primitives::eof::EofDecodeError,
+};
+use std::{io::Error as IoError, path::Path};
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("Invalid hex or path: {0}")]
+    InvalidInput(String),
+    #[error("Invalid EOF test suite {path}: {error}")]
+    InvalidTestSuite {
+        path: String,
+        error: serde_json::Error,
+    },
+    #[error("{failed} out of {total} EOF validation results do not match")]
+    Mismatch { failed: usize, total: usize },
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+/// `eof-validate` command.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// EOF containers in hex format or paths to folders or files containing `EOFTests`
+    /// fixtures.
+    ///
+    /// Folders will be searched recursively for files with the extension `.json`.
+    #[structopt(required = true)]
+    inputs: Vec<String>,
+    /// Print the validation result of every container and fork, not only mismatches.
+    #[structopt(short = "v", long)]
+    verbose: bool,
+}
+
+impl Cmd {
+    /// Run eof-validate command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let mut failed = 0;
+        let mut total = 0;
+        for input in &self.inputs {
+            let path = Path::new(input);
+            if path.exists() {
+                for test_file in find_all_json_tests(path) {
+                    let (file_failed, file_total) = self.run_test_file(&test_file)?;
+                    failed += file_failed;
+                    total += file_total;
+                }
+            } else {
+                let bytes = hex::decode(input.trim().trim_start_matches("0x"))
+                    .map_err(|_| Errors::InvalidInput(input.clone()))?;
+                println!("{input}: {}", outcome(&validate_raw_eof(bytes.into())));
+            }
+        }
+        if total != 0 {
+            println!("Passed: {}/{total}", total - failed);
+        }
+        if failed != 0 {
+            return Err(Errors::Mismatch { failed, total });
+        }
+        Ok(())
+    }
+
+    /// Validates all containers of the fixture file against the expected result and exception
+    /// of every fork, returning the number of mismatches and the number of checked results.
+    fn run_test_file(&self, path: &Path) -> Result<(usize, usize), Errors> {
+        let s = std::fs::read_to_string(path)?;
+        let suite: TestSuite =
+            serde_json::from_str(&s).map_err(|error| Errors::InvalidTestSuite {
+                path: path.display().to_string(),
+                error,
+            })?;
+
+        let mut failed = 0;
+        let mut total = 0;
+        for (name, unit) in suite.0 {
+            for (vector_name, vector) in unit.vectors {
+                let result = validate_raw_eof(vector.code.clone());
+                for (fork, expected) in &vector.results {
+                    total += 1;
+                    let pass = matches_expected(&result, expected);
+                    if !pass {
+                        failed += 1;
+                    }
+                    if !pass || self.verbose {
+                        println!(
+                            "{} {name}/{vector_name} {fork}: {}, expected {}",
+                            if pass { "PASS" } else { "FAIL" },
+                            outcome(&result),
+                            expected_outcome(expected),
+                        );
+                    }
+                }
+            }
+        }
+        Ok((failed, total))
+    }
+}
+
+/// Returns true if the validation result is the expected one.
+///
+/// Error of an invalid container has to be mapped to the expected exception, if the fixture
+/// names one.
+fn matches_expected<T>(result: &Result<T, EofError>, expected: &TestResult) -> bool {
+    match (result, &expected.exception) {
+        (Ok(_), _) => expected.result,
+        (Err(_), _) if expected.result => false,
+        (Err(error), Some(exception)) => exception_names(error).contains(&exception.as_str()),
+        (Err(_), None) => true,
+    }
+}
+
+/// Returns the names of the fixture exceptions that the error corresponds to.
+///
+/// Some errors are reported by several exceptions, as the fixtures distinguish the cases
+/// that the decoder and the validator report with the same error.
+fn exception_names(error: &EofError) -> &'static [&'static str] {
+    match error {
+        EofError::Decode(error) => match error {
+            EofDecodeError::MissingInput => &[
+                "EOF_InvalidPrefix",
+                "EOF_UnknownVersion",
+                "EOF_IncompleteSectionNumber",
+                "EOF_IncompleteSectionSize",
+                "EOF_SectionHeadersNotTerminated",
+                "EOF_HeaderTerminatorMissing",
+                "EOF_InvalidSectionBodiesSize",
+                "EOF_TypeSectionMissing",
+                "EOF_CodeSectionMissing",
+                "EOF_DataSectionMissing",
+            ],
+            EofDecodeError::MissingBodyWithoutData | EofDecodeError::DanglingData => {
+                &["EOF_InvalidSectionBodiesSize"]
+            }
+            // Decoder checks the limits of the types section values.
+            EofDecodeError::InvalidTypesSection => &[
+                "EOF_InvalidFirstSectionType",
+                "EOF_InputsOutputsNumAboveLimit",
+                "EOF_MaxStackHeightExceeded",
+            ],
+            EofDecodeError::InvalidTypesSectionSize | EofDecodeError::MismatchCodeAndTypesSize => {
+                &["EOF_InvalidTypeSectionSize"]
+            }
+            EofDecodeError::InvalidEOFMagicNumber => &["EOF_InvalidMagic", "EOF_InvalidPrefix"],
+            EofDecodeError::InvalidEOFVersion => &["EOF_InvalidVersion", "EOF_UnknownVersion"],
+            EofDecodeError::InvalidTypesKind => &["EOF_TypeSectionMissing"],
+            EofDecodeError::InvalidCodeKind => &["EOF_CodeSectionMissing"],
+            EofDecodeError::InvalidTerminalByte => &["EOF_HeaderTerminatorMissing"],
+            EofDecodeError::InvalidDataKind | EofDecodeError::InvalidKindAfterCode => {
+                &["EOF_DataSectionMissing"]
+            }
+            EofDecodeError::ShortInputForSizes => &[
+                "EOF_IncompleteSectionSize",
+                "EOF_SectionHeadersNotTerminated",
+            ],
+            EofDecodeError::NonSizes
+            | EofDecodeError::ZeroSize
+            | EofDecodeError::ZeroCodeSections => &["EOF_ZeroSectionSize"],
+            EofDecodeError::TooManyCodeSections => &["EOF_TooManyCodeSections"],
+            EofDecodeError::TooManyContainerSections => &["EOF_TooManyContainerSections"],
+        },
+        EofError::Validation(error) => match error {
+            EofValidationError::FalsePossitive => &[],
+            EofValidationError::UnknownOpcode | EofValidationError::OpcodeDisabled => {
+                &["EOF_UndefinedInstruction"]
+            }
+            // Jump into the middle of the unreachable code is reported as not accessed code.
+            EofValidationError::InstructionNotForwardAccessed => {
+                &["EOF_UnreachableCode", "EOF_InvalidJumpDestination"]
+            }
+            EofValidationError::MissingImmediateBytes
+            | EofValidationError::MissingRJUMPVImmediateBytes => &["EOF_TruncatedImmediate"],
+            EofValidationError::JumpToImmediateBytes
+            | EofValidationError::BackwardJumpToImmediateBytes
+            | EofValidationError::RJUMPVZeroMaxIndex
+            | EofValidationError::JumpZeroOffset
+            | EofValidationError::JumpUnderflow
+            | EofValidationError::JumpOverflow => &["EOF_InvalidJumpDestination"],
+            EofValidationError::EOFCREATEInvalidIndex => &["EOF_InvalidContainerSectionIndex"],
+            EofValidationError::CodeSectionOutOfBounds => &["EOF_InvalidCodeSectionIndex"],
+            EofValidationError::CALLFNonReturningFunction => &["EOF_CallfToNonReturningFunction"],
+            EofValidationError::StackOverflow => {
+                &["EOF_StackOverflow", "EOF_MaxStackHeightExceeded"]
+            }
+            EofValidationError::JUMPFEnoughOutputs
+            | EofValidationError::JUMPFStackHigherThanOutputs => &[
+                "EOF_JumpfDestinationIncompatibleOutputs",
+                "EOF_InvalidNumberOfOutputs",
+            ],
+            EofValidationError::DataLoadOutOfBounds => &["EOF_InvalidDataloadnIndex"],
+            EofValidationError::RETFBiggestStackNumMoreThenOutputs => {
+                &["EOF_InvalidNumberOfOutputs"]
+            }
+            EofValidationError::StackUnderflow | EofValidationError::TypesStackUnderflow => {
+                &["EOF_StackUnderflow"]
+            }
+            EofValidationError::BackwardJumpBiggestNumMismatch
+            | EofValidationError::BackwardJumpSmallestNumMismatch => {
+                &["EOF_ConflictingStackHeight"]
+            }
+            EofValidationError::LastInstructionNotTerminating => &["EOF_InvalidCodeTermination"],
+            EofValidationError::CodeSectionNotAccessed => &["EOF_UnreachableCodeSections"],
+            EofValidationError::InvalidTypesSection => &[
+                "EOF_InvalidFirstSectionType",
+                "EOF_InputsOutputsNumAboveLimit",
+                "EOF_InvalidMaxStackHeight",
+            ],
+            EofValidationError::InvalidFirstTypesSection => &["EOF_InvalidFirstSectionType"],
+            EofValidationError::MaxStackMismatch => &["EOF_InvalidMaxStackHeight"],
+            EofValidationError::NoCodeSections => &["EOF_CodeSectionMissing"],
+        },
+    }
+}
+
+fn outcome<T>(result: &Result<T, EofError>) -> String {
+    match result {
+        Ok(_) => "valid".to_string(),
+        Err(error) => format!("invalid ({error:?})"),
+    }
+}
+
+fn expected_outcome(expected: &TestResult) -> String {
+    match (&expected.exception, expected.result) {
+        (_, true) => "valid".to_string(),
+        (Some(exception), false) => format!("invalid ({exception})"),
+        (None, false) => "invalid".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::Bytes;
+    use std::path::PathBuf;
+
+    #[test]
+    fn eip3540_fixtures() {
+        let cmd = Cmd {
+            inputs: Vec::new(),
+            verbose: false,
+        };
+        let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../crates/interpreter/tests/EOFTests/EIP3540");
+        let mut total = 0;
+        for test_file in find_all_json_tests(&fixtures) {
+            let (failed, file_total) = cmd.run_test_file(&test_file).unwrap();
+            assert_eq!(failed, 0, "{}", test_file.display());
+            total += file_total;
+        }
+        assert_ne!(total, 0);
+    }
+
+    #[test]
+    fn compare_exceptions() {
+        let expected = |result: bool, exception: Option<&str>| TestResult {
+            result,
+            exception: exception.map(ToString::to_string),
+        };
+        let result = validate_raw_eof(Bytes::from_static(&[0xEF, 0x00, 0x02]));
+        assert!(matches_expected(
+            &result,
+            &expected(false, Some("EOF_UnknownVersion"))
+        ));
+        assert!(matches_expected(&result, &expected(false, None)));
+        assert!(!matches_expected(
+            &result,
+            &expected(false, Some("EOF_InvalidMagic"))
+        ));
+        assert!(!matches_expected(&result, &expected(true, None)));
+    }
+}
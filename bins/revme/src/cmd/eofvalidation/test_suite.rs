@@ -0,0 +1,28 @@
+use revm::primitives::Bytes;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Test suite of the `EOFTests` fixtures.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct TestSuite(pub BTreeMap<String, TestUnit>);
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct TestUnit {
+    /// Test info is optional
+    #[serde(default, rename = "_info")]
+    pub info: Option<serde_json::Value>,
+    pub vectors: BTreeMap<String, TestVector>,
+}
+
+/// EOF container with the expected validation result per fork.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct TestVector {
+    pub code: Bytes,
+    pub results: BTreeMap<String, TestResult>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct TestResult {
+    pub result: bool,
+    pub exception: Option<String>,
+}
@@ -1,10 +1,17 @@
+mod debugger;
+
+pub use debugger::{Breakpoint, Debugger};
+
+use crate::cmd::statetest::models::AccountInfo;
 use revm::{
-    db::BenchmarkDB,
+    db::{BenchmarkDB, CacheDB},
     inspector_handle_register,
-    inspectors::TracerEip3155,
-    primitives::{Address, Bytecode, TransactTo},
-    Evm,
+    inspectors::{FlameGraphWeight, Profiler, TracerEip3155},
+    interpreter::opcode::{assembly::assemble, OpCode},
+    primitives::{keccak256, Address, Bytecode, Bytes, HashMap, TransactTo},
+    Database, Evm,
 };
+use std::fmt;
 use std::io::Error as IoError;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -21,8 +28,12 @@ pub enum Errors {
     InvalidBytecode,
     #[error("Invalid input")]
     InvalidInput,
+    #[error("Invalid pre-state: {0}")]
+    InvalidPrestate(serde_json::Error),
     #[error("EVM Error")]
     EVMError,
+    #[error("Invalid assembly: {0}")]
+    InvalidAssembly(String),
     #[error(transparent)]
     Io(IoError),
 }
@@ -44,6 +55,10 @@ pub struct Cmd {
     /// Overrides the bytecode option.
     #[structopt(long)]
     path: Option<PathBuf>,
+    /// Bytecode is in the assembly format instead of hex, with labels, `PUSH`
+    /// auto-sizing and `data` sections.
+    #[structopt(long)]
+    asm: bool,
     /// Run in benchmarking mode.
     #[structopt(long)]
     bench: bool,
@@ -56,6 +71,20 @@ pub struct Cmd {
     /// Print the trace.
     #[structopt(long)]
     trace: bool,
+    /// Run in the interactive step debugger.
+    #[structopt(long)]
+    debug: bool,
+    /// Print the gas and time profile per opcode and per contract.
+    #[structopt(long)]
+    profile: bool,
+    /// Path of the folded stacks file weighted by gas, for flamegraph tools.
+    /// Implies `--profile`.
+    #[structopt(long)]
+    flamegraph: Option<PathBuf>,
+    /// Path to JSON file with the pre-state accounts, in the state test `pre` format.
+    /// Accounts are added on top of the bytecode deployed at zero address.
+    #[structopt(long)]
+    prestate: Option<PathBuf>,
 }
 
 impl Cmd {
@@ -71,16 +100,50 @@ impl Cmd {
             self.bytecode.as_str().into()
         };
 
-        let bytecode = hex::decode(bytecode_str.trim()).map_err(|_| Errors::InvalidBytecode)?;
+        let bytecode = if self.asm {
+            assemble(&bytecode_str)
+                .map_err(|e| Errors::InvalidAssembly(e.to_string()))?
+                .original_bytes()
+                .to_vec()
+        } else {
+            hex::decode(bytecode_str.trim()).map_err(|_| Errors::InvalidBytecode)?
+        };
         let input = hex::decode(self.input.trim())
             .map_err(|_| Errors::InvalidInput)?
             .into();
         // BenchmarkDB is dummy state that implements Database trait.
         // the bytecode is deployed at zero address.
+        let db = BenchmarkDB::new_bytecode(Bytecode::new_raw(bytecode.into()));
+        let Some(path) = &self.prestate else {
+            return self.execute(db, input);
+        };
+
+        // Pre-state accounts are kept in the CacheDB on top of the BenchmarkDB.
+        let mut db = CacheDB::new(db);
+        let prestate: HashMap<Address, AccountInfo> =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(Errors::InvalidPrestate)?;
+        for (address, info) in prestate {
+            let code_hash = keccak256(&info.code);
+            let code = Bytecode::new_raw(info.code);
+            db.insert_account_info(
+                address,
+                revm::primitives::AccountInfo::new(info.balance, info.nonce, code_hash, code),
+            );
+            for (slot, value) in info.storage {
+                db.insert_account_storage(address, slot, value).unwrap();
+            }
+        }
+        self.execute(db, input)
+    }
+
+    /// Executes the bytecode deployed at zero address over the given database.
+    fn execute<DB>(&self, db: DB, input: Bytes) -> Result<(), Errors>
+    where
+        DB: Database,
+        DB::Error: fmt::Debug,
+    {
         let mut evm = Evm::builder()
-            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
-                bytecode.into(),
-            )))
+            .with_db(db)
             .modify_tx_env(|tx| {
                 // execution globals block hash/gas_limit/coinbase/timestamp..
                 tx.caller = "0x0000000000000000000000000000000000000001"
@@ -102,7 +165,59 @@ impl Cmd {
             return Ok(());
         }
 
-        let out = if self.trace {
+        let out = if self.profile || self.flamegraph.is_some() {
+            let mut evm = evm
+                .modify()
+                .reset_handler_with_external_context(Profiler::new())
+                .append_handler_register(inspector_handle_register)
+                .build();
+
+            let out = evm.transact().map_err(|_| Errors::EVMError)?;
+            println!("Result: {:#?}", out.result);
+            let profiler = &evm.context.external;
+            let mut opcodes: Vec<_> = profiler.opcodes().iter().collect();
+            opcodes.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.gas));
+            println!("{:<16}{:>10}{:>14}{:>14}", "opcode", "count", "gas", "time");
+            for (opcode, entry) in opcodes {
+                println!(
+                    "{:<16}{:>10}{:>14}{:>14?}",
+                    OpCode::name_by_op(*opcode),
+                    entry.count,
+                    entry.gas,
+                    entry.time
+                );
+            }
+            println!(
+                "{:<44}{:>10}{:>14}{:>14}",
+                "contract", "count", "gas", "time"
+            );
+            for (address, entry) in profiler.contracts() {
+                println!(
+                    "{:<44}{:>10}{:>14}{:>14?}",
+                    address.to_string(),
+                    entry.count,
+                    entry.gas,
+                    entry.time
+                );
+            }
+            if let Some(path) = &self.flamegraph {
+                fs::write(path, profiler.folded_stacks(FlameGraphWeight::Gas))?;
+            }
+            out
+        } else if self.debug {
+            let mut evm = evm
+                .modify()
+                .reset_handler_with_external_context(Debugger::new(
+                    std::io::stdin().lock(),
+                    std::io::stdout(),
+                ))
+                .append_handler_register(inspector_handle_register)
+                .build();
+
+            let out = evm.transact().map_err(|_| Errors::EVMError)?;
+            println!("Result: {:#?}", out.result);
+            out
+        } else if self.trace {
             let mut evm = evm
                 .modify()
                 .reset_handler_with_external_context(TracerEip3155::new(
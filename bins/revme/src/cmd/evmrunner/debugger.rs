@@ -0,0 +1,362 @@
+use revm::{
+    interpreter::{opcode, CallInputs, CallOutcome, Interpreter, OpCode},
+    primitives::{hex, Address, U256},
+    Database, EvmContext, Inspector,
+};
+use std::{
+    fmt,
+    io::{BufRead, Write},
+};
+
+const HELP: &str = "\
+commands:
+  s, step                 execute one instruction
+  n, next                 execute one instruction, stepping over calls
+  c, continue             run until the next breakpoint
+  q, quit                 run to the end without pausing
+  b, break pc <pc>        break at program counter
+  b, break op <opcode>    break at opcode, e.g. `b op SSTORE`
+  b, break addr <address> break when a call to the address starts
+  b, break sstore [slot]  break before a storage write, optionally to the slot
+  bl, breakpoints         list breakpoints
+  d, delete <index>       delete breakpoint
+  w, where                print the current instruction
+  st, stack               print the stack, top first
+  mem, memory             print the memory of the current call
+  storage                 print the loaded storage of the current address
+  rd, returndata          print the return data of the last call
+  h, help                 print this help";
+
+/// Breakpoint of the [`Debugger`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Instruction at the program counter, in any call.
+    Pc(usize),
+    /// Execution of the opcode.
+    Opcode(u8),
+    /// Start of a call to the address.
+    Address(Address),
+    /// Storage write, to any slot if `None`.
+    StorageWrite(Option<U256>),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pc(pc) => write!(f, "pc {pc}"),
+            Self::Opcode(op) => write!(f, "opcode {}", OpCode::name_by_op(*op)),
+            Self::Address(address) => write!(f, "address {address}"),
+            Self::StorageWrite(Some(slot)) => write!(f, "sstore {slot:#x}"),
+            Self::StorageWrite(None) => f.write_str("sstore"),
+        }
+    }
+}
+
+impl Breakpoint {
+    /// Parses the arguments of the `break` command.
+    fn parse(args: &[&str]) -> Option<Self> {
+        match args {
+            ["pc", pc] => parse_number(pc).map(Self::Pc),
+            ["op", op] => OpCode::parse(&op.to_uppercase()).map(|op| Self::Opcode(op.get())),
+            ["addr", address] => address.parse().ok().map(Self::Address),
+            ["sstore"] => Some(Self::StorageWrite(None)),
+            ["sstore", slot] => slot.parse().ok().map(|slot| Self::StorageWrite(Some(slot))),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the breakpoint is hit by the instruction the interpreter is about to
+    /// execute.
+    fn is_hit(&self, interp: &Interpreter) -> bool {
+        match self {
+            Self::Pc(pc) => interp.program_counter() == *pc,
+            Self::Opcode(op) => interp.current_opcode() == *op,
+            Self::Address(_) => false,
+            Self::StorageWrite(slot) => {
+                interp.current_opcode() == opcode::SSTORE
+                    && (slot.is_none() || interp.stack.peek(0).ok() == *slot)
+            }
+        }
+    }
+}
+
+fn parse_number(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Pause before the next instruction.
+    Step,
+    /// Pause before the next instruction at the depth or above.
+    Next(u64),
+    /// Pause only at breakpoints.
+    Continue,
+    /// Never pause.
+    Detached,
+}
+
+/// Interactive step debugger that pauses execution before instructions and reads commands
+/// from the input.
+///
+/// It pauses before the first instruction. When the input is closed, execution runs to the
+/// end without pausing.
+#[derive(Debug)]
+pub struct Debugger<R, W> {
+    input: R,
+    output: W,
+    mode: Mode,
+    breakpoints: Vec<Breakpoint>,
+    /// Address breakpoint hit by a call, pauses at the first instruction of the call.
+    pending: Option<Breakpoint>,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            mode: Mode::Step,
+            breakpoints: Vec::new(),
+            pending: None,
+        }
+    }
+
+    /// Adds a breakpoint.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    /// Returns the output.
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    /// Prints the instruction and reads commands until execution is resumed.
+    fn pause<DB: Database>(&mut self, interp: &Interpreter, context: &EvmContext<DB>) {
+        self.print_where(interp, context);
+        loop {
+            let _ = write!(self.output, "> ");
+            let _ = self.output.flush();
+            let mut line = String::new();
+            if !matches!(self.input.read_line(&mut line), Ok(read) if read > 0) {
+                self.mode = Mode::Detached;
+                return;
+            }
+            let args: Vec<&str> = line.split_whitespace().collect();
+            let Some((command, args)) = args.split_first() else {
+                continue;
+            };
+            match *command {
+                "s" | "step" => self.mode = Mode::Step,
+                "n" | "next" => self.mode = Mode::Next(context.journaled_state.depth()),
+                "c" | "continue" => self.mode = Mode::Continue,
+                "q" | "quit" => self.mode = Mode::Detached,
+                "b" | "break" => {
+                    match Breakpoint::parse(args) {
+                        Some(breakpoint) => {
+                            let _ = writeln!(
+                                self.output,
+                                "breakpoint {}: {breakpoint}",
+                                self.breakpoints.len()
+                            );
+                            self.breakpoints.push(breakpoint);
+                        }
+                        None => {
+                            let _ = writeln!(self.output, "invalid breakpoint, see `help`");
+                        }
+                    }
+                    continue;
+                }
+                "bl" | "breakpoints" => {
+                    for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+                        let _ = writeln!(self.output, "{index}: {breakpoint}");
+                    }
+                    continue;
+                }
+                "d" | "delete" => {
+                    match args.first().and_then(|index| index.parse::<usize>().ok()) {
+                        Some(index) if index < self.breakpoints.len() => {
+                            self.breakpoints.remove(index);
+                        }
+                        _ => {
+                            let _ = writeln!(self.output, "invalid breakpoint index");
+                        }
+                    }
+                    continue;
+                }
+                "w" | "where" => {
+                    self.print_where(interp, context);
+                    continue;
+                }
+                "st" | "stack" => {
+                    for (index, value) in interp.stack.data().iter().rev().enumerate() {
+                        let _ = writeln!(self.output, "{index}: {value:#x}");
+                    }
+                    continue;
+                }
+                "mem" | "memory" => {
+                    let memory = interp.shared_memory.context_memory();
+                    for (index, word) in memory.chunks(32).enumerate() {
+                        let _ = writeln!(self.output, "{:#06x}: {}", index * 32, hex::encode(word));
+                    }
+                    continue;
+                }
+                "storage" => {
+                    let address = interp.contract.target_address;
+                    if let Some(account) = context.journaled_state.state.get(&address) {
+                        let mut storage: Vec<_> = account.storage.iter().collect();
+                        storage.sort_by_key(|(slot, _)| **slot);
+                        for (slot, value) in storage {
+                            let _ = writeln!(
+                                self.output,
+                                "{slot:#x}: {:#x} (original {:#x})",
+                                value.present_value, value.original_value
+                            );
+                        }
+                    }
+                    continue;
+                }
+                "rd" | "returndata" => {
+                    let _ = writeln!(self.output, "{}", interp.return_data_buffer);
+                    continue;
+                }
+                "h" | "help" => {
+                    let _ = writeln!(self.output, "{HELP}");
+                    continue;
+                }
+                _ => {
+                    let _ = writeln!(self.output, "unknown command `{command}`, see `help`");
+                    continue;
+                }
+            }
+            return;
+        }
+    }
+
+    fn print_where<DB: Database>(&mut self, interp: &Interpreter, context: &EvmContext<DB>) {
+        let _ = writeln!(
+            self.output,
+            "[depth {}] {} pc {} {} gas {}",
+            context.journaled_state.depth(),
+            interp.contract.target_address,
+            interp.program_counter(),
+            OpCode::name_by_op(interp.current_opcode()),
+            interp.gas.remaining(),
+        );
+    }
+}
+
+impl<DB: Database, R: BufRead, W: Write> Inspector<DB> for Debugger<R, W> {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if self.mode == Mode::Detached {
+            return;
+        }
+        let breakpoint = self
+            .pending
+            .take()
+            .or_else(|| self.breakpoints.iter().find(|b| b.is_hit(interp)).cloned());
+        if let Some(breakpoint) = &breakpoint {
+            let _ = writeln!(self.output, "breakpoint hit: {breakpoint}");
+        }
+        let pause = breakpoint.is_some()
+            || match self.mode {
+                Mode::Step => true,
+                Mode::Next(depth) => context.journaled_state.depth() <= depth,
+                Mode::Continue | Mode::Detached => false,
+            };
+        if pause {
+            self.pause(interp, context);
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if self.mode != Mode::Detached {
+            self.pending = self
+                .breakpoints
+                .iter()
+                .find(|b| **b == Breakpoint::Address(inputs.target_address))
+                .cloned();
+        }
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        // call without code does not execute any instruction.
+        self.pending = None;
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::opcode::assembly::assemble,
+        primitives::{address, TransactTo},
+        Evm,
+    };
+
+    fn debug(source: &str, commands: &str) -> String {
+        let bytecode = assemble(source).unwrap();
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .with_external_context(Debugger::new(commands.as_bytes(), Vec::new()))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.transact_to = TransactTo::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap();
+        String::from_utf8(evm.context.external.output().clone()).unwrap()
+    }
+
+    #[test]
+    fn step_and_breakpoints() {
+        let source = "
+            PUSH1 0x01
+            PUSH1 0x02
+            ADD
+            PUSH1 0x00
+            SSTORE
+            STOP
+        ";
+        let output = debug(source, "s\nstack\nb sstore 0\nc\nstack\nstorage\nc\n");
+        let expected = "\
+[depth 1] 0x0000000000000000000000000000000000000000 pc 0 PUSH1 gas 79000
+> [depth 1] 0x0000000000000000000000000000000000000000 pc 2 PUSH1 gas 78997
+> 0: 0x1
+> breakpoint 0: sstore 0x0
+> breakpoint hit: sstore 0x0
+[depth 1] 0x0000000000000000000000000000000000000000 pc 7 SSTORE gas 78988
+> 0: 0x0
+1: 0x3
+> > ";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn closed_input_runs_to_end() {
+        let output = debug("PUSH1 0x01\nPOP\nSTOP", "");
+        assert_eq!(
+            output,
+            "[depth 1] 0x0000000000000000000000000000000000000000 pc 0 PUSH1 gas 79000\n> "
+        );
+    }
+}
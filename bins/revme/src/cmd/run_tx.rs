@@ -0,0 +1,522 @@
+pub mod models;
+pub mod transaction;
+
+use crate::cmd::statetest::models::{AccountInfo, SpecName};
+use alloy_rlp::{Encodable, Header};
+use models::{AllocAccount, Env, ExecutionOutcome, Receipt, RejectedTransaction, Transaction};
+use revm::{
+    db::{log_rlp_hash, ordered_trie_root, state_merkle_trie_root},
+    primitives::{
+        alloy_primitives::{Bloom, U64},
+        calc_excess_blob_gas, keccak256, Address, BlockEnv, Bytecode, EVMError, HashMap, Log,
+        SpecId, B256, U256,
+    },
+    BlockExecutionError, BlockExecutor, CacheState, Evm, State, Withdrawal,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("failed to read {path}: {error}")]
+    Read { path: PathBuf, error: String },
+    #[error("failed to write {path}: {error}")]
+    Write { path: PathBuf, error: String },
+    #[error("unsupported fork: {0}")]
+    UnsupportedFork(String),
+    #[error("EVM error: {0}")]
+    EVMError(String),
+}
+
+/// Executes transactions against the pre-state, compatible with the geth `evm t8n` tool.
+///
+/// Pre-state accounts, block environment and transactions are read from the
+/// `alloc.json`, `env.json` and `txs.json` files. Post-state accounts and the execution
+/// result, containing the state root, receipts and rejected transactions, are written to
+/// the `alloc.json` and `result.json` files in the output directory. Use `stdout` or
+/// `stderr` as the output file name to print the output instead.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Path to the pre-state accounts.
+    #[structopt(long = "input.alloc", default_value = "alloc.json")]
+    input_alloc: PathBuf,
+    /// Path to the block environment.
+    #[structopt(long = "input.env", default_value = "env.json")]
+    input_env: PathBuf,
+    /// Path to the transactions.
+    #[structopt(long = "input.txs", default_value = "txs.json")]
+    input_txs: PathBuf,
+    /// Directory of the output files.
+    #[structopt(long = "output.basedir", default_value = ".")]
+    output_basedir: PathBuf,
+    /// File name of the execution result.
+    #[structopt(long = "output.result", default_value = "result.json")]
+    output_result: String,
+    /// File name of the post-state accounts.
+    #[structopt(long = "output.alloc", default_value = "alloc.json")]
+    output_alloc: String,
+    /// Name of the fork, as used in the state tests.
+    #[structopt(long = "state.fork", default_value = "Cancun")]
+    fork: String,
+    /// Chain id of the transactions.
+    #[structopt(long = "state.chainid", default_value = "1")]
+    chain_id: u64,
+    /// Block reward of the coinbase before the Merge, negative value disables the reward.
+    #[structopt(long = "state.reward", default_value = "0", allow_hyphen_values = true)]
+    reward: i64,
+}
+
+impl Cmd {
+    /// Run `run-tx` command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let spec_id = self.spec_id()?;
+        let alloc: HashMap<Address, AccountInfo> = read_json(&self.input_alloc)?;
+        let env: Env = read_json(&self.input_env)?;
+        let txs: Vec<Transaction> = read_json(&self.input_txs)?;
+
+        let reward = u128::try_from(self.reward).ok();
+        let (outcome, post_alloc) = execute(spec_id, self.chain_id, reward, alloc, env, &txs)?;
+
+        self.write_json(&self.output_result, &outcome)?;
+        self.write_json(&self.output_alloc, &post_alloc)
+    }
+
+    fn spec_id(&self) -> Result<SpecId, Errors> {
+        let spec_name: SpecName = serde_json::from_value(self.fork.clone().into())
+            .map_err(|_| Errors::UnsupportedFork(self.fork.clone()))?;
+        match spec_name {
+            SpecName::ByzantiumToConstantinopleAt5
+            | SpecName::Constantinople
+            | SpecName::Unknown => Err(Errors::UnsupportedFork(self.fork.clone())),
+            spec_name => Ok(spec_name.to_spec_id()),
+        }
+    }
+
+    fn write_json<T: Serialize>(&self, name: &str, value: &T) -> Result<(), Errors> {
+        let path = self.output_basedir.join(name);
+        let json = serde_json::to_string_pretty(value).map_err(|e| Errors::Write {
+            path: path.clone(),
+            error: e.to_string(),
+        })?;
+        match name {
+            "stdout" => println!("{json}"),
+            "stderr" => eprintln!("{json}"),
+            _ => fs::write(&path, json).map_err(|e| Errors::Write {
+                path,
+                error: e.to_string(),
+            })?,
+        }
+        Ok(())
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Errors> {
+    let read_error = |error: String| Errors::Read {
+        path: path.to_path_buf(),
+        error,
+    };
+    let json = fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+    serde_json::from_str(&json).map_err(|e| read_error(e.to_string()))
+}
+
+/// Executes the transactions and returns the execution outcome and post-state accounts.
+///
+/// Receipts always contain the status of the transaction, pre-Byzantium intermediate
+/// state roots are not supported.
+pub fn execute(
+    spec_id: SpecId,
+    chain_id: u64,
+    reward: Option<u128>,
+    alloc: HashMap<Address, AccountInfo>,
+    env: Env,
+    txs: &[Transaction],
+) -> Result<(ExecutionOutcome, BTreeMap<Address, AllocAccount>), Errors> {
+    let mut cache_state = CacheState::new(false);
+    for (address, info) in alloc {
+        let acc_info = revm::primitives::AccountInfo {
+            balance: info.balance,
+            code_hash: keccak256(&info.code),
+            code: Some(Bytecode::new_raw(info.code)),
+            nonce: info.nonce,
+        };
+        cache_state.insert_account_with_storage(address, acc_info, info.storage);
+    }
+    cache_state.set_state_clear_flag(SpecId::enabled(spec_id, SpecId::SPURIOUS_DRAGON));
+    let state = State::builder()
+        .with_cached_prestate(cache_state)
+        .with_block_hashes(env.block_hashes.clone())
+        .build();
+
+    let block = block_env(spec_id, &env);
+    let basefee = block.basefee;
+    let excess_blob_gas = block
+        .blob_excess_gas_and_price
+        .as_ref()
+        .map(|blob| blob.excess_blob_gas);
+
+    let mut executor = BlockExecutor::new(
+        Evm::builder()
+            .with_db(state)
+            .modify_cfg_env(|cfg| cfg.chain_id = chain_id)
+            .with_spec_id(spec_id)
+            .build(),
+    );
+    executor
+        .begin_block(block)
+        .map_err(|e| Errors::EVMError(e.to_string()))?;
+
+    let mut receipts = Vec::new();
+    let mut encoded_receipts = Vec::new();
+    let mut rejected = Vec::new();
+    let mut all_logs = Vec::new();
+    for (index, tx) in txs.iter().enumerate() {
+        let mut reject = |error: String| rejected.push(RejectedTransaction { index, error });
+
+        let caller = match tx.sender(chain_id, spec_id) {
+            Ok(caller) => caller,
+            Err(error) => {
+                reject(error);
+                continue;
+            }
+        };
+        let receipt = match executor.execute_transaction(tx.to_tx_env(caller, chain_id)) {
+            Ok(receipt) => receipt,
+            Err(BlockExecutionError::BlockGasLimitExceeded {
+                transaction_gas_limit,
+                block_available_gas,
+                ..
+            }) => {
+                reject(format!(
+                    "gas limit reached: transaction gas limit {transaction_gas_limit} exceeds available block gas {block_available_gas}"
+                ));
+                continue;
+            }
+            Err(BlockExecutionError::BlobGasLimitExceeded {
+                transaction_blob_gas,
+                block_available_blob_gas,
+                ..
+            }) => {
+                reject(format!(
+                    "blob gas limit reached: transaction blob gas {transaction_blob_gas} exceeds available block blob gas {block_available_blob_gas}"
+                ));
+                continue;
+            }
+            Err(BlockExecutionError::Transaction {
+                error: EVMError::Transaction(error),
+                ..
+            }) => {
+                reject(error.to_string());
+                continue;
+            }
+            Err(error) => return Err(Errors::EVMError(error.to_string())),
+        };
+
+        let contract_address =
+            (tx.to.is_none() && receipt.success).then(|| caller.create(tx.nonce));
+        // Type is validated by the sender recovery.
+        let tx_type = tx.tx_type().unwrap_or_default();
+        encoded_receipts.push(encode_receipt(
+            tx_type,
+            receipt.success,
+            receipt.cumulative_gas_used,
+            &receipt.logs_bloom,
+            &receipt.logs,
+        ));
+        all_logs.extend_from_slice(&receipt.logs);
+        receipts.push(Receipt {
+            tx_type: U64::from(tx_type),
+            status: U64::from(receipt.success as u64),
+            cumulative_gas_used: U64::from(receipt.cumulative_gas_used),
+            logs_bloom: receipt.logs_bloom,
+            logs: receipt.logs.clone(),
+            contract_address,
+            gas_used: U64::from(receipt.gas_used),
+            transaction_index: U64::from(receipts.len()),
+        });
+    }
+
+    let withdrawals: Vec<_> = env
+        .withdrawals
+        .iter()
+        .map(|withdrawal| Withdrawal {
+            index: withdrawal.index,
+            validator_index: withdrawal.validator_index,
+            address: withdrawal.address,
+            amount: withdrawal.amount,
+        })
+        .collect();
+    let output = executor
+        .finish_block(&withdrawals)
+        .map_err(|e| Errors::EVMError(e.to_string()))?;
+
+    let (mut state, _) = executor.into_evm().into_db_and_env_with_handler_cfg();
+    if let Some(reward) = reward.filter(|_| !SpecId::enabled(spec_id, SpecId::MERGE)) {
+        state
+            .increment_balances([(env.current_coinbase, reward)])
+            .map_err(|e| Errors::EVMError(e.to_string()))?;
+    }
+
+    let post_alloc = state
+        .cache
+        .trie_account()
+        .into_iter()
+        .map(|(address, account)| {
+            let code = account
+                .info
+                .code
+                .clone()
+                .or_else(|| state.cache.contracts.get(&account.info.code_hash).cloned())
+                .map(|code| code.original_bytes())
+                .unwrap_or_default();
+            let storage = account
+                .storage
+                .iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(key, value)| (B256::from(*key), B256::from(*value)))
+                .collect();
+            (
+                address,
+                AllocAccount {
+                    balance: account.info.balance,
+                    code,
+                    nonce: U64::from(account.info.nonce),
+                    storage,
+                },
+            )
+        })
+        .collect();
+
+    let is_cancun = SpecId::enabled(spec_id, SpecId::CANCUN);
+    let outcome = ExecutionOutcome {
+        state_root: state_merkle_trie_root(state.cache.trie_account()),
+        receipts_root: ordered_trie_root(encoded_receipts),
+        logs_hash: log_rlp_hash(&all_logs),
+        logs_bloom: output.logs_bloom,
+        receipts,
+        rejected,
+        gas_used: U64::from(output.gas_used),
+        current_base_fee: SpecId::enabled(spec_id, SpecId::LONDON).then_some(basefee),
+        current_excess_blob_gas: excess_blob_gas.filter(|_| is_cancun).map(U64::from),
+        blob_gas_used: is_cancun.then_some(U64::from(output.blob_gas_used)),
+    };
+    Ok((outcome, post_alloc))
+}
+
+/// Creates the block environment, deriving the base fee and the excess blob gas from the
+/// parent block if they are not set.
+fn block_env(spec_id: SpecId, env: &Env) -> BlockEnv {
+    let mut block = BlockEnv {
+        number: env.current_number,
+        coinbase: env.current_coinbase,
+        timestamp: env.current_timestamp,
+        gas_limit: env.current_gas_limit,
+        difficulty: env.current_difficulty.unwrap_or_default(),
+        prevrandao: env.current_random,
+        parent_beacon_block_root: env.parent_beacon_block_root,
+        ..Default::default()
+    };
+
+    block.basefee = match (
+        env.current_base_fee,
+        env.parent_base_fee,
+        env.parent_gas_used,
+        env.parent_gas_limit,
+    ) {
+        (Some(base_fee), ..) => base_fee,
+        (None, Some(parent_base_fee), Some(parent_gas_used), Some(parent_gas_limit))
+            if SpecId::enabled(spec_id, SpecId::LONDON) =>
+        {
+            U256::from(calc_base_fee(
+                parent_base_fee.saturating_to(),
+                parent_gas_used.saturating_to(),
+                parent_gas_limit.saturating_to(),
+            ))
+        }
+        _ => U256::ZERO,
+    };
+
+    if SpecId::enabled(spec_id, SpecId::CANCUN) {
+        let excess_blob_gas = match (
+            env.current_excess_blob_gas,
+            env.parent_excess_blob_gas,
+            env.parent_blob_gas_used,
+        ) {
+            (Some(excess_blob_gas), ..) => excess_blob_gas.saturating_to(),
+            (None, Some(parent_excess_blob_gas), Some(parent_blob_gas_used)) => {
+                calc_excess_blob_gas(
+                    parent_excess_blob_gas.saturating_to(),
+                    parent_blob_gas_used.saturating_to(),
+                )
+            }
+            _ => 0,
+        };
+        block.set_blob_excess_gas_and_price(excess_blob_gas);
+    }
+    block
+}
+
+/// Calculates the base fee of the block from its parent as specified in EIP-1559.
+pub fn calc_base_fee(parent_base_fee: u64, parent_gas_used: u64, parent_gas_limit: u64) -> u64 {
+    const BASE_FEE_MAX_CHANGE_DENOMINATOR: u128 = 8;
+    const ELASTICITY_MULTIPLIER: u64 = 2;
+
+    let parent_gas_target = parent_gas_limit / ELASTICITY_MULTIPLIER;
+    if parent_gas_target == 0 {
+        return parent_base_fee;
+    }
+    let change = |gas_delta: u64| {
+        (parent_base_fee as u128 * gas_delta as u128
+            / parent_gas_target as u128
+            / BASE_FEE_MAX_CHANGE_DENOMINATOR) as u64
+    };
+    match parent_gas_used.cmp(&parent_gas_target) {
+        Ordering::Equal => parent_base_fee,
+        Ordering::Greater => {
+            parent_base_fee.saturating_add(change(parent_gas_used - parent_gas_target).max(1))
+        }
+        Ordering::Less => parent_base_fee - change(parent_gas_target - parent_gas_used),
+    }
+}
+
+/// Encodes the receipt as it is stored in the receipts trie.
+pub fn encode_receipt(
+    tx_type: u8,
+    success: bool,
+    cumulative_gas_used: u64,
+    logs_bloom: &Bloom,
+    logs: &Vec<Log>,
+) -> Vec<u8> {
+    let fields: [&dyn Encodable; 4] = [&success, &cumulative_gas_used, logs_bloom, logs];
+    let mut out = Vec::new();
+    if tx_type != transaction::LEGACY_TX_TYPE {
+        out.push(tx_type);
+    }
+    Header {
+        list: true,
+        payload_length: fields.iter().map(|field| field.length()).sum(),
+    }
+    .encode(&mut out);
+    for field in fields {
+        field.encode(&mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::PlainAccount,
+        primitives::{address, b256},
+    };
+
+    #[test]
+    fn base_fee() {
+        assert_eq!(
+            calc_base_fee(1_000_000_000, 15_000_000, 30_000_000),
+            1_000_000_000
+        );
+        assert_eq!(
+            calc_base_fee(1_000_000_000, 30_000_000, 30_000_000),
+            1_125_000_000
+        );
+        assert_eq!(calc_base_fee(1_000_000_000, 0, 30_000_000), 875_000_000);
+    }
+
+    #[test]
+    fn execute_transfer() {
+        let sender = address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        let alloc: HashMap<Address, AccountInfo> = serde_json::from_str(
+            r#"{"0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": {"balance": "0x5ffd4878be161d74"}}"#,
+        )
+        .unwrap();
+        let env: Env = serde_json::from_str(
+            r#"{
+                "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                "currentGasLimit": "0x1000000",
+                "currentNumber": "0x1",
+                "currentTimestamp": "0x3e8",
+                "currentRandom": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "currentBaseFee": "0x7",
+                "parentBeaconBlockRoot": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "currentExcessBlobGas": "0x0",
+                "withdrawals": []
+            }"#,
+        )
+        .unwrap();
+        let txs: Vec<Transaction> = serde_json::from_str(
+            r#"[
+                {
+                    "type": "0x2",
+                    "chainId": "0x1",
+                    "nonce": "0x0",
+                    "maxPriorityFeePerGas": "0x1",
+                    "maxFeePerGas": "0xa",
+                    "gas": "0x5208",
+                    "to": "0x1000000000000000000000000000000000000000",
+                    "value": "0x1",
+                    "input": "0x",
+                    "accessList": [],
+                    "secretKey": "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+                },
+                {
+                    "type": "0x2",
+                    "chainId": "0x1",
+                    "nonce": "0x0",
+                    "maxPriorityFeePerGas": "0x1",
+                    "maxFeePerGas": "0xa",
+                    "gas": "0x5208",
+                    "to": "0x1000000000000000000000000000000000000000",
+                    "value": "0x1",
+                    "input": "0x",
+                    "accessList": [],
+                    "secretKey": "0x45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let (outcome, post_alloc) = execute(SpecId::CANCUN, 1, None, alloc, env, &txs).unwrap();
+
+        assert_eq!(outcome.receipts.len(), 1);
+        assert_eq!(outcome.receipts[0].status, U64::from(1));
+        assert_eq!(outcome.gas_used, U64::from(21_000));
+        assert_eq!(outcome.rejected.len(), 1);
+        assert_eq!(outcome.rejected[0].index, 1);
+        assert_eq!(outcome.blob_gas_used, Some(U64::ZERO));
+        assert_eq!(
+            outcome.logs_hash,
+            b256!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+        );
+
+        let receiver = &post_alloc[&address!("1000000000000000000000000000000000000000")];
+        assert_eq!(receiver.balance, U256::from(1));
+        assert_eq!(post_alloc[&sender].nonce, U64::from(1));
+        // state root is the root of the post-state accounts.
+        let accounts: Vec<_> = post_alloc
+            .iter()
+            .map(|(address, account)| {
+                let info = revm::primitives::AccountInfo {
+                    balance: account.balance,
+                    nonce: account.nonce.to(),
+                    ..Default::default()
+                };
+                (*address, PlainAccount::from(info))
+            })
+            .collect();
+        assert_eq!(
+            outcome.state_root,
+            state_merkle_trie_root(
+                accounts
+                    .iter()
+                    .map(|(address, account)| (*address, account))
+            )
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::cmd::statetest::models::{deserialize_maybe_empty, deserialize_str_as_u64, AccessList};
+use revm::primitives::{
+    alloy_primitives::{Bloom, U64},
+    Address, Bytes, Log, B256, U256,
+};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+/// Block environment, the `env.json` input of the `t8n` tool.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Env {
+    pub current_coinbase: Address,
+    pub current_difficulty: Option<U256>,
+    pub current_random: Option<B256>,
+    pub current_gas_limit: U256,
+    pub current_number: U256,
+    pub current_timestamp: U256,
+    pub current_base_fee: Option<U256>,
+
+    pub parent_base_fee: Option<U256>,
+    pub parent_gas_used: Option<U256>,
+    pub parent_gas_limit: Option<U256>,
+
+    pub parent_beacon_block_root: Option<B256>,
+
+    pub current_excess_blob_gas: Option<U256>,
+    pub parent_excess_blob_gas: Option<U256>,
+    pub parent_blob_gas_used: Option<U256>,
+
+    #[serde(default, deserialize_with = "deserialize_block_hashes")]
+    pub block_hashes: BTreeMap<u64, B256>,
+    #[serde(default)]
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    pub index: u64,
+    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    pub validator_index: u64,
+    pub address: Address,
+    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    pub amount: u64,
+}
+
+/// Transaction of the `txs.json` input of the `t8n` tool.
+///
+/// Sender is taken from the `sender` field, derived from the `secretKey` or recovered
+/// from the signature, in that order.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    #[serde(rename = "type")]
+    pub tx_type: Option<U64>,
+    pub chain_id: Option<U256>,
+    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    pub nonce: u64,
+    pub gas_price: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(alias = "gasLimit", deserialize_with = "deserialize_str_as_u64")]
+    pub gas: u64,
+    #[serde(default, deserialize_with = "deserialize_maybe_empty")]
+    pub to: Option<Address>,
+    pub value: U256,
+    #[serde(alias = "data")]
+    pub input: Bytes,
+    pub access_list: Option<AccessList>,
+    pub max_fee_per_blob_gas: Option<U256>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<B256>,
+    pub authorization_list: Option<Vec<Authorization>>,
+
+    #[serde(default)]
+    pub v: U256,
+    #[serde(default)]
+    pub r: U256,
+    #[serde(default)]
+    pub s: U256,
+    pub secret_key: Option<B256>,
+    pub sender: Option<Address>,
+}
+
+/// Signed authorization tuple of the EIP-7702 transaction.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: Address,
+    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    pub nonce: u64,
+    #[serde(alias = "v")]
+    pub y_parity: U64,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// Result of the `t8n` tool, the `result.json` output.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionOutcome {
+    pub state_root: B256,
+    pub receipts_root: B256,
+    pub logs_hash: B256,
+    pub logs_bloom: Bloom,
+    pub receipts: Vec<Receipt>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rejected: Vec<RejectedTransaction>,
+    pub gas_used: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_base_fee: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_excess_blob_gas: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    #[serde(rename = "type")]
+    pub tx_type: U64,
+    pub status: U64,
+    pub cumulative_gas_used: U64,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<Log>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<Address>,
+    pub gas_used: U64,
+    pub transaction_index: U64,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct RejectedTransaction {
+    pub index: usize,
+    pub error: String,
+}
+
+/// Account of the `alloc.json` output.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct AllocAccount {
+    pub balance: U256,
+    #[serde(skip_serializing_if = "<[u8]>::is_empty")]
+    pub code: Bytes,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub nonce: U64,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+fn is_zero(value: &U64) -> bool {
+    value.is_zero()
+}
+
+/// Deserializes block hashes keyed by the decimal or hex block number.
+fn deserialize_block_hashes<'de, D>(deserializer: D) -> Result<BTreeMap<u64, B256>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, B256>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(number, hash)| {
+            if let Some(stripped) = number.strip_prefix("0x") {
+                u64::from_str_radix(stripped, 16)
+            } else {
+                number.parse()
+            }
+            .map(|number| (number, hash))
+            .map_err(de::Error::custom)
+        })
+        .collect()
+}
@@ -0,0 +1,404 @@
+use super::models::Transaction;
+use crate::cmd::statetest::utils::recover_address;
+use alloy_rlp::{Encodable, Header, RlpEncodable};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use revm::primitives::{
+    eip7702::SECP256K1N_HALF, keccak256, Address, Authorization, AuthorizationList, Bytes, SpecId,
+    TransactTo, TxEnv, B256, U256,
+};
+
+/// Legacy transaction type.
+pub const LEGACY_TX_TYPE: u8 = 0;
+/// EIP-2930 access list transaction type.
+pub const EIP2930_TX_TYPE: u8 = 1;
+/// EIP-1559 dynamic fee transaction type.
+pub const EIP1559_TX_TYPE: u8 = 2;
+/// EIP-4844 blob transaction type.
+pub const EIP4844_TX_TYPE: u8 = 3;
+/// EIP-7702 set code transaction type.
+pub const EIP7702_TX_TYPE: u8 = 4;
+
+#[derive(RlpEncodable)]
+struct AccessListItem {
+    address: Address,
+    storage_keys: Vec<B256>,
+}
+
+#[derive(RlpEncodable)]
+struct AuthorizationItem {
+    chain_id: U256,
+    address: Address,
+    nonce: u64,
+    y_parity: u64,
+    r: U256,
+    s: U256,
+}
+
+impl Transaction {
+    /// Returns the type of the transaction, inferred from the fields if it is not set.
+    ///
+    /// Returns an error if the type is not known.
+    pub fn tx_type(&self) -> Result<u8, String> {
+        if let Some(tx_type) = self.tx_type {
+            return u8::try_from(tx_type)
+                .ok()
+                .filter(|tx_type| *tx_type <= EIP7702_TX_TYPE)
+                .ok_or_else(|| format!("unsupported transaction type: {tx_type}"));
+        }
+        Ok(if self.authorization_list.is_some() {
+            EIP7702_TX_TYPE
+        } else if self.max_fee_per_blob_gas.is_some() || !self.blob_versioned_hashes.is_empty() {
+            EIP4844_TX_TYPE
+        } else if self.max_fee_per_gas.is_some() {
+            EIP1559_TX_TYPE
+        } else if self.access_list.is_some() {
+            EIP2930_TX_TYPE
+        } else {
+            LEGACY_TX_TYPE
+        })
+    }
+
+    /// Returns the chain id the transaction is signed for.
+    ///
+    /// Legacy transactions take it from `v` as specified in EIP-155, `None` if unprotected.
+    pub fn signed_chain_id(&self, chain_id: u64) -> Option<u64> {
+        if self.tx_type() != Ok(LEGACY_TX_TYPE) {
+            return Some(self.chain_id.map_or(chain_id, |id| id.saturating_to()));
+        }
+        if self.secret_key.is_some() || self.sender.is_some() {
+            return self.chain_id.map(|id| id.saturating_to());
+        }
+        let v: u64 = self.v.saturating_to();
+        (v >= 35).then(|| (v - 35) / 2)
+    }
+
+    /// Returns the sender of the transaction.
+    ///
+    /// Since Homestead signatures with `s` in the upper half of the curve order are
+    /// rejected as specified in EIP-2.
+    pub fn sender(&self, chain_id: u64, spec_id: SpecId) -> Result<Address, String> {
+        let tx_type = self.tx_type()?;
+        if let Some(sender) = self.sender {
+            return Ok(sender);
+        }
+        if let Some(secret_key) = self.secret_key {
+            return recover_address(secret_key.as_slice())
+                .ok_or_else(|| format!("invalid secret key: {secret_key}"));
+        }
+
+        let v: u64 = self.v.saturating_to();
+        let y_parity = match tx_type {
+            LEGACY_TX_TYPE if v >= 35 => (v - 35) % 2,
+            LEGACY_TX_TYPE => v.wrapping_sub(27),
+            _ => v,
+        };
+        if spec_id.is_enabled_in(SpecId::HOMESTEAD) && self.s > SECP256K1N_HALF {
+            return Err(format!("invalid signature s value: {}", self.s));
+        }
+        let recovery_id = u8::try_from(y_parity)
+            .ok()
+            .and_then(RecoveryId::from_byte)
+            .ok_or_else(|| format!("invalid signature v value: {}", self.v))?;
+        let signature =
+            Signature::from_scalars(self.r.to_be_bytes::<32>(), self.s.to_be_bytes::<32>())
+                .map_err(|e| format!("invalid signature: {e}"))?;
+
+        let signature_hash = self.signature_hash(chain_id)?;
+        let key =
+            VerifyingKey::recover_from_prehash(signature_hash.as_slice(), &signature, recovery_id)
+                .map_err(|e| format!("invalid signature: {e}"))?;
+        let public_key = key.to_encoded_point(false);
+        Ok(Address::from_raw_public_key(&public_key.as_bytes()[1..]))
+    }
+
+    /// Returns the hash of the transaction payload that is signed by the sender.
+    ///
+    /// Returns an error if the type is not known.
+    pub fn signature_hash(&self, chain_id: u64) -> Result<B256, String> {
+        self.encode_with_signature(chain_id, false).map(keccak256)
+    }
+
+    /// Encodes the signed transaction as it is stored in the transactions trie.
+    ///
+    /// Returns an error if the type is not known.
+    pub fn encode(&self, chain_id: u64) -> Result<Vec<u8>, String> {
+        self.encode_with_signature(chain_id, true)
+    }
+
+    /// Encodes the transaction payload and, if `signed` is true, the signature.
+    fn encode_with_signature(&self, chain_id: u64, signed: bool) -> Result<Vec<u8>, String> {
+        let signed_chain_id = self.signed_chain_id(chain_id);
+        let chain_id = U256::from(signed_chain_id.unwrap_or_default());
+        let to = self
+            .to
+            .map_or_else(Bytes::new, |to| Bytes::copy_from_slice(to.as_slice()));
+        let gas_price = self.gas_price.unwrap_or_default();
+        let max_priority_fee_per_gas = self.max_priority_fee_per_gas.unwrap_or_default();
+        let max_fee_per_gas = self.max_fee_per_gas.unwrap_or_default();
+        let max_fee_per_blob_gas = self.max_fee_per_blob_gas.unwrap_or_default();
+        let access_list: Vec<AccessListItem> = self
+            .access_list
+            .iter()
+            .flatten()
+            .map(|item| AccessListItem {
+                address: item.address,
+                storage_keys: item.storage_keys.clone(),
+            })
+            .collect();
+        let authorization_list: Vec<AuthorizationItem> = self
+            .authorization_list
+            .iter()
+            .flatten()
+            .map(|item| AuthorizationItem {
+                chain_id: item.chain_id,
+                address: item.address,
+                nonce: item.nonce,
+                y_parity: item.y_parity.to(),
+                r: item.r,
+                s: item.s,
+            })
+            .collect();
+
+        let tx_type = self.tx_type()?;
+        let mut fields: Vec<&dyn Encodable> = match tx_type {
+            LEGACY_TX_TYPE => {
+                let mut fields: Vec<&dyn Encodable> = vec![
+                    &self.nonce,
+                    &gas_price,
+                    &self.gas,
+                    &to,
+                    &self.value,
+                    &self.input,
+                ];
+                if signed_chain_id.is_some() && !signed {
+                    fields.extend([&chain_id as &dyn Encodable, &0u8, &0u8]);
+                }
+                fields
+            }
+            EIP2930_TX_TYPE => vec![
+                &chain_id,
+                &self.nonce,
+                &gas_price,
+                &self.gas,
+                &to,
+                &self.value,
+                &self.input,
+                &access_list,
+            ],
+            EIP1559_TX_TYPE => vec![
+                &chain_id,
+                &self.nonce,
+                &max_priority_fee_per_gas,
+                &max_fee_per_gas,
+                &self.gas,
+                &to,
+                &self.value,
+                &self.input,
+                &access_list,
+            ],
+            EIP4844_TX_TYPE => vec![
+                &chain_id,
+                &self.nonce,
+                &max_priority_fee_per_gas,
+                &max_fee_per_gas,
+                &self.gas,
+                &to,
+                &self.value,
+                &self.input,
+                &access_list,
+                &max_fee_per_blob_gas,
+                &self.blob_versioned_hashes,
+            ],
+            EIP7702_TX_TYPE => vec![
+                &chain_id,
+                &self.nonce,
+                &max_priority_fee_per_gas,
+                &max_fee_per_gas,
+                &self.gas,
+                &to,
+                &self.value,
+                &self.input,
+                &access_list,
+                &authorization_list,
+            ],
+            _ => return Err(format!("unsupported transaction type: {tx_type}")),
+        };
+        if signed {
+            // Typed transactions are signed with the y parity in place of `v`.
+            fields.extend([&self.v as &dyn Encodable, &self.r, &self.s]);
+        }
+
+        let mut out = Vec::new();
+        if tx_type != LEGACY_TX_TYPE {
+            out.push(tx_type);
+        }
+        Header {
+            list: true,
+            payload_length: fields.iter().map(|field| field.length()).sum(),
+        }
+        .encode(&mut out);
+        for field in fields {
+            field.encode(&mut out);
+        }
+        Ok(out)
+    }
+
+    /// Creates the transaction environment.
+    // Default fills the optimism fields when the feature is enabled.
+    #[allow(clippy::needless_update)]
+    pub fn to_tx_env(&self, caller: Address, chain_id: u64) -> TxEnv {
+        TxEnv {
+            caller,
+            gas_limit: self.gas,
+            gas_price: self.gas_price.or(self.max_fee_per_gas).unwrap_or_default(),
+            gas_priority_fee: self.max_priority_fee_per_gas,
+            transact_to: match self.to {
+                Some(to) => TransactTo::Call(to),
+                None => TransactTo::Create,
+            },
+            value: self.value,
+            data: self.input.clone(),
+            nonce: Some(self.nonce),
+            chain_id: self.signed_chain_id(chain_id),
+            access_list: self
+                .access_list
+                .iter()
+                .flatten()
+                .map(|item| {
+                    (
+                        item.address,
+                        item.storage_keys
+                            .iter()
+                            .map(|key| U256::from_be_bytes(key.0))
+                            .collect(),
+                    )
+                })
+                .collect(),
+            blob_hashes: self.blob_versioned_hashes.clone(),
+            max_fee_per_blob_gas: self.max_fee_per_blob_gas,
+            authorization_list: self.authorization_list.as_ref().map(|list| {
+                AuthorizationList::Signed(
+                    list.iter()
+                        .map(|item| {
+                            Authorization {
+                                chain_id: item.chain_id,
+                                address: item.address,
+                                nonce: item.nonce,
+                            }
+                            .into_signed(
+                                item.y_parity.saturating_to(),
+                                item.r,
+                                item.s,
+                            )
+                        })
+                        .collect(),
+                )
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{address, alloy_primitives::U64, hex};
+
+    #[test]
+    fn recover_sender() {
+        // Example transaction of EIP-155.
+        let tx: Transaction = serde_json::from_str(
+            r#"{
+                "nonce": "0x9",
+                "gasPrice": "0x4a817c800",
+                "gas": "0x5208",
+                "to": "0x3535353535353535353535353535353535353535",
+                "value": "0xde0b6b3a7640000",
+                "input": "0x",
+                "v": "0x25",
+                "r": "0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276",
+                "s": "0x67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(tx.tx_type(), Ok(LEGACY_TX_TYPE));
+        assert_eq!(tx.signed_chain_id(1), Some(1));
+        assert_eq!(
+            tx.sender(1, SpecId::CANCUN),
+            Ok(address!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"))
+        );
+        assert_eq!(
+            tx.encode(1),
+            Ok(hex!("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").to_vec())
+        );
+    }
+
+    #[test]
+    fn reject_invalid_transactions() {
+        let tx = Transaction {
+            tx_type: Some(U64::from(0x100)),
+            ..Default::default()
+        };
+        assert_eq!(
+            tx.sender(1, SpecId::CANCUN),
+            Err("unsupported transaction type: 256".to_string())
+        );
+        let tx = Transaction {
+            tx_type: Some(U64::from(5)),
+            ..Default::default()
+        };
+        assert!(tx.signature_hash(1).is_err());
+
+        // Signature of the EIP-155 example with the malleated `s` value.
+        let tx = Transaction {
+            v: U256::from(0x26),
+            r: U256::from(1),
+            s: SECP256K1N_HALF + U256::from(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            tx.sender(1, SpecId::CANCUN),
+            Err(format!("invalid signature s value: {}", tx.s))
+        );
+    }
+
+    #[test]
+    fn eip7702_transaction() {
+        let tx: Transaction = serde_json::from_str(
+            r#"{
+                "type": "0x4",
+                "chainId": "0x1",
+                "nonce": "0x0",
+                "maxPriorityFeePerGas": "0x1",
+                "maxFeePerGas": "0x2",
+                "gas": "0x5208",
+                "to": "0x3535353535353535353535353535353535353535",
+                "value": "0x0",
+                "input": "0x",
+                "accessList": [],
+                "authorizationList": [{
+                    "chainId": "0x1",
+                    "address": "0x0000000000000000000000000000000000000100",
+                    "nonce": "0x1",
+                    "yParity": "0x1",
+                    "r": "0x2",
+                    "s": "0x3"
+                }],
+                "sender": "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(tx.tx_type(), Ok(EIP7702_TX_TYPE));
+        assert!(tx.signature_hash(1).is_ok());
+        let tx_env = tx.to_tx_env(tx.sender(1, SpecId::PRAGUE).unwrap(), 1);
+        assert_eq!(
+            tx_env.authorization_list,
+            Some(AuthorizationList::Signed(vec![Authorization {
+                chain_id: U256::from(1),
+                address: address!("0000000000000000000000000000000000000100"),
+                nonce: 1,
+            }
+            .into_signed(1, U256::from(2), U256::from(3))]))
+        );
+    }
+}
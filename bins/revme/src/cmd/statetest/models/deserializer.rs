@@ -19,10 +19,8 @@ pub fn deserialize_maybe_empty<'de, D>(deserializer: D) -> Result<Option<Address
 where
     D: de::Deserializer<'de>,
 {
-    let string = String::deserialize(deserializer)?;
-    if string.is_empty() {
-        Ok(None)
-    } else {
-        string.parse().map_err(de::Error::custom).map(Some)
+    match Option::<String>::deserialize(deserializer)? {
+        Some(string) if !string.is_empty() => string.parse().map_err(de::Error::custom).map(Some),
+        _ => Ok(None),
     }
 }
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 mod deserializer;
-use deserializer::*;
+pub use deserializer::*;
 
 mod spec;
 pub use self::spec::SpecName;
@@ -59,9 +59,11 @@ pub struct TxPartIndices {
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AccountInfo {
     pub balance: U256,
+    #[serde(default)]
     pub code: Bytes,
-    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    #[serde(default, deserialize_with = "deserialize_str_as_u64")]
     pub nonce: u64,
+    #[serde(default)]
     pub storage: HashMap<U256, U256>,
 }
 
@@ -20,8 +20,12 @@ pub enum SpecName {
     Berlin,
     BerlinToLondonAt5,
     London,
+    #[serde(alias = "Paris")]
     Merge,
+    #[serde(alias = "ParisToShanghaiAtTime15k")]
+    MergeToShanghaiAtTime15k,
     Shanghai,
+    ShanghaiToCancunAtTime15k,
     Cancun,
     #[serde(other)]
     Unknown,
@@ -42,8 +46,8 @@ impl SpecName {
             Self::Berlin => SpecId::BERLIN,
             Self::London | Self::BerlinToLondonAt5 => SpecId::LONDON,
             Self::Merge => SpecId::MERGE,
-            Self::Shanghai => SpecId::SHANGHAI,
-            Self::Cancun => SpecId::CANCUN,
+            Self::Shanghai | Self::MergeToShanghaiAtTime15k => SpecId::SHANGHAI,
+            Self::Cancun | Self::ShanghaiToCancunAtTime15k => SpecId::CANCUN,
             Self::ByzantiumToConstantinopleAt5 | Self::Constantinople => {
                 panic!("Overridden with PETERSBURG")
             }
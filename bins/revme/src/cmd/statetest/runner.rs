@@ -1,11 +1,10 @@
 use super::{
-    merkle_trie::{log_rlp_hash, state_merkle_trie_root},
     models::{SpecName, Test, TestSuite},
     utils::recover_address,
 };
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use revm::{
-    db::EmptyDB,
+    db::{log_rlp_hash, state_merkle_trie_root, EmptyDB},
     inspector_handle_register,
     inspectors::TracerEip3155,
     primitives::{
@@ -21,7 +20,7 @@ use std::{
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, OnceLock,
     },
     time::{Duration, Instant},
 };
@@ -53,10 +52,42 @@ pub enum TestErrorKind {
         expected_output: Option<Bytes>,
         got_output: Option<Bytes>,
     },
+    #[error("receipts root mismatch: got {got}, expected {expected}")]
+    ReceiptsRootMismatch { got: B256, expected: B256 },
+    #[error("block gas used mismatch: got {got}, expected {expected}")]
+    BlockGasUsedMismatch { got: u64, expected: u64 },
+    #[error("last block hash mismatch: got {got}, expected {expected}")]
+    LastBlockHashMismatch { got: B256, expected: B256 },
+    #[error("block {number} execution failed: {error}")]
+    BlockExecution { number: u64, error: String },
     #[error(transparent)]
     SerdeDeserialize(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
     #[error("thread panicked")]
     Panic,
+    #[error("differential execution {field} mismatch: got {got}, expected {expected}")]
+    DifferentialMismatch {
+        field: &'static str,
+        got: String,
+        expected: String,
+    },
+}
+
+/// Execution of the transaction with another execution backend, it is compared with the
+/// interpreter by [`set_differential_execution`].
+pub type DifferentialExecution = dyn Fn(&mut State<EmptyDB>, &Env, SpecId) -> EVMResultGeneric<ExecutionResult, Infallible>
+    + Send
+    + Sync;
+
+static DIFFERENTIAL_EXECUTION: OnceLock<Box<DifferentialExecution>> = OnceLock::new();
+
+/// Runs every test again with the execution and compares the result and the state root with
+/// the interpreter.
+///
+/// Only the first set execution is used.
+pub fn set_differential_execution(execution: Box<DifferentialExecution>) {
+    let _ = DIFFERENTIAL_EXECUTION.set(execution);
 }
 
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
@@ -237,6 +268,52 @@ fn check_evm_execution<EXT>(
     Ok(())
 }
 
+/// Executes the test with the differential execution and compares it with the interpreter.
+fn check_differential_execution<EXT>(
+    test_name: &str,
+    cache_state: &revm::CacheState,
+    env: &Env,
+    spec_id: SpecId,
+    exec_result: &EVMResultGeneric<ExecutionResult, Infallible>,
+    evm: &Evm<'_, EXT, &mut State<EmptyDB>>,
+) -> Result<(), TestError> {
+    let Some(execution) = DIFFERENTIAL_EXECUTION.get() else {
+        return Ok(());
+    };
+    let mut cache = cache_state.clone();
+    cache.set_state_clear_flag(SpecId::enabled(
+        spec_id,
+        revm::primitives::SpecId::SPURIOUS_DRAGON,
+    ));
+    let mut state = revm::db::State::builder()
+        .with_cached_prestate(cache)
+        .with_bundle_update()
+        .build();
+    let result = execution(&mut state, env, spec_id);
+
+    let expected_state_root = state_merkle_trie_root(evm.context.evm.db.cache.trie_account());
+    let state_root = state_merkle_trie_root(state.cache.trie_account());
+    let kind = if result != *exec_result {
+        TestErrorKind::DifferentialMismatch {
+            field: "result",
+            got: format!("{result:?}"),
+            expected: format!("{exec_result:?}"),
+        }
+    } else if state_root != expected_state_root {
+        TestErrorKind::DifferentialMismatch {
+            field: "state root",
+            got: state_root.to_string(),
+            expected: expected_state_root.to_string(),
+        }
+    } else {
+        return Ok(());
+    };
+    Err(TestError {
+        name: test_name.to_string(),
+        kind,
+    })
+}
+
 pub fn execute_test_suite(
     path: &Path,
     elapsed: &Arc<Mutex<Duration>>,
@@ -417,6 +494,9 @@ pub fn execute_test_suite(
                         &evm,
                         print_json_outcome,
                     );
+                    let output = output.and_then(|()| {
+                        check_differential_execution(&name, &cache_state, &env, spec_id, &res, &evm)
+                    });
                     let Err(e) = output else {
                         continue;
                     };
@@ -468,11 +548,38 @@ pub fn execute_test_suite(
 }
 
 pub fn run(
+    test_files: Vec<PathBuf>,
+    single_thread: bool,
+    trace: bool,
+    print_outcome: bool,
+    keep_going: bool,
+) -> Result<(), TestError> {
+    run_test_files(
+        test_files,
+        single_thread,
+        trace,
+        print_outcome,
+        keep_going,
+        execute_test_suite,
+    )
+}
+
+/// Function that executes all tests of the test file.
+pub type ExecuteTestFile = fn(
+    path: &Path,
+    elapsed: &Arc<Mutex<Duration>>,
+    trace: bool,
+    print_outcome: bool,
+) -> Result<(), TestError>;
+
+/// Runs the test files in parallel with the given test executor.
+pub fn run_test_files(
     test_files: Vec<PathBuf>,
     mut single_thread: bool,
     trace: bool,
     mut print_outcome: bool,
     keep_going: bool,
+    execute_test_file: ExecuteTestFile,
 ) -> Result<(), TestError> {
     // trace implies print_outcome
     if trace {
@@ -521,7 +628,7 @@ pub fn run(
                 (prev_idx, test_path)
             };
 
-            let result = execute_test_suite(&test_path, &elapsed, trace, print_outcome);
+            let result = execute_test_file(&test_path, &elapsed, trace, print_outcome);
 
             // Increment after the test is done.
             console_bar.inc(1);
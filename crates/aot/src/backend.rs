@@ -0,0 +1,433 @@
+//! Compiled bytecode cache and the `execute_frame` handle register.
+
+use crate::{
+    compiler::{AotError, Compiler},
+    runtime::CompiledBytecode,
+};
+use core::{
+    fmt, mem,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use revm::{
+    handler::register::EvmHandler,
+    interpreter::{opcode::InstructionTables, Contract, DecodedBytecode, EMPTY_SHARED_MEMORY},
+    primitives::{Bytecode, HashMap, B256},
+    Database,
+};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, TryLockError};
+
+/// Configuration of the [AotBackend].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AotConfig {
+    /// Number of frames that execute the bytecode with the interpreter before the bytecode is
+    /// compiled. With zero the bytecode is compiled when it is executed for the first time.
+    pub hot_threshold: u64,
+    /// Maximum number of compiled bytecodes, including the ones that failed to compile.
+    ///
+    /// Compiled code can't be freed, so after the limit is reached new bytecodes are not
+    /// compiled nor tracked and they are executed by the interpreter.
+    pub max_compiled: usize,
+    /// Maximum number of tracked bytecodes that did not become hot yet. When the limit is
+    /// reached their execution counts are dropped.
+    pub max_cold: usize,
+}
+
+impl Default for AotConfig {
+    fn default() -> Self {
+        Self {
+            hot_threshold: 16,
+            max_compiled: 4096,
+            max_cold: 65536,
+        }
+    }
+}
+
+/// Metrics of the [AotBackend].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AotMetrics {
+    /// Number of compiled bytecodes.
+    pub compiled: u64,
+    /// Number of bytecodes that failed to compile.
+    pub failed: u64,
+    /// Number of frames that were executed by the compiled code.
+    pub native_frames: u64,
+    /// Number of frames that were executed by the interpreter.
+    pub interpreted_frames: u64,
+}
+
+/// Compilation backend that compiles hot legacy bytecode to native code at runtime.
+///
+/// Compiled code is cached by the code hash. Clones share the compiled code and the metrics,
+/// so one backend can be used by all [Evm](revm::Evm) instances of a worker pool.
+///
+/// Bytecode is compiled synchronously by the frame that makes it hot, so that frame waits for
+/// the compilation. Compilations are not parallel, frames that become hot while another
+/// bytecode is compiled are interpreted and compiled on a later execution instead of waiting.
+#[derive(Clone)]
+pub struct AotBackend {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    config: AotConfig,
+    compiler: Mutex<Compiler>,
+    contracts: RwLock<Contracts>,
+    compiled: AtomicU64,
+    failed: AtomicU64,
+    native_frames: AtomicU64,
+    interpreted_frames: AtomicU64,
+}
+
+#[derive(Default)]
+struct Contracts {
+    map: HashMap<B256, Entry>,
+    /// Number of the cold entries.
+    cold: usize,
+}
+
+impl Contracts {
+    /// Returns true if no more bytecodes are compiled.
+    fn is_full(&self, config: &AotConfig) -> bool {
+        self.map.len() - self.cold >= config.max_compiled
+    }
+}
+
+enum Entry {
+    /// Bytecode is executed by the interpreter until it becomes hot.
+    Cold {
+        executions: AtomicU64,
+    },
+    Compiled(Arc<CompiledBytecode>),
+    Failed,
+}
+
+impl fmt::Debug for AotBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AotBackend")
+            .field("config", &self.inner.config)
+            .field("metrics", &self.metrics())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AotBackend {
+    /// Creates the backend that compiles for the host machine.
+    pub fn new(config: AotConfig) -> Result<Self, AotError> {
+        Ok(Self {
+            inner: Arc::new(Inner {
+                config,
+                compiler: Mutex::new(Compiler::new()?),
+                contracts: RwLock::default(),
+                compiled: AtomicU64::new(0),
+                failed: AtomicU64::new(0),
+                native_frames: AtomicU64::new(0),
+                interpreted_frames: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    /// Returns the configuration of the backend.
+    pub fn config(&self) -> &AotConfig {
+        &self.inner.config
+    }
+
+    /// Returns the metrics of the backend.
+    pub fn metrics(&self) -> AotMetrics {
+        AotMetrics {
+            compiled: self.inner.compiled.load(Ordering::Relaxed),
+            failed: self.inner.failed.load(Ordering::Relaxed),
+            native_frames: self.inner.native_frames.load(Ordering::Relaxed),
+            interpreted_frames: self.inner.interpreted_frames.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the compiled code of the contract, the bytecode is compiled when it becomes hot.
+    ///
+    /// Only analysed legacy bytecode with a code hash is compiled, initcode of CREATE does not
+    /// have one.
+    fn compiled(&self, contract: &Contract) -> Option<Arc<CompiledBytecode>> {
+        let hash = contract.hash.filter(|hash| *hash != B256::ZERO)?;
+        let Bytecode::LegacyAnalyzed(bytecode) = &contract.bytecode else {
+            return None;
+        };
+
+        let config = &self.inner.config;
+        let executions = {
+            let contracts = self.read();
+            match contracts.map.get(&hash) {
+                Some(Entry::Compiled(compiled)) => return Some(compiled.clone()),
+                Some(Entry::Failed) => return None,
+                Some(Entry::Cold { executions }) => {
+                    Some(executions.fetch_add(1, Ordering::Relaxed) + 1)
+                }
+                None if contracts.is_full(config) => return None,
+                None => None,
+            }
+        };
+        let executions = match executions {
+            Some(executions) => executions,
+            None => {
+                let mut contracts = self.write();
+                if !contracts.map.contains_key(&hash) {
+                    if contracts.cold >= config.max_cold {
+                        contracts
+                            .map
+                            .retain(|_, entry| !matches!(entry, Entry::Cold { .. }));
+                        contracts.cold = 0;
+                    }
+                    contracts.cold += 1;
+                }
+                match contracts.map.entry(hash).or_insert(Entry::Cold {
+                    executions: AtomicU64::new(0),
+                }) {
+                    Entry::Cold { executions } => executions.fetch_add(1, Ordering::Relaxed) + 1,
+                    _ => return None,
+                }
+            }
+        };
+        if executions <= config.hot_threshold {
+            return None;
+        }
+
+        // Bytecode stays cold and is compiled on a later execution if the compiler is busy.
+        let mut compiler = match self.inner.compiler.try_lock() {
+            Ok(compiler) => compiler,
+            Err(TryLockError::Poisoned(error)) => error.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        {
+            let contracts = self.read();
+            match contracts.map.get(&hash) {
+                // Other thread could have compiled the same bytecode.
+                Some(Entry::Compiled(compiled)) => return Some(compiled.clone()),
+                Some(Entry::Cold { .. }) if !contracts.is_full(config) => (),
+                _ => return None,
+            }
+        }
+        let (entry, compiled) = match compiler.compile(&DecodedBytecode::new(bytecode)) {
+            Ok(compiled) => {
+                self.inner.compiled.fetch_add(1, Ordering::Relaxed);
+                let compiled = Arc::new(compiled);
+                (Entry::Compiled(compiled.clone()), Some(compiled))
+            }
+            Err(_) => {
+                self.inner.failed.fetch_add(1, Ordering::Relaxed);
+                (Entry::Failed, None)
+            }
+        };
+        let mut contracts = self.write();
+        if let Some(Entry::Cold { .. }) = contracts.map.insert(hash, entry) {
+            contracts.cold -= 1;
+        }
+        if contracts.is_full(config) {
+            // Cold bytecodes are not compiled anymore.
+            contracts
+                .map
+                .retain(|_, entry| !matches!(entry, Entry::Cold { .. }));
+            contracts.cold = 0;
+        }
+        compiled
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Contracts> {
+        self.inner
+            .contracts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Contracts> {
+        self.inner
+            .contracts
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Provides access to an [AotBackend] instance.
+pub trait GetAotBackend {
+    /// Returns the associated [AotBackend].
+    fn get_aot_backend(&self) -> &AotBackend;
+}
+
+impl GetAotBackend for AotBackend {
+    #[inline]
+    fn get_aot_backend(&self) -> &AotBackend {
+        self
+    }
+}
+
+/// Register the `execute_frame` handle that executes the frames with the compiled code.
+///
+/// Frames that are not compiled are executed by the previous `execute_frame` handle. Inspector
+/// uses boxed instructions and execution budget is checked by the interpreter loop, so these
+/// frames are always interpreted.
+pub fn aot_handle_register<EXT: GetAotBackend, DB: Database>(
+    handler: &mut EvmHandler<'_, EXT, DB>,
+) {
+    let interpreted = handler.execution.execute_frame.clone();
+    handler.execution.execute_frame =
+        Arc::new(move |frame, shared_memory, instruction_tables, context| {
+            let backend = context.external.get_aot_backend();
+            let compiled = match instruction_tables {
+                InstructionTables::Plain(table)
+                    if context.evm.env.cfg.execution_budget.is_unlimited() =>
+                {
+                    backend
+                        .compiled(&frame.interpreter().contract)
+                        .map(|compiled| (compiled, table))
+                }
+                _ => None,
+            };
+            let Some((compiled, table)) = compiled else {
+                backend
+                    .inner
+                    .interpreted_frames
+                    .fetch_add(1, Ordering::Relaxed);
+                return interpreted(frame, shared_memory, instruction_tables, context);
+            };
+            backend.inner.native_frames.fetch_add(1, Ordering::Relaxed);
+
+            let interpreter = frame.interpreter_mut();
+            let memory = mem::replace(shared_memory, EMPTY_SHARED_MEMORY);
+            let next_action = compiled.run(interpreter, memory, table, context);
+            // Take the shared memory back.
+            *shared_memory = interpreter.take_memory();
+            Ok(next_action)
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::InMemoryDB,
+        primitives::{address, hex, keccak256, AccountInfo, Address, ResultAndState, TransactTo},
+        Evm,
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+    const CONTRACT: Address = address!("2000000000000000000000000000000000000000");
+    const CALLEE: Address = address!("3000000000000000000000000000000000000000");
+
+    /// Contract calls the callee and stores the call status and the remaining gas, callee
+    /// stores the counter of a loop.
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        let contract = hex!(
+            "600060006000600060007330000000000000000000000000000000000000005af1"
+            "6000555a60015500"
+        );
+        let callee = hex!("60055b600190038080558060025700");
+        for (address, code) in [(CONTRACT, &contract[..]), (CALLEE, &callee[..])] {
+            let code = Bytecode::new_raw(code.to_vec().into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(Default::default(), 1, keccak256(code.bytes()), code),
+            );
+        }
+        db
+    }
+
+    fn transact(backend: Option<&AotBackend>) -> ResultAndState {
+        let builder = Evm::builder().with_db(db()).modify_tx_env(|tx| {
+            tx.caller = CALLER;
+            tx.transact_to = TransactTo::Call(CONTRACT);
+        });
+        match backend {
+            Some(backend) => builder
+                .with_external_context(backend.clone())
+                .append_handler_register(aot_handle_register)
+                .build()
+                .transact(),
+            None => builder.build().transact(),
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn same_as_interpreter() {
+        let backend = AotBackend::new(AotConfig {
+            hot_threshold: 0,
+            ..Default::default()
+        })
+        .unwrap();
+        let expected = transact(None);
+        assert!(expected.result.is_success());
+        assert_eq!(transact(Some(&backend)), expected);
+        // Contract is executed again after the call returns.
+        assert_eq!(
+            backend.metrics(),
+            AotMetrics {
+                compiled: 2,
+                failed: 0,
+                native_frames: 3,
+                interpreted_frames: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn hot_threshold() {
+        let backend = AotBackend::new(AotConfig {
+            hot_threshold: 2,
+            ..Default::default()
+        })
+        .unwrap();
+        let expected = transact(None);
+        assert_eq!(transact(Some(&backend)), expected);
+        assert_eq!(backend.metrics().compiled, 0);
+        assert_eq!(backend.metrics().interpreted_frames, 3);
+
+        // Contract is executed twice by each transaction, so it becomes hot before the callee.
+        assert_eq!(transact(Some(&backend)), expected);
+        assert_eq!(backend.metrics().compiled, 1);
+        assert_eq!(transact(Some(&backend)), expected);
+        assert_eq!(
+            backend.metrics(),
+            AotMetrics {
+                compiled: 2,
+                failed: 0,
+                native_frames: 5,
+                interpreted_frames: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn bounded_entries() {
+        // Callee is not compiled after the contract.
+        let backend = AotBackend::new(AotConfig {
+            hot_threshold: 0,
+            max_compiled: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let expected = transact(None);
+        assert_eq!(transact(Some(&backend)), expected);
+        assert_eq!(transact(Some(&backend)), expected);
+        assert_eq!(
+            backend.metrics(),
+            AotMetrics {
+                compiled: 1,
+                failed: 0,
+                native_frames: 4,
+                interpreted_frames: 2,
+            }
+        );
+        assert_eq!(backend.read().map.len(), 1);
+
+        // Contract and callee drop the execution count of each other.
+        let backend = AotBackend::new(AotConfig {
+            hot_threshold: 2,
+            max_cold: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        for _ in 0..3 {
+            assert_eq!(transact(Some(&backend)), expected);
+        }
+        assert_eq!(backend.metrics().compiled, 0);
+        assert_eq!(backend.read().cold, 1);
+    }
+}
@@ -0,0 +1,548 @@
+//! Translation of the decoded legacy bytecode to native code.
+
+use crate::runtime::{exit, AotContext, CompiledBytecode, CompiledFunction};
+use core::{fmt, mem::offset_of};
+use cranelift_codegen::{
+    ir::{
+        condcodes::IntCC, types, AbiParam, Block, InstBuilder, MemFlags, SigRef, Signature, Value,
+    },
+    settings::{self, Configurable},
+    Context,
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Switch, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Module};
+use revm::interpreter::{opcode, DecodedBytecode, DecodedInstruction, STACK_LIMIT};
+
+/// Size of the stack word in bytes.
+const WORD: i32 = 32;
+
+/// Error of the JIT compiler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AotError {
+    /// Compiler backend does not support the host machine.
+    UnsupportedHost(String),
+    /// Bytecode could not be compiled.
+    Compile(String),
+}
+
+impl fmt::Display for AotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedHost(e) => write!(f, "unsupported host: {e}"),
+            Self::Compile(e) => write!(f, "compilation failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AotError {}
+
+/// Compiles the decoded bytecode to the functions of the JIT module.
+///
+/// Machine code of the compiled functions is never freed.
+pub(crate) struct Compiler {
+    module: JITModule,
+    context: Context,
+    builder_context: FunctionBuilderContext,
+}
+
+impl fmt::Debug for Compiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compiler")
+            .field("isa", &self.module.isa().triple())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Compiler {
+    /// Creates the compiler for the host machine.
+    pub(crate) fn new() -> Result<Self, AotError> {
+        let mut flags = settings::builder();
+        for (name, value) in [
+            ("opt_level", "speed"),
+            ("use_colocated_libcalls", "false"),
+            ("is_pic", "false"),
+        ] {
+            flags
+                .set(name, value)
+                .map_err(|e| AotError::UnsupportedHost(e.to_string()))?;
+        }
+        let isa = cranelift_native::builder()
+            .map_err(|e| AotError::UnsupportedHost(e.to_string()))?
+            .finish(settings::Flags::new(flags))
+            .map_err(|e| AotError::UnsupportedHost(e.to_string()))?;
+        // Compiled code addresses the context and the stack with 64-bit pointers.
+        if isa.pointer_type() != types::I64 {
+            return Err(AotError::UnsupportedHost(format!(
+                "{} pointers are not supported",
+                isa.pointer_type()
+            )));
+        }
+
+        let module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+        Ok(Self {
+            context: module.make_context(),
+            module,
+            builder_context: FunctionBuilderContext::new(),
+        })
+    }
+
+    /// Compiles the bytecode to native code.
+    pub(crate) fn compile(
+        &mut self,
+        decoded: &DecodedBytecode,
+    ) -> Result<CompiledBytecode, AotError> {
+        let result = self.translate_and_define(decoded);
+        self.module.clear_context(&mut self.context);
+        let id = result?;
+        self.module
+            .finalize_definitions()
+            .map_err(|e| AotError::Compile(e.to_string()))?;
+        let code = self.module.get_finalized_function(id);
+        // SAFETY: function has the signature of the compiled function and the module is never
+        // freed.
+        Ok(unsafe {
+            CompiledBytecode::new(core::mem::transmute::<*const u8, CompiledFunction>(code))
+        })
+    }
+
+    fn translate_and_define(
+        &mut self,
+        decoded: &DecodedBytecode,
+    ) -> Result<cranelift_module::FuncId, AotError> {
+        let ptr = types::I64;
+        let mut signature = self.module.make_signature();
+        signature.params.push(AbiParam::new(ptr));
+        signature.returns.push(AbiParam::new(types::I32));
+        let mut step = self.module.make_signature();
+        step.params
+            .extend([ptr, types::I32, types::I64].map(AbiParam::new));
+        step.returns.push(AbiParam::new(types::I32));
+
+        self.context.func.signature = signature.clone();
+        let builder = FunctionBuilder::new(&mut self.context.func, &mut self.builder_context);
+        Translator::new(builder, decoded, step).translate();
+
+        let id = self
+            .module
+            .declare_anonymous_function(&signature)
+            .map_err(|e| AotError::Compile(e.to_string()))?;
+        self.module
+            .define_function(id, &mut self.context)
+            .map_err(|e| AotError::Compile(e.to_string()))?;
+        Ok(id)
+    }
+}
+
+/// Translates the instruction stream to the body of the compiled function.
+///
+/// Stack length and remaining gas are kept in variables. Static gas of a run of the natively
+/// executed instructions, [DecodedInstruction::run_gas], is charged when the run is entered,
+/// so the instructions that are called from the instruction table see the same gas as in the
+/// interpreter.
+struct Translator<'a> {
+    builder: FunctionBuilder<'a>,
+    decoded: &'a DecodedBytecode,
+    /// Block of each instruction that starts a basic block.
+    blocks: Vec<Option<Block>>,
+    context: Value,
+    stack: Value,
+    stack_len: Variable,
+    gas: Variable,
+    step: SigRef,
+    /// Exit block with the exit code and the program counter parameters.
+    exit: Block,
+    /// Dynamic jump block with the target limbs and the program counter parameters.
+    jump: Block,
+}
+
+impl<'a> Translator<'a> {
+    fn new(
+        mut builder: FunctionBuilder<'a>,
+        decoded: &'a DecodedBytecode,
+        step: Signature,
+    ) -> Self {
+        let entry = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        builder.switch_to_block(entry);
+        let context = builder.block_params(entry)[0];
+
+        let stack_len = Variable::from_u32(0);
+        let gas = Variable::from_u32(1);
+        builder.declare_var(stack_len, types::I64);
+        builder.declare_var(gas, types::I64);
+        let load = |builder: &mut FunctionBuilder<'a>, offset: usize| {
+            builder
+                .ins()
+                .load(types::I64, MemFlags::trusted(), context, offset as i32)
+        };
+        let stack = load(&mut builder, offset_of!(AotContext, stack));
+        let value = load(&mut builder, offset_of!(AotContext, stack_len));
+        builder.def_var(stack_len, value);
+        let value = load(&mut builder, offset_of!(AotContext, gas_remaining));
+        builder.def_var(gas, value);
+
+        let exit = builder.create_block();
+        builder.append_block_param(exit, types::I32);
+        builder.append_block_param(exit, types::I64);
+        let jump = builder.create_block();
+        for _ in 0..5 {
+            builder.append_block_param(jump, types::I64);
+        }
+
+        let blocks = decoded
+            .instructions()
+            .iter()
+            .map(|instruction| instruction.block_start.then(|| builder.create_block()))
+            .collect();
+        let step = builder.import_signature(step);
+        Self {
+            builder,
+            decoded,
+            blocks,
+            context,
+            stack,
+            stack_len,
+            gas,
+            step,
+            exit,
+            jump,
+        }
+    }
+
+    fn translate(mut self) {
+        self.entry();
+
+        let instructions = self.decoded.instructions();
+        // Whether the current block is terminated.
+        let mut terminated = true;
+        // Whether the static gas of the current run of native instructions is charged.
+        let mut charged = false;
+        let mut index = 0;
+        while index < instructions.len() {
+            let instruction = instructions[index];
+            if let Some(block) = self.blocks[index] {
+                if !terminated {
+                    self.builder.ins().jump(block, &[]);
+                }
+                self.builder.switch_to_block(block);
+                terminated = false;
+                charged = false;
+            } else if terminated {
+                // Unreachable instruction.
+                let block = self.builder.create_block();
+                self.builder.switch_to_block(block);
+                terminated = false;
+            }
+            let pc = instruction.pc as u64;
+
+            if !instruction.native {
+                self.call_step(index, instruction);
+                // Next instruction continues in a new block or starts a basic block.
+                terminated = self.blocks.get(index + 1).is_none_or(Option::is_some);
+                charged = false;
+                index += 1;
+                continue;
+            }
+            if !charged {
+                self.charge(instruction.run_gas, pc);
+                charged = true;
+            }
+
+            let opcode = instruction.opcode;
+            match opcode {
+                opcode::PUSH1..=opcode::PUSH32 => match instruction.jump {
+                    None => {
+                        self.check_overflow(pc);
+                        let value = self.decoded.immediates()[instruction.immediate as usize];
+                        let top = self.top();
+                        for (i, limb) in value.as_limbs().iter().enumerate() {
+                            let limb = self.builder.ins().iconst(types::I64, *limb as i64);
+                            self.store(limb, top, i as i32 * 8);
+                        }
+                        self.add_len(1);
+                    }
+                    Some(target) => {
+                        // Pushed jump destination is not put on the stack.
+                        self.check_overflow(pc);
+                        index += 1;
+                        let jump = instructions[index];
+                        let target = self.blocks[target as usize].expect("jump destination");
+                        if jump.opcode == opcode::JUMP {
+                            self.builder.ins().jump(target, &[]);
+                        } else {
+                            let jump_pc = jump.pc as u64;
+                            self.check_underflow(1, jump_pc);
+                            let cond = self.word(1);
+                            self.add_len(-1);
+                            let next = self.blocks[index + 1].expect("block after JUMPI");
+                            self.builder.ins().brif(cond, target, &[], next, &[]);
+                        }
+                        terminated = true;
+                    }
+                },
+                opcode::DUP1..=opcode::DUP16 => {
+                    let n = (opcode - opcode::DUP1) as i64 + 1;
+                    self.check_underflow(n, pc);
+                    self.check_overflow(pc);
+                    let top = self.top();
+                    for i in 0..4 {
+                        let limb = self.load(top, i * 8 - n as i32 * WORD);
+                        self.store(limb, top, i * 8);
+                    }
+                    self.add_len(1);
+                }
+                opcode::SWAP1..=opcode::SWAP16 => {
+                    let n = (opcode - opcode::SWAP1) as i64 + 1;
+                    self.check_underflow(n + 1, pc);
+                    let top = self.top();
+                    for i in 0..4 {
+                        let a = self.load(top, i * 8 - WORD);
+                        let b = self.load(top, i * 8 - (n as i32 + 1) * WORD);
+                        self.store(b, top, i * 8 - WORD);
+                        self.store(a, top, i * 8 - (n as i32 + 1) * WORD);
+                    }
+                }
+                opcode::POP => {
+                    self.check_underflow(1, pc);
+                    self.add_len(-1);
+                }
+                opcode::JUMPDEST => {}
+                opcode::JUMP => {
+                    self.check_underflow(1, pc);
+                    let args = self.jump_args(1, pc);
+                    self.add_len(-1);
+                    self.builder.ins().jump(self.jump, &args);
+                    terminated = true;
+                }
+                opcode::JUMPI => {
+                    self.check_underflow(2, pc);
+                    let args = self.jump_args(1, pc);
+                    let cond = self.word(2);
+                    self.add_len(-2);
+                    let next = self.blocks[index + 1].expect("block after JUMPI");
+                    self.builder.ins().brif(cond, self.jump, &args, next, &[]);
+                    terminated = true;
+                }
+                _ => unreachable!("instruction is not executed natively"),
+            }
+            index += 1;
+        }
+
+        self.exit();
+        self.dynamic_jump();
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+    }
+
+    /// Dispatches to the program counter of the context.
+    ///
+    /// Execution starts at the first instruction or continues after a call or a create.
+    fn entry(&mut self) {
+        let pc = self.load_context(offset_of!(AotContext, pc));
+        let mut switch = Switch::new();
+        let instructions = self.decoded.instructions();
+        switch.set_entry(0, self.blocks[0].expect("first block"));
+        for (index, instruction) in instructions.iter().enumerate().skip(1) {
+            if matches!(
+                instructions[index - 1].opcode,
+                opcode::CALL
+                    | opcode::CALLCODE
+                    | opcode::DELEGATECALL
+                    | opcode::STATICCALL
+                    | opcode::CREATE
+                    | opcode::CREATE2
+            ) {
+                let block = self.blocks[index].expect("block after call");
+                switch.set_entry(instruction.pc as u128, block);
+            }
+        }
+        let fallback = self.builder.create_block();
+        switch.emit(&mut self.builder, pc, fallback);
+        self.builder.switch_to_block(fallback);
+        let code = self.builder.ins().iconst(types::I32, exit::FALLBACK as i64);
+        self.builder.ins().jump(self.exit, &[code, pc]);
+    }
+
+    /// Writes the stack length, the gas and the program counter to the context and returns
+    /// the exit code.
+    fn exit(&mut self) {
+        self.builder.switch_to_block(self.exit);
+        let (code, pc) = match self.builder.block_params(self.exit) {
+            [code, pc] => (*code, *pc),
+            _ => unreachable!(),
+        };
+        self.store_context();
+        self.builder.ins().store(
+            MemFlags::trusted(),
+            pc,
+            self.context,
+            offset_of!(AotContext, pc) as i32,
+        );
+        self.builder.ins().return_(&[code]);
+    }
+
+    /// Jumps to the JUMPDEST at the popped target.
+    fn dynamic_jump(&mut self) {
+        self.builder.switch_to_block(self.jump);
+        let params = self.builder.block_params(self.jump).to_vec();
+        let (limbs, pc) = (&params[..4], params[4]);
+        let high = self.builder.ins().bor(limbs[1], limbs[2]);
+        let high = self.builder.ins().bor(high, limbs[3]);
+
+        let invalid = self.builder.create_block();
+        let dispatch = self.builder.create_block();
+        self.builder.ins().brif(high, invalid, &[], dispatch, &[]);
+
+        self.builder.switch_to_block(dispatch);
+        let mut switch = Switch::new();
+        for (index, instruction) in self.decoded.instructions().iter().enumerate() {
+            if instruction.opcode == opcode::JUMPDEST {
+                let block = self.blocks[index].expect("jump destination");
+                switch.set_entry(instruction.pc as u128, block);
+            }
+        }
+        switch.emit(&mut self.builder, limbs[0], invalid);
+
+        self.builder.switch_to_block(invalid);
+        let code = self
+            .builder
+            .ins()
+            .iconst(types::I32, exit::INVALID_JUMP as i64);
+        self.builder.ins().jump(self.exit, &[code, pc]);
+    }
+
+    /// Executes the instruction from the instruction table.
+    fn call_step(&mut self, index: usize, instruction: DecodedInstruction) {
+        self.store_context();
+        let step = self.load_context(offset_of!(AotContext, step));
+        let opcode = self
+            .builder
+            .ins()
+            .iconst(types::I32, instruction.opcode as i64);
+        let pc = self.builder.ins().iconst(types::I64, instruction.pc as i64);
+        let call = self
+            .builder
+            .ins()
+            .call_indirect(self.step, step, &[self.context, opcode, pc]);
+        let proceed = self.builder.inst_results(call)[0];
+
+        let value = self.load_context(offset_of!(AotContext, stack_len));
+        self.builder.def_var(self.stack_len, value);
+        let value = self.load_context(offset_of!(AotContext, gas_remaining));
+        self.builder.def_var(self.gas, value);
+
+        let stopped = self.builder.ins().iconst(types::I32, exit::STOPPED as i64);
+        let next = match self.blocks.get(index + 1) {
+            Some(Some(block)) => *block,
+            Some(None) => self.builder.create_block(),
+            // Implicit STOP always stops.
+            None => {
+                self.builder.ins().jump(self.exit, &[stopped, pc]);
+                return;
+            }
+        };
+        self.builder
+            .ins()
+            .brif(proceed, next, &[], self.exit, &[stopped, pc]);
+        if self.blocks[index + 1].is_none() {
+            self.builder.switch_to_block(next);
+        }
+    }
+
+    /// Charges the gas or falls back to the interpreter at the program counter.
+    fn charge(&mut self, cost: u64, pc: u64) {
+        let gas = self.builder.use_var(self.gas);
+        let insufficient = self
+            .builder
+            .ins()
+            .icmp_imm(IntCC::UnsignedLessThan, gas, cost as i64);
+        self.exit_if(insufficient, exit::FALLBACK, pc);
+        let gas = self.builder.ins().iadd_imm(gas, -(cost as i64));
+        self.builder.def_var(self.gas, gas);
+    }
+
+    fn check_underflow(&mut self, n: i64, pc: u64) {
+        let len = self.builder.use_var(self.stack_len);
+        let underflow = self.builder.ins().icmp_imm(IntCC::UnsignedLessThan, len, n);
+        self.exit_if(underflow, exit::STACK_UNDERFLOW, pc);
+    }
+
+    fn check_overflow(&mut self, pc: u64) {
+        let len = self.builder.use_var(self.stack_len);
+        let overflow = self
+            .builder
+            .ins()
+            .icmp_imm(IntCC::Equal, len, STACK_LIMIT as i64);
+        self.exit_if(overflow, exit::STACK_OVERFLOW, pc);
+    }
+
+    /// Exits with the code if the condition is true.
+    fn exit_if(&mut self, cond: Value, code: u32, pc: u64) {
+        let code = self.builder.ins().iconst(types::I32, code as i64);
+        let pc = self.builder.ins().iconst(types::I64, pc as i64);
+        let next = self.builder.create_block();
+        self.builder
+            .ins()
+            .brif(cond, self.exit, &[code, pc], next, &[]);
+        self.builder.switch_to_block(next);
+    }
+
+    /// Returns the address of the first free stack slot.
+    fn top(&mut self) -> Value {
+        let len = self.builder.use_var(self.stack_len);
+        let offset = self.builder.ins().ishl_imm(len, 5);
+        self.builder.ins().iadd(self.stack, offset)
+    }
+
+    fn add_len(&mut self, n: i64) {
+        let len = self.builder.use_var(self.stack_len);
+        let len = self.builder.ins().iadd_imm(len, n);
+        self.builder.def_var(self.stack_len, len);
+    }
+
+    /// Returns whether the `n`th word from the top of the stack is not zero.
+    fn word(&mut self, n: i32) -> Value {
+        let top = self.top();
+        let limbs: Vec<_> = (0..4).map(|i| self.load(top, i * 8 - n * WORD)).collect();
+        let value = self.builder.ins().bor(limbs[0], limbs[1]);
+        let value = self.builder.ins().bor(value, limbs[2]);
+        self.builder.ins().bor(value, limbs[3])
+    }
+
+    /// Returns the arguments of the dynamic jump to the `n`th word from the top of the stack.
+    fn jump_args(&mut self, n: i32, pc: u64) -> Vec<Value> {
+        let top = self.top();
+        let mut args: Vec<_> = (0..4).map(|i| self.load(top, i * 8 - n * WORD)).collect();
+        args.push(self.builder.ins().iconst(types::I64, pc as i64));
+        args
+    }
+
+    fn load(&mut self, address: Value, offset: i32) -> Value {
+        self.builder
+            .ins()
+            .load(types::I64, MemFlags::trusted(), address, offset)
+    }
+
+    fn store(&mut self, value: Value, address: Value, offset: i32) {
+        self.builder
+            .ins()
+            .store(MemFlags::trusted(), value, address, offset);
+    }
+
+    fn load_context(&mut self, offset: usize) -> Value {
+        self.load(self.context, offset as i32)
+    }
+
+    /// Writes the stack length and the gas to the context.
+    fn store_context(&mut self) {
+        let len = self.builder.use_var(self.stack_len);
+        let gas = self.builder.use_var(self.gas);
+        self.store(len, self.context, offset_of!(AotContext, stack_len) as i32);
+        self.store(
+            gas,
+            self.context,
+            offset_of!(AotContext, gas_remaining) as i32,
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Just-in-time compilation backend of revm.
+//!
+//! Hot legacy bytecode is compiled at runtime to native code with [Cranelift](https://cranelift.dev) and
+//! executed instead of the [Interpreter](revm::interpreter::Interpreter) loop. The backend is
+//! registered with [aot_handle_register] that replaces the `execute_frame` handle:
+//!
+//! ```
+//! use revm::{db::EmptyDB, Evm};
+//! use revm_aot::{aot_handle_register, AotBackend, AotConfig};
+//!
+//! let backend = AotBackend::new(AotConfig::default()).unwrap();
+//! let mut evm = Evm::builder()
+//!     .with_db(EmptyDB::default())
+//!     .with_external_context(backend)
+//!     .append_handler_register(aot_handle_register)
+//!     .build();
+//! ```
+//!
+//! Stack and control flow instructions (PUSH, DUP, SWAP, POP, JUMP, JUMPI and JUMPDEST) are
+//! compiled to native code, other instructions are called from the instruction table, so the
+//! compiled code has the semantics of the interpreter. Frames fall back to the interpreter
+//! when the bytecode is not compiled: EOF and initcode, bytecode that did not become hot or
+//! that became hot after [AotConfig::max_compiled] was reached, execution with an inspector or
+//! with an execution budget. Compiled code falls back to the
+//! interpreter in the middle of the frame when the gas is not enough for the next natively
+//! executed instructions.
+//!
+//! The `jit_statetest` example runs the state tests with the backend and compares them with
+//! the interpreter.
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+mod backend;
+mod compiler;
+mod runtime;
+
+pub use backend::{aot_handle_register, AotBackend, AotConfig, AotMetrics, GetAotBackend};
+pub use compiler::AotError;
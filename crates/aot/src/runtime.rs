@@ -0,0 +1,264 @@
+//! Execution of the compiled bytecode.
+
+use revm::{
+    interpreter::{
+        opcode::InstructionTable, Host, InstructionResult, Interpreter, InterpreterAction,
+        SharedMemory, STACK_LIMIT,
+    },
+    primitives::U256,
+};
+
+/// Compiled function of the bytecode.
+pub(crate) type CompiledFunction = unsafe extern "C" fn(context: *mut AotContext) -> u32;
+
+/// Trampoline that executes the instruction from the instruction table.
+///
+/// Returns zero if the interpreter stopped.
+pub(crate) type StepFunction =
+    unsafe extern "C" fn(context: *mut AotContext, opcode: u32, pc: u64) -> u32;
+
+/// Exit codes of the compiled function.
+pub(crate) mod exit {
+    /// Instruction from the instruction table stopped the interpreter.
+    pub(crate) const STOPPED: u32 = 0;
+    /// Execution continues in the interpreter at the program counter.
+    pub(crate) const FALLBACK: u32 = 1;
+    pub(crate) const STACK_UNDERFLOW: u32 = 2;
+    pub(crate) const STACK_OVERFLOW: u32 = 3;
+    pub(crate) const INVALID_JUMP: u32 = 4;
+}
+
+/// State shared by the compiled function and the [step] trampoline.
+///
+/// Stack length and remaining gas are kept by the compiled function and written back before
+/// every instruction that is executed by the instruction table.
+#[repr(C)]
+pub(crate) struct AotContext {
+    /// Stack buffer of the interpreter, its capacity is [STACK_LIMIT].
+    pub(crate) stack: *mut U256,
+    pub(crate) stack_len: u64,
+    pub(crate) gas_remaining: u64,
+    /// Program counter where the execution starts, and where it exited.
+    pub(crate) pc: u64,
+    pub(crate) step: StepFunction,
+    interpreter: *mut Interpreter,
+    host: *mut (),
+    table: *const (),
+}
+
+/// Bytecode that is compiled to native code.
+#[derive(Debug)]
+pub(crate) struct CompiledBytecode {
+    function: CompiledFunction,
+}
+
+impl CompiledBytecode {
+    /// # Safety
+    ///
+    /// Function has to be compiled by [Compiler](crate::compiler::Compiler) and its code must
+    /// outlive the returned value.
+    pub(crate) unsafe fn new(function: CompiledFunction) -> Self {
+        Self { function }
+    }
+
+    /// Executes the compiled bytecode until the interpreter returns or stops.
+    ///
+    /// Interpreter has to execute the bytecode that this code was compiled from.
+    pub(crate) fn run<H: Host>(
+        &self,
+        interpreter: &mut Interpreter,
+        shared_memory: SharedMemory,
+        instruction_table: &InstructionTable<H>,
+        host: &mut H,
+    ) -> InterpreterAction {
+        interpreter.next_action = InterpreterAction::None;
+        interpreter.shared_memory = shared_memory;
+        if interpreter.instruction_result != InstructionResult::Continue {
+            return interpreter.take_next_action();
+        }
+
+        // Compiled code writes the stack buffer directly, deserialized stack can have
+        // a smaller capacity.
+        let stack = interpreter.stack.data_mut();
+        stack.reserve_exact(STACK_LIMIT.saturating_sub(stack.len()));
+        let (stack, stack_len) = (stack.as_mut_ptr(), stack.len() as u64);
+        let mut context = AotContext {
+            stack,
+            stack_len,
+            gas_remaining: interpreter.gas.remaining(),
+            pc: interpreter.program_counter() as u64,
+            step: step::<H>,
+            interpreter: interpreter as *mut Interpreter,
+            host: (host as *mut H).cast(),
+            table: (instruction_table as *const InstructionTable<H>).cast(),
+        };
+        // SAFETY: context points to the interpreter, host and table of the matching types,
+        // and the function was compiled from the bytecode of the interpreter.
+        let exit = unsafe { (self.function)(&mut context) };
+        sync(&context, interpreter);
+
+        let pc = context.pc as usize;
+        let result = match exit {
+            exit::STOPPED => return interpreter.take_next_action(),
+            exit::FALLBACK => {
+                // SAFETY: compiled function exits at the program counter of an instruction.
+                interpreter.instruction_pointer = unsafe { interpreter.bytecode.as_ptr().add(pc) };
+                let shared_memory = interpreter.take_memory();
+                return interpreter.run(shared_memory, instruction_table, host);
+            }
+            exit::STACK_UNDERFLOW => InstructionResult::StackUnderflow,
+            exit::STACK_OVERFLOW => InstructionResult::StackOverflow,
+            exit::INVALID_JUMP => InstructionResult::InvalidJump,
+            _ => unreachable!("unknown exit code {exit}"),
+        };
+        interpreter.instruction_result = result;
+        // SAFETY: program counter of the instruction is in bounds of the bytecode.
+        interpreter.instruction_pointer = unsafe { interpreter.bytecode.as_ptr().add(pc + 1) };
+        interpreter.take_next_action()
+    }
+}
+
+/// Writes the stack length and the remaining gas of the compiled code to the interpreter.
+#[inline]
+fn sync(context: &AotContext, interpreter: &mut Interpreter) {
+    // SAFETY: compiled code keeps the length in the capacity and writes the pushed values.
+    unsafe {
+        interpreter
+            .stack
+            .data_mut()
+            .set_len(context.stack_len as usize)
+    };
+    let spent = interpreter.gas.remaining() - context.gas_remaining;
+    let _ = interpreter.gas.record_cost(spent);
+}
+
+/// Executes the instruction from the instruction table.
+///
+/// # Safety
+///
+/// Context has to be created by [CompiledBytecode::run] with the same host type.
+unsafe extern "C" fn step<H: Host>(context: *mut AotContext, opcode: u32, pc: u64) -> u32 {
+    let context = &mut *context;
+    let interpreter = &mut *context.interpreter;
+    let host = &mut *context.host.cast::<H>();
+    let table = &*context.table.cast::<InstructionTable<H>>();
+
+    sync(context, interpreter);
+    interpreter.instruction_pointer = interpreter.bytecode.as_ptr().add(pc as usize + 1);
+    table[opcode as usize](interpreter, host);
+    context.stack_len = interpreter.stack.len() as u64;
+    context.gas_remaining = interpreter.gas.remaining();
+    (interpreter.instruction_result == InstructionResult::Continue) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+    use revm::{
+        interpreter::{
+            analysis::to_analysed, opcode::make_instruction_table, Contract, DecodedBytecode,
+            DummyHost,
+        },
+        primitives::{Bytecode, Bytes, CancunSpec},
+    };
+
+    /// Executes the bytecode from the program counter with the compiled code and with
+    /// [Interpreter::run] and compares the interpreters.
+    fn assert_same_as_run(code: &[u8], gas_limit: u64, pc: usize) {
+        let table = make_instruction_table::<DummyHost, CancunSpec>();
+        let bytecode = to_analysed(Bytecode::new_raw(Bytes::copy_from_slice(code)));
+        let decoded = match &bytecode {
+            Bytecode::LegacyAnalyzed(bytecode) => DecodedBytecode::new(bytecode),
+            _ => unreachable!(),
+        };
+        let compiled = Compiler::new().unwrap().compile(&decoded).unwrap();
+        let contract = Contract::new(
+            Bytes::new(),
+            bytecode,
+            None,
+            Default::default(),
+            Default::default(),
+            U256::ZERO,
+        );
+
+        let run = |native: bool| {
+            let mut interpreter = Interpreter::new(contract.clone(), gas_limit, false);
+            interpreter.instruction_pointer = unsafe { interpreter.bytecode.as_ptr().add(pc) };
+            let mut host = DummyHost::default();
+            let action = if native {
+                compiled.run(&mut interpreter, SharedMemory::new(), &table, &mut host)
+            } else {
+                interpreter.run(SharedMemory::new(), &table, &mut host)
+            };
+            // Gas and stack of the halted interpreter are not used. Static gas of the natively
+            // executed instructions is charged in advance and jump destination pushed right
+            // before the jump is not put on the stack.
+            let halted = interpreter.instruction_result.is_error();
+            (
+                interpreter.instruction_result,
+                interpreter.program_counter(),
+                (!halted).then(|| (action, interpreter.gas, interpreter.stack.data().clone())),
+            )
+        };
+        assert_eq!(
+            run(true),
+            run(false),
+            "code {code:02x?}, gas limit {gas_limit}"
+        );
+    }
+
+    #[test]
+    fn same_as_run() {
+        let codes: [&[u8]; 11] = [
+            // loop that decrements the counter from 5 and stores the remaining gas to memory
+            &[
+                0x60, 0x05, 0x5b, 0x60, 0x01, 0x90, 0x03, 0x80, 0x5a, 0x81, 0x52, 0x80, 0x60, 0x02,
+                0x57, 0x60, 0x20, 0x60, 0x00, 0xf3,
+            ],
+            // DUP3, SWAP2 and the sum of the stack is returned
+            &[
+                0x60, 0x01, 0x60, 0x02, 0x60, 0x03, 0x82, 0x91, 0x01, 0x01, 0x01, 0x60, 0x00, 0x52,
+                0x60, 0x20, 0x60, 0x00, 0xf3,
+            ],
+            // JUMPI to the computed target
+            &[
+                0x60, 0x01, 0x60, 0x04, 0x60, 0x05, 0x01, 0x57, 0x00, 0x5b, 0x60, 0x2a, 0x60, 0x00,
+                0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+            ],
+            // GAS after the run of native instructions
+            &[
+                0x60, 0x01, 0x50, 0x5a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+            ],
+            // invalid jump
+            &[0x60, 0x03, 0x56, 0x00],
+            // invalid jump to the target that does not fit in 64 bits
+            &[0x68, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x0a, 0x56, 0x5b],
+            // stack underflow
+            &[0x60, 0x01, 0x90],
+            // stack underflow in the resolved jump
+            &[0x60, 0x04, 0x57, 0x00, 0x5b],
+            // stack overflow in the loop
+            &[0x5b, 0x60, 0x01, 0x60, 0x00, 0x56],
+            // out of gas in the run of native instructions
+            &[0x60, 0x01, 0x60, 0x01, 0x60, 0x01, 0x60, 0x01],
+            // truncated PUSH
+            &[0x60, 0x01, 0x62, 0x02, 0x03],
+        ];
+        for code in codes {
+            for gas_limit in [5, 15, 40, 1_000_000] {
+                assert_same_as_run(code, gas_limit, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn fallback_at_unknown_pc() {
+        // Execution continues at the JUMPDEST of the loop.
+        let code = [
+            0x60, 0x05, 0x5b, 0x60, 0x01, 0x90, 0x03, 0x80, 0x5a, 0x81, 0x52, 0x80, 0x60, 0x02,
+            0x57, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ];
+        assert_same_as_run(&code, 1_000_000, 2);
+    }
+}
@@ -13,7 +13,8 @@ pub trait Host {
 
     /// Load an account.
     ///
-    /// Returns (is_cold, is_new_account)
+    /// Returns (is_cold, is_new_account) and if the account is EIP-7702 delegated,
+    /// whether the delegated account is cold.
     fn load_account(&mut self, address: Address) -> Option<LoadAccountResult>;
 
     /// Get the block hash of the given block `number`.
@@ -22,11 +23,15 @@ pub trait Host {
     /// Get balance of `address` and if the account is cold.
     fn balance(&mut self, address: Address) -> Option<(U256, bool)>;
 
-    /// Get code of `address` and if the account is cold.
-    fn code(&mut self, address: Address) -> Option<(Bytecode, bool)>;
+    /// Get code of `address` and the result of the account load.
+    ///
+    /// Code of the delegated account is returned for the EIP-7702 delegated accounts.
+    fn code(&mut self, address: Address) -> Option<(Bytecode, LoadAccountResult)>;
 
-    /// Get code hash of `address` and if the account is cold.
-    fn code_hash(&mut self, address: Address) -> Option<(B256, bool)>;
+    /// Get code hash of `address` and the result of the account load.
+    ///
+    /// Code hash of the delegated account is returned for the EIP-7702 delegated accounts.
+    fn code_hash(&mut self, address: Address) -> Option<(B256, LoadAccountResult)>;
 
     /// Get storage value of `address` at `index` and if the account is cold.
     fn sload(&mut self, address: Address, index: U256) -> Option<(U256, bool)>;
@@ -70,6 +75,10 @@ pub struct LoadAccountResult {
     pub is_cold: bool,
     /// Is account empty, if true account is not created.
     pub is_empty: bool,
+    /// If account is EIP-7702 delegated, is the delegated account cold loaded.
+    ///
+    /// `None` if the account is not delegated.
+    pub is_delegate_account_cold: Option<bool>,
 }
 
 /// Result of a selfdestruct instruction.
@@ -61,13 +61,13 @@ impl Host for DummyHost {
     }
 
     #[inline]
-    fn code(&mut self, _address: Address) -> Option<(Bytecode, bool)> {
-        Some((Bytecode::default(), false))
+    fn code(&mut self, _address: Address) -> Option<(Bytecode, LoadAccountResult)> {
+        Some((Bytecode::default(), LoadAccountResult::default()))
     }
 
     #[inline]
-    fn code_hash(&mut self, __address: Address) -> Option<(B256, bool)> {
-        Some((KECCAK_EMPTY, false))
+    fn code_hash(&mut self, __address: Address) -> Option<(B256, LoadAccountResult)> {
+        Some((KECCAK_EMPTY, LoadAccountResult::default()))
     }
 
     #[inline]
@@ -1,4 +1,4 @@
-use crate::primitives::{HaltReason, OutOfGasError, SuccessReason};
+use crate::primitives::{HaltReason, InterruptReason, OutOfGasError, SuccessReason};
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
@@ -19,6 +19,8 @@ pub enum InstructionResult {
 
     // Actions
     CallOrCreate = 0x20,
+    /// Execution is suspended before the current instruction, it can be resumed later.
+    Suspend,
 
     // error codes
     OutOfGas = 0x50,
@@ -53,6 +55,12 @@ pub enum InstructionResult {
     EOFOpcodeDisabledInLegacy,
     /// EOF function stack overflow
     EOFFunctionStackOverflow,
+    /// Maximum number of instructions of the execution budget was executed.
+    InstructionLimitReached,
+    /// Time limit of the execution budget was exceeded.
+    TimeLimitReached,
+    /// Execution was cancelled.
+    Cancelled,
 }
 
 impl From<SuccessReason> for InstructionResult {
@@ -93,6 +101,11 @@ impl From<HaltReason> for InstructionResult {
             HaltReason::CallNotAllowedInsideStatic => Self::CallNotAllowedInsideStatic,
             HaltReason::OutOfFunds => Self::OutOfFunds,
             HaltReason::CallTooDeep => Self::CallTooDeep,
+            HaltReason::ExecutionInterrupted(reason) => match reason {
+                InterruptReason::InstructionLimit => Self::InstructionLimitReached,
+                InterruptReason::TimeLimit => Self::TimeLimitReached,
+                InterruptReason::Cancelled => Self::Cancelled,
+            },
             #[cfg(feature = "optimism")]
             HaltReason::FailedDeposit => Self::FatalExternalError,
         }
@@ -145,6 +158,9 @@ macro_rules! return_error {
             | InstructionResult::ReturnContractInNotInitEOF
             | InstructionResult::EOFOpcodeDisabledInLegacy
             | InstructionResult::EOFFunctionStackOverflow
+            | InstructionResult::InstructionLimitReached
+            | InstructionResult::TimeLimitReached
+            | InstructionResult::Cancelled
     };
 }
 
@@ -178,6 +194,8 @@ pub enum SuccessOrHalt {
     InternalContinue,
     /// Internal instruction that signals call or create.
     InternalCallOrCreate,
+    /// Internal instruction that signals the execution was suspended.
+    InternalSuspend,
 }
 
 impl SuccessOrHalt {
@@ -227,6 +245,7 @@ impl From<InstructionResult> for SuccessOrHalt {
             InstructionResult::SelfDestruct => Self::Success(SuccessReason::SelfDestruct),
             InstructionResult::Revert => Self::Revert,
             InstructionResult::CallOrCreate => Self::InternalCallOrCreate, // used only in interpreter loop
+            InstructionResult::Suspend => Self::InternalSuspend, // used only in interpreter loop
             InstructionResult::CallTooDeep => Self::Halt(HaltReason::CallTooDeep), // not gonna happen for first call
             InstructionResult::OutOfFunds => Self::Halt(HaltReason::OutOfFunds), // Check for first call is done separately.
             InstructionResult::OutOfGas => Self::Halt(HaltReason::OutOfGas(OutOfGasError::Basic)),
@@ -269,6 +288,15 @@ impl From<InstructionResult> for SuccessOrHalt {
             InstructionResult::FatalExternalError => Self::FatalExternalError,
             InstructionResult::EOFOpcodeDisabledInLegacy => Self::Halt(HaltReason::OpcodeNotFound),
             InstructionResult::EOFFunctionStackOverflow => Self::FatalExternalError,
+            InstructionResult::InstructionLimitReached => Self::Halt(
+                HaltReason::ExecutionInterrupted(InterruptReason::InstructionLimit),
+            ),
+            InstructionResult::TimeLimitReached => {
+                Self::Halt(HaltReason::ExecutionInterrupted(InterruptReason::TimeLimit))
+            }
+            InstructionResult::Cancelled => {
+                Self::Halt(HaltReason::ExecutionInterrupted(InterruptReason::Cancelled))
+            }
             InstructionResult::ReturnContract => {
                 panic!("Unexpected EOF internal Return Contract")
             }
@@ -287,6 +315,7 @@ mod tests {
             return_revert!() => {}
             return_ok!() => {}
             InstructionResult::CallOrCreate => {}
+            InstructionResult::Suspend => {}
         }
     }
 
@@ -340,6 +369,9 @@ mod tests {
             InstructionResult::CreateContractStartingWithEF,
             InstructionResult::CreateInitCodeSizeLimit,
             InstructionResult::FatalExternalError,
+            InstructionResult::InstructionLimitReached,
+            InstructionResult::TimeLimitReached,
+            InstructionResult::Cancelled,
         ];
 
         for result in error_results {
@@ -11,7 +11,7 @@ use crate::{
     interpreter::Interpreter,
     primitives::{Address, Bytes, Eof, Spec, SpecId::*, U256},
     CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, EOFCreateInput, Host,
-    InstructionResult, InterpreterAction, InterpreterResult, LoadAccountResult, MAX_INITCODE_SIZE,
+    InstructionResult, InterpreterAction, InterpreterResult, MAX_INITCODE_SIZE,
 };
 use core::{cmp::max, ops::Range};
 use std::boxed::Box;
@@ -172,6 +172,15 @@ pub fn extcall_gas_calc<H: Host + ?Sized>(
         gas!(interpreter, gas::COLD_ACCOUNT_ACCESS_COST, None);
     }
 
+    // EIP-7702: Loading the code of the delegated account is charged as an account access.
+    if let Some(is_delegate_account_cold) = load_result.is_delegate_account_cold {
+        gas!(
+            interpreter,
+            gas::warm_cold_cost(is_delegate_account_cold),
+            None
+        );
+    }
+
     // TODO(EOF) is_empty should only be checked on delegatecall
     let call_cost = gas::call_cost(
         BerlinSpec::SPEC_ID,
@@ -385,13 +394,14 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, is_empty }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
+    let is_empty = account_load.is_empty;
     let Some(mut gas_limit) = calc_call_gas::<SPEC>(
         interpreter,
-        is_cold,
+        account_load,
         has_transfer,
         is_empty,
         local_gas_limit,
@@ -435,14 +445,14 @@ pub fn call_code<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, .. }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
 
     let Some(mut gas_limit) = calc_call_gas::<SPEC>(
         interpreter,
-        is_cold,
+        account_load,
         value != U256::ZERO,
         false,
         local_gas_limit,
@@ -486,12 +496,12 @@ pub fn delegate_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, .. }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
     let Some(gas_limit) =
-        calc_call_gas::<SPEC>(interpreter, is_cold, false, false, local_gas_limit)
+        calc_call_gas::<SPEC>(interpreter, account_load, false, false, local_gas_limit)
     else {
         return;
     };
@@ -527,13 +537,13 @@ pub fn static_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, .. }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
 
     let Some(gas_limit) =
-        calc_call_gas::<SPEC>(interpreter, is_cold, false, false, local_gas_limit)
+        calc_call_gas::<SPEC>(interpreter, account_load, false, false, local_gas_limit)
     else {
         return;
     };
@@ -42,12 +42,16 @@ pub fn selfbalance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
 
 pub fn extcodesize<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     pop_address!(interpreter, address);
-    let Some((code, is_cold)) = host.code(address) else {
+    let Some((code, load)) = host.code(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
     if SPEC::enabled(BERLIN) {
-        gas!(interpreter, warm_cold_cost(is_cold));
+        gas!(interpreter, warm_cold_cost(load.is_cold));
+        // EIP-7702: Loading the code of the delegated account is charged as an account access.
+        if let Some(is_delegate_account_cold) = load.is_delegate_account_cold {
+            gas!(interpreter, warm_cold_cost(is_delegate_account_cold));
+        }
     } else if SPEC::enabled(TANGERINE) {
         gas!(interpreter, 700);
     } else {
@@ -61,12 +65,16 @@ pub fn extcodesize<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
 pub fn extcodehash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CONSTANTINOPLE);
     pop_address!(interpreter, address);
-    let Some((code_hash, is_cold)) = host.code_hash(address) else {
+    let Some((code_hash, load)) = host.code_hash(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
     if SPEC::enabled(BERLIN) {
-        gas!(interpreter, warm_cold_cost(is_cold));
+        gas!(interpreter, warm_cold_cost(load.is_cold));
+        // EIP-7702: Loading the code of the delegated account is charged as an account access.
+        if let Some(is_delegate_account_cold) = load.is_delegate_account_cold {
+            gas!(interpreter, warm_cold_cost(is_delegate_account_cold));
+        }
     } else if SPEC::enabled(ISTANBUL) {
         gas!(interpreter, 700);
     } else {
@@ -79,7 +87,7 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     pop_address!(interpreter, address);
     pop!(interpreter, memory_offset, code_offset, len_u256);
 
-    let Some((code, is_cold)) = host.code(address) else {
+    let Some((code, load)) = host.code(address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
@@ -87,8 +95,12 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
     let len = as_usize_or_fail!(interpreter, len_u256);
     gas_or_fail!(
         interpreter,
-        gas::extcodecopy_cost(SPEC::SPEC_ID, len as u64, is_cold)
+        gas::extcodecopy_cost(SPEC::SPEC_ID, len as u64, load.is_cold)
     );
+    // EIP-7702: Loading the code of the delegated account is charged as an account access.
+    if let Some(is_delegate_account_cold) = load.is_delegate_account_cold {
+        gas!(interpreter, warm_cold_cost(is_delegate_account_cold));
+    }
     if len == 0 {
         return;
     }
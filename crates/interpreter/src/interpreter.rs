@@ -1,11 +1,15 @@
 pub mod analysis;
+mod budget;
 mod contract;
+mod decoded;
 #[cfg(feature = "serde")]
 pub mod serde;
 mod shared_memory;
 mod stack;
 
+pub use budget::BudgetTracker;
 pub use contract::Contract;
+pub use decoded::{DecodedBytecode, DecodedInstruction};
 pub use shared_memory::{num_words, SharedMemory, EMPTY_SHARED_MEMORY};
 pub use stack::{Stack, STACK_LIMIT};
 
@@ -364,7 +368,38 @@ impl Interpreter {
         while self.instruction_result == InstructionResult::Continue {
             self.step(instruction_table, host);
         }
+        self.take_next_action()
+    }
 
+    /// Executes the interpreter until it returns or stops, or until the budget is exceeded.
+    pub fn run_with_budget<FN, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+        budget: &mut BudgetTracker,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+        // main loop
+        while self.instruction_result == InstructionResult::Continue {
+            if let Err(result) = budget.step() {
+                self.instruction_result = result;
+                break;
+            }
+            self.step(instruction_table, host);
+        }
+        self.take_next_action()
+    }
+
+    /// Returns the action of the stopped interpreter loop.
+    ///
+    /// It is used by the execution backends that run the instructions outside of
+    /// [Interpreter::run].
+    pub fn take_next_action(&mut self) -> InterpreterAction {
         // Return next action if it is some.
         if self.next_action.is_some() {
             return core::mem::take(&mut self.next_action);
@@ -0,0 +1,120 @@
+use crate::{primitives::ExecutionBudget, InstructionResult};
+use core::time::Duration;
+
+/// Tracks the [ExecutionBudget] of a transaction over all of its call frames.
+#[derive(Clone, Debug)]
+pub struct BudgetTracker {
+    /// Budget of the execution.
+    budget: ExecutionBudget,
+    /// Number of executed instructions.
+    instructions: u64,
+    /// Number of executed instructions at which the interrupts are checked next.
+    next_check: u64,
+    /// Clock time when the execution started.
+    started: Duration,
+}
+
+impl BudgetTracker {
+    /// Starts tracking the budget.
+    pub fn new(budget: ExecutionBudget) -> Self {
+        let started = budget
+            .time_limit
+            .as_ref()
+            .map(|limit| (limit.clock)())
+            .unwrap_or_default();
+        Self {
+            budget,
+            instructions: 0,
+            next_check: 0,
+            started,
+        }
+    }
+
+    /// Returns the number of executed instructions.
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    /// Counts the next instruction, returns the halt result if the budget is exceeded.
+    #[inline]
+    pub fn step(&mut self) -> Result<(), InstructionResult> {
+        if self
+            .budget
+            .max_instructions
+            .is_some_and(|max| self.instructions >= max)
+        {
+            return Err(InstructionResult::InstructionLimitReached);
+        }
+        if self.instructions == self.next_check {
+            self.check_interrupt()?;
+            self.next_check += ExecutionBudget::CHECK_INTERVAL;
+        }
+        self.instructions += 1;
+        Ok(())
+    }
+
+    /// Checks the cancel flag and the time limit.
+    #[cold]
+    fn check_interrupt(&self) -> Result<(), InstructionResult> {
+        if self.budget.is_cancelled() {
+            return Err(InstructionResult::Cancelled);
+        }
+        if let Some(limit) = &self.budget.time_limit {
+            if (limit.clock)().saturating_sub(self.started) > limit.max_duration {
+                return Err(InstructionResult::TimeLimitReached);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn instruction_limit() {
+        let mut tracker = BudgetTracker::new(ExecutionBudget::default().with_max_instructions(3));
+        for _ in 0..3 {
+            assert_eq!(tracker.step(), Ok(()));
+        }
+        assert_eq!(
+            tracker.step(),
+            Err(InstructionResult::InstructionLimitReached)
+        );
+        assert_eq!(tracker.instructions(), 3);
+    }
+
+    #[test]
+    fn cancel_flag() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut tracker =
+            BudgetTracker::new(ExecutionBudget::default().with_cancel_flag(cancel.clone()));
+        assert_eq!(tracker.step(), Ok(()));
+        cancel.store(true, Ordering::Relaxed);
+        // Flag is checked on the interval.
+        for _ in 1..ExecutionBudget::CHECK_INTERVAL {
+            assert_eq!(tracker.step(), Ok(()));
+        }
+        assert_eq!(tracker.step(), Err(InstructionResult::Cancelled));
+    }
+
+    #[test]
+    fn time_limit() {
+        let now = Arc::new(AtomicU64::new(5));
+        let clock = now.clone();
+        let mut tracker = BudgetTracker::new(ExecutionBudget::default().with_time_limit(
+            Arc::new(move || Duration::from_secs(clock.load(Ordering::Relaxed))),
+            Duration::from_secs(10),
+        ));
+        now.store(15, Ordering::Relaxed);
+        assert_eq!(tracker.step(), Ok(()));
+        now.store(16, Ordering::Relaxed);
+        for _ in 1..ExecutionBudget::CHECK_INTERVAL {
+            assert_eq!(tracker.step(), Ok(()));
+        }
+        assert_eq!(tracker.step(), Err(InstructionResult::TimeLimitReached));
+    }
+}
@@ -0,0 +1,504 @@
+use super::{Interpreter, STACK_LIMIT};
+use crate::{
+    gas, opcode,
+    primitives::{LegacyAnalyzedBytecode, U256},
+    Host, InstructionResult, InterpreterAction,
+};
+use std::{vec, vec::Vec};
+
+/// Marks the program counters that are not the start of an instruction.
+const NOT_AN_INSTRUCTION: u32 = u32::MAX;
+
+/// Instruction of the [DecodedBytecode].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// Opcode of the instruction.
+    pub opcode: u8,
+    /// Whether the instruction starts a basic block.
+    pub block_start: bool,
+    /// Whether the instruction is executed natively instead of by the instruction table.
+    pub native: bool,
+    /// Program counter of the instruction.
+    pub pc: u32,
+    /// Index of the materialized immediate for PUSH instructions.
+    pub immediate: u32,
+    /// Index of the jump destination for PUSH instructions that are followed by JUMP or JUMPI
+    /// and push a valid jump destination.
+    pub jump: Option<u32>,
+    /// Pre-summed static gas of the run of consecutive natively executed instructions of the
+    /// basic block.
+    ///
+    /// It is only set on the first instruction of the run.
+    pub run_gas: u64,
+}
+
+/// Legacy bytecode pre-decoded into an instruction stream.
+///
+/// PUSH immediates are materialized, static gas of the stack and control flow instructions
+/// (PUSH, DUP, SWAP, POP, JUMP, JUMPI and JUMPDEST) is pre-summed for each run of these
+/// instructions inside of a basic block and jump destinations are resolved to instruction
+/// indices. Those instructions are executed
+/// natively by [Interpreter::run_decoded], other instructions are executed by the instruction
+/// table. Jump destination that is pushed right before JUMP or JUMPI is resolved when the
+/// bytecode is decoded, and the PUSH is executed together with the jump.
+///
+/// Basic blocks start at the jump destinations and end after the instructions that change
+/// the control flow or observe the remaining gas, so these instructions see the same gas as
+/// in the [Interpreter::run] loop.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DecodedBytecode {
+    /// Instruction stream, it ends with the implicit STOP.
+    instructions: Vec<DecodedInstruction>,
+    /// Materialized PUSH immediates.
+    immediates: Vec<U256>,
+    /// Instruction index of each program counter.
+    pc_to_index: Vec<u32>,
+}
+
+impl DecodedBytecode {
+    /// Decodes the analysed legacy bytecode.
+    pub fn new(bytecode: &LegacyAnalyzedBytecode) -> Self {
+        // Bytecode is padded so that immediates of the last PUSH can be read.
+        let code = bytecode.bytecode();
+        let len = bytecode.original_len();
+
+        let mut decoded = Self {
+            instructions: Vec::with_capacity(len + 1),
+            immediates: Vec::new(),
+            pc_to_index: vec![NOT_AN_INSTRUCTION; code.len()],
+        };
+        let mut run = 0;
+        let mut pc = 0;
+        while pc <= len {
+            // Bytecode ends with the implicit STOP.
+            let opcode = if pc < len { code[pc] } else { opcode::STOP };
+            let prev = decoded.instructions.last();
+            let block_start = match prev {
+                Some(prev) => opcode == opcode::JUMPDEST || ends_block(prev.opcode),
+                None => true,
+            };
+            let native = native_gas(opcode);
+            if block_start || prev.is_some_and(|prev| !prev.native) {
+                run = decoded.instructions.len();
+            }
+
+            let mut immediate = 0;
+            let mut next = pc + 1;
+            if let Some(size) = push_size(opcode) {
+                immediate = decoded.immediates.len() as u32;
+                decoded
+                    .immediates
+                    .push(U256::from_be_slice(&code[next..next + size]));
+                next += size;
+            }
+
+            decoded.pc_to_index[pc] = decoded.instructions.len() as u32;
+            decoded.instructions.push(DecodedInstruction {
+                opcode,
+                block_start,
+                native: native.is_some(),
+                pc: pc as u32,
+                immediate,
+                jump: None,
+                run_gas: 0,
+            });
+            decoded.instructions[run].run_gas += native.unwrap_or_default();
+
+            // Implicit STOP is after the truncated PUSH immediate.
+            if pc < len && next > len {
+                pc = next.min(code.len() - 1);
+                decoded.push_stop(pc);
+                break;
+            }
+            pc = next;
+        }
+
+        // Resolve the jump destinations pushed right before the jumps.
+        for index in 1..decoded.instructions.len() {
+            let (push, jump) = (decoded.instructions[index - 1], decoded.instructions[index]);
+            if push_size(push.opcode).is_some()
+                && matches!(jump.opcode, opcode::JUMP | opcode::JUMPI)
+            {
+                let target = decoded.immediates[push.immediate as usize];
+                decoded.instructions[index - 1].jump =
+                    decoded.jump_index(target).map(|target| target as u32);
+            }
+        }
+        decoded
+    }
+
+    /// Appends the implicit STOP after the truncated PUSH immediate.
+    fn push_stop(&mut self, pc: usize) {
+        self.pc_to_index[pc] = self.instructions.len() as u32;
+        self.instructions.push(DecodedInstruction {
+            opcode: opcode::STOP,
+            block_start: true,
+            native: false,
+            pc: pc as u32,
+            immediate: 0,
+            jump: None,
+            run_gas: 0,
+        });
+    }
+
+    /// Returns the instruction stream.
+    pub fn instructions(&self) -> &[DecodedInstruction] {
+        &self.instructions
+    }
+
+    /// Returns the materialized PUSH immediates.
+    pub fn immediates(&self) -> &[U256] {
+        &self.immediates
+    }
+
+    /// Returns the index of the instruction at the program counter.
+    #[inline]
+    pub fn instruction_index(&self, pc: usize) -> Option<usize> {
+        self.pc_to_index
+            .get(pc)
+            .filter(|index| **index != NOT_AN_INSTRUCTION)
+            .map(|index| *index as usize)
+    }
+
+    /// Returns the index of the JUMPDEST instruction at the jump target.
+    #[inline]
+    pub fn jump_index(&self, target: U256) -> Option<usize> {
+        let index = self.instruction_index(usize::try_from(target).ok()?)?;
+        (self.instructions[index].opcode == opcode::JUMPDEST).then_some(index)
+    }
+}
+
+/// Returns the size of the PUSH immediate.
+#[inline]
+const fn push_size(opcode: u8) -> Option<usize> {
+    if opcode >= opcode::PUSH1 && opcode <= opcode::PUSH32 {
+        Some((opcode - opcode::PUSH1) as usize + 1)
+    } else {
+        None
+    }
+}
+
+/// Returns the static gas of the natively executed instruction.
+#[inline]
+const fn native_gas(opcode: u8) -> Option<u64> {
+    match opcode {
+        opcode::PUSH1..=opcode::PUSH32
+        | opcode::DUP1..=opcode::DUP16
+        | opcode::SWAP1..=opcode::SWAP16 => Some(gas::VERYLOW),
+        opcode::POP => Some(gas::BASE),
+        opcode::JUMPDEST => Some(gas::JUMPDEST),
+        opcode::JUMP => Some(gas::MID),
+        opcode::JUMPI => Some(gas::HIGH),
+        _ => None,
+    }
+}
+
+/// Returns true if the instruction ends the basic block.
+///
+/// These instructions change the control flow or observe the remaining gas.
+#[inline]
+const fn ends_block(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        opcode::JUMP
+            | opcode::JUMPI
+            | opcode::STOP
+            | opcode::RETURN
+            | opcode::REVERT
+            | opcode::INVALID
+            | opcode::SELFDESTRUCT
+            | opcode::GAS
+            | opcode::SSTORE
+            | opcode::CALL
+            | opcode::CALLCODE
+            | opcode::DELEGATECALL
+            | opcode::STATICCALL
+            | opcode::CREATE
+            | opcode::CREATE2
+    )
+}
+
+impl Interpreter {
+    /// Executes the pre-decoded legacy bytecode until it returns or stops.
+    ///
+    /// Static gas of the run of natively executed instructions is charged when the run is
+    /// entered if there is enough gas, otherwise the instructions of the run are charged one by
+    /// one. Other instructions are not executed with less gas than in [Interpreter::run], so the
+    /// results are the same.
+    ///
+    /// Natively executed instructions are not taken from the instruction table.
+    pub fn run_decoded<FN, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: crate::SharedMemory,
+        decoded: &DecodedBytecode,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        let Some(mut index) = decoded.instruction_index(self.program_counter()) else {
+            return self.run(shared_memory, instruction_table, host);
+        };
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+
+        // Whether the static gas of the current run was charged.
+        let mut prepaid = false;
+        // Program counter of the last executed instruction.
+        let mut pc = 0;
+
+        /// Charges the static gas of the instruction if its block is not prepaid.
+        macro_rules! charge {
+            ($gas:expr) => {
+                if !prepaid && !self.gas.record_cost($gas) {
+                    self.instruction_result = InstructionResult::OutOfGas;
+                    break;
+                }
+            };
+        }
+
+        while self.instruction_result == InstructionResult::Continue {
+            // SAFETY: Instruction stream ends with STOP and jumps are resolved to instruction
+            // indices, so the index is in bounds.
+            let instruction = unsafe { decoded.instructions.get_unchecked(index) };
+            index += 1;
+            pc = instruction.pc as usize;
+            if instruction.run_gas != 0 {
+                prepaid = self.gas.record_cost(instruction.run_gas);
+            }
+
+            let opcode = instruction.opcode;
+            if !instruction.native {
+                // SAFETY: program counter of the instruction is in bounds of the bytecode.
+                self.instruction_pointer = unsafe { self.bytecode.as_ptr().add(pc + 1) };
+                (instruction_table[opcode as usize])(self, host);
+                continue;
+            }
+
+            let result = match opcode {
+                opcode::PUSH1..=opcode::PUSH32 => {
+                    charge!(gas::VERYLOW);
+                    match instruction.jump {
+                        None => self
+                            .stack
+                            .push(decoded.immediates[instruction.immediate as usize]),
+                        // Pushed jump destination is not put on the stack.
+                        Some(_) if self.stack.len() == STACK_LIMIT => {
+                            Err(InstructionResult::StackOverflow)
+                        }
+                        Some(target) => {
+                            // SAFETY: JUMP or JUMPI follows the PUSH.
+                            let jump = unsafe { decoded.instructions.get_unchecked(index) };
+                            index += 1;
+                            pc = jump.pc as usize;
+                            if jump.opcode == opcode::JUMP {
+                                charge!(gas::MID);
+                                index = target as usize;
+                                Ok(())
+                            } else {
+                                charge!(gas::HIGH);
+                                self.stack.pop().map(|cond| {
+                                    if !cond.is_zero() {
+                                        index = target as usize;
+                                    }
+                                })
+                            }
+                        }
+                    }
+                }
+                opcode::DUP1..=opcode::DUP16 => {
+                    charge!(gas::VERYLOW);
+                    self.stack.dup((opcode - opcode::DUP1) as usize + 1)
+                }
+                opcode::SWAP1..=opcode::SWAP16 => {
+                    charge!(gas::VERYLOW);
+                    self.stack.swap((opcode - opcode::SWAP1) as usize + 1)
+                }
+                opcode::POP => {
+                    charge!(gas::BASE);
+                    self.stack.pop().map(drop)
+                }
+                opcode::JUMPDEST => {
+                    charge!(gas::JUMPDEST);
+                    Ok(())
+                }
+                opcode::JUMP => {
+                    charge!(gas::MID);
+                    self.stack.pop().and_then(|target| {
+                        index = decoded
+                            .jump_index(target)
+                            .ok_or(InstructionResult::InvalidJump)?;
+                        Ok(())
+                    })
+                }
+                opcode::JUMPI => {
+                    charge!(gas::HIGH);
+                    if self.stack.len() < 2 {
+                        Err(InstructionResult::StackUnderflow)
+                    } else {
+                        // SAFETY: stack length is checked above.
+                        let (target, cond) =
+                            unsafe { (self.stack.pop_unsafe(), self.stack.pop_unsafe()) };
+                        if cond.is_zero() {
+                            Ok(())
+                        } else {
+                            decoded
+                                .jump_index(target)
+                                .map(|target| index = target)
+                                .ok_or(InstructionResult::InvalidJump)
+                        }
+                    }
+                }
+                _ => unreachable!("instruction is not executed natively"),
+            };
+            if let Err(result) = result {
+                self.instruction_result = result;
+            }
+        }
+
+        // SAFETY: program counter of the instruction is in bounds of the bytecode.
+        self.instruction_pointer = unsafe { self.bytecode.as_ptr().add(pc + 1) };
+        self.take_next_action()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::to_analysed,
+        opcode::make_instruction_table,
+        primitives::{Bytecode, Bytes, CancunSpec},
+        Contract, DummyHost, SharedMemory,
+    };
+
+    fn decode(code: &[u8]) -> DecodedBytecode {
+        match to_analysed(Bytecode::new_raw(Bytes::copy_from_slice(code))) {
+            Bytecode::LegacyAnalyzed(bytecode) => DecodedBytecode::new(&bytecode),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn decode_blocks() {
+        // PUSH2 0x0102, PUSH1 0x07, JUMP, INVALID, JUMPDEST, GAS, POP
+        let decoded = decode(&[0x61, 0x01, 0x02, 0x60, 0x07, 0x56, 0xfe, 0x5b, 0x5a, 0x50]);
+        let blocks: Vec<_> = decoded
+            .instructions()
+            .iter()
+            .map(|i| (i.opcode, i.pc, i.block_start, i.run_gas, i.jump))
+            .collect();
+        assert_eq!(
+            blocks,
+            [
+                (0x61, 0, true, 14, None),
+                (0x60, 3, false, 0, Some(4)),
+                (0x56, 5, false, 0, None),
+                (0xfe, 6, true, 0, None),
+                (0x5b, 7, true, 1, None),
+                (0x5a, 8, false, 0, None),
+                (0x50, 9, true, 2, None),
+                (opcode::STOP, 10, false, 0, None),
+            ]
+        );
+        assert_eq!(decoded.immediates(), [U256::from(0x0102), U256::from(7)]);
+        assert_eq!(decoded.jump_index(U256::from(7)), Some(4));
+        assert_eq!(decoded.jump_index(U256::from(1)), None);
+        assert_eq!(decoded.jump_index(U256::from(8)), None);
+        assert_eq!(decoded.instruction_index(1), None);
+    }
+
+    #[test]
+    fn decode_runs() {
+        // PUSH1 0x01, PUSH1 0x02, ADD, POP, JUMPDEST
+        let decoded = decode(&[0x60, 0x01, 0x60, 0x02, 0x01, 0x50, 0x5b]);
+        let runs: Vec<_> = decoded
+            .instructions()
+            .iter()
+            .map(|i| (i.opcode, i.block_start, i.run_gas))
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                (0x60, true, 6),
+                (0x60, false, 0),
+                (0x01, false, 0),
+                (0x50, false, 2),
+                (0x5b, true, 1),
+                (opcode::STOP, false, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_push() {
+        // PUSH1 0x01, PUSH3 0x0203
+        let decoded = decode(&[0x60, 0x01, 0x62, 0x02, 0x03]);
+        let last = decoded.instructions().last().unwrap();
+        assert_eq!((last.opcode, last.pc), (opcode::STOP, 6));
+        assert_eq!(decoded.immediates()[1], U256::from(0x020300));
+    }
+
+    #[test]
+    fn same_as_run() {
+        let table = make_instruction_table::<DummyHost, CancunSpec>();
+        // loop that decrements the counter from 5 and stores the remaining gas to memory
+        let codes: [&[u8]; 6] = [
+            &[
+                0x60, 0x05, 0x5b, 0x60, 0x01, 0x90, 0x03, 0x80, 0x5a, 0x81, 0x52, 0x80, 0x60, 0x02,
+                0x57, 0x60, 0x20, 0x60, 0x00, 0xf3,
+            ],
+            // invalid jump
+            &[0x60, 0x03, 0x56, 0x00],
+            // stack underflow
+            &[0x60, 0x01, 0x90],
+            // stack underflow in the resolved jump
+            &[0x60, 0x04, 0x57, 0x00, 0x5b],
+            // out of gas in the block
+            &[0x60, 0x01, 0x60, 0x01, 0x60, 0x01, 0x60, 0x01],
+            // stack underflow with the gas for the following run
+            &[0x60, 0x01, 0x01, 0x60, 0x01, 0x60, 0x01],
+        ];
+        for code in codes {
+            for gas_limit in [5, 9, 15, 1_000_000] {
+                let bytecode = to_analysed(Bytecode::new_raw(Bytes::copy_from_slice(code)));
+                let decoded = match &bytecode {
+                    Bytecode::LegacyAnalyzed(bytecode) => DecodedBytecode::new(bytecode),
+                    _ => unreachable!(),
+                };
+                let contract = Contract::new(
+                    Bytes::new(),
+                    bytecode,
+                    None,
+                    Default::default(),
+                    Default::default(),
+                    U256::ZERO,
+                );
+
+                let mut interp = Interpreter::new(contract.clone(), gas_limit, false);
+                let expected = interp.run(SharedMemory::new(), &table, &mut DummyHost::default());
+                let mut interp = Interpreter::new(contract, gas_limit, false);
+                let action = interp.run_decoded(
+                    SharedMemory::new(),
+                    &decoded,
+                    &table,
+                    &mut DummyHost::default(),
+                );
+                let (
+                    InterpreterAction::Return { result },
+                    InterpreterAction::Return { result: expected },
+                ) = (action, expected)
+                else {
+                    unreachable!()
+                };
+                assert_eq!(result.result, expected.result);
+                assert_eq!(result.output, expected.output);
+                // Remaining gas of the halted interpreter is not used.
+                if !result.is_error() {
+                    assert_eq!(result.gas, expected.gas);
+                }
+            }
+        }
+    }
+}
@@ -42,7 +42,7 @@ impl<'de> Deserialize<'de> for Interpreter {
         struct InterpreterVisitor;
 
         #[derive(serde::Deserialize)]
-        #[serde(field_identifier, rename_all = "lowercase")]
+        #[serde(field_identifier, rename_all = "snake_case")]
         enum InterpreterFields {
             ProgramCounter,
             Gas,
@@ -1,5 +1,9 @@
 //! EVM opcode definitions and utilities.
 
+#[cfg(feature = "parse")]
+pub mod assembly;
+#[cfg(feature = "parse")]
+pub mod eof_assembly;
 pub mod eof_printer;
 
 use crate::{instructions::*, primitives::Spec, Host, Interpreter};
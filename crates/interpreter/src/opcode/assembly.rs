@@ -0,0 +1,326 @@
+//! Textual assembly of legacy bytecode.
+//!
+//! One instruction per line, labels mark the position of the next instruction and can be
+//! pushed to the stack. `PUSH` without size uses the smallest push that fits the value,
+//! labels are pushed with `PUSH2`. Raw bytes are written with `data`:
+//!
+//! ```text
+//!     PUSH 10
+//! loop:
+//!     JUMPDEST
+//!     PUSH1 0x01
+//!     SWAP1
+//!     SUB
+//!     DUP1
+//!     PUSH :loop
+//!     JUMPI
+//!     STOP
+//!     data 0xdeadbeef
+//! ```
+//!
+//! Comments start with `;`.
+
+use super::*;
+use crate::interpreter::analysis::to_analysed;
+use core::fmt::Write;
+use revm_primitives::{hex, Bytecode, U256};
+use std::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Error while assembling the bytecode, with the line where it occurred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssemblyError {
+    /// Line number, starting from one.
+    pub line: usize,
+    /// Kind of the error.
+    pub kind: AssemblyErrorKind,
+}
+
+/// Kind of the [`AssemblyError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssemblyErrorKind {
+    /// Opcode name is not known.
+    UnknownOpcode(String),
+    /// Immediate is missing, malformed or does not fit the instruction.
+    InvalidImmediate(String),
+    /// Push of a label that is not defined.
+    UnknownLabel(String),
+    /// Label is defined twice.
+    DuplicateLabel(String),
+    /// Position of the label does not fit into the push.
+    LabelOutOfRange(String),
+    /// Hex bytes are malformed.
+    InvalidHex(String),
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AssemblyErrorKind::UnknownOpcode(name) => write!(f, "unknown opcode {name}"),
+            AssemblyErrorKind::InvalidImmediate(s) => write!(f, "invalid immediate {s}"),
+            AssemblyErrorKind::UnknownLabel(label) => write!(f, "unknown label {label}"),
+            AssemblyErrorKind::DuplicateLabel(label) => write!(f, "duplicate label {label}"),
+            AssemblyErrorKind::LabelOutOfRange(label) => {
+                write!(f, "label {label} does not fit into push")
+            }
+            AssemblyErrorKind::InvalidHex(s) => write!(f, "invalid hex {s}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssemblyError {}
+
+/// Push of the label position that is resolved at the end of the assembly.
+struct Fixup {
+    line: usize,
+    /// Position of the push immediate in the code.
+    position: usize,
+    size: usize,
+    label: String,
+}
+
+/// Assembles the legacy bytecode from the textual assembly format.
+pub fn assemble(source: &str) -> Result<Bytecode, AssemblyError> {
+    let mut code = Vec::new();
+    let mut labels = BTreeMap::new();
+    let mut fixups = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |kind| AssemblyError {
+            line: line_number,
+            kind,
+        };
+        let text = line.split(';').next().unwrap_or_default().trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Some(label) = text.strip_suffix(':') {
+            if labels.insert(label.to_string(), code.len()).is_some() {
+                return Err(error(AssemblyErrorKind::DuplicateLabel(label.to_string())));
+            }
+            continue;
+        }
+
+        let (name, args) = text
+            .split_once(char::is_whitespace)
+            .map(|(name, args)| (name, args.trim()))
+            .unwrap_or((text, ""));
+        let invalid = || error(AssemblyErrorKind::InvalidImmediate(args.to_string()));
+
+        if name == "data" {
+            let data =
+                hex::decode(args).map_err(|_| error(AssemblyErrorKind::InvalidHex(args.into())))?;
+            code.extend(data);
+            continue;
+        }
+
+        let size = if name == "PUSH" {
+            None
+        } else {
+            let opcode = OpCode::parse(name)
+                .ok_or_else(|| error(AssemblyErrorKind::UnknownOpcode(name.to_string())))?;
+            if !opcode.is_push() {
+                if !args.is_empty() {
+                    return Err(invalid());
+                }
+                code.push(opcode.get());
+                continue;
+            }
+            Some((opcode.get() - PUSH1 + 1) as usize)
+        };
+
+        if let Some(label) = args.strip_prefix(':') {
+            let size = size.unwrap_or(2);
+            code.push(PUSH1 + size as u8 - 1);
+            fixups.push(Fixup {
+                line: line_number,
+                position: code.len(),
+                size,
+                label: label.to_string(),
+            });
+            code.resize(code.len() + size, 0);
+        } else {
+            let value: U256 = args.parse().map_err(|_| invalid())?;
+            let min_size = value.byte_len().max(1);
+            let size = size.unwrap_or(min_size);
+            if min_size > size {
+                return Err(invalid());
+            }
+            code.push(PUSH1 + size as u8 - 1);
+            code.extend_from_slice(&value.to_be_bytes::<32>()[32 - size..]);
+        }
+    }
+
+    for fixup in fixups {
+        let error = |kind| AssemblyError {
+            line: fixup.line,
+            kind,
+        };
+        let position = *labels
+            .get(&fixup.label)
+            .ok_or_else(|| error(AssemblyErrorKind::UnknownLabel(fixup.label.clone())))?;
+        let position = U256::from(position);
+        if position.byte_len() > fixup.size {
+            return Err(error(AssemblyErrorKind::LabelOutOfRange(fixup.label)));
+        }
+        code[fixup.position..fixup.position + fixup.size]
+            .copy_from_slice(&position.to_be_bytes::<32>()[32 - fixup.size..]);
+    }
+
+    Ok(to_analysed(Bytecode::new_raw(code.into())))
+}
+
+/// Disassembles the legacy bytecode into the textual assembly format.
+///
+/// Valid jump destinations of the [`JumpTable`](revm_primitives::JumpTable) are labeled
+/// `L<pc>` and `PUSH2` of their position are written as label pushes. Unknown opcodes and
+/// truncated pushes are written as data.
+///
+/// Assembling the output with [`assemble`] returns the same bytecode.
+pub fn disassemble(bytecode: &Bytecode) -> String {
+    let analysed;
+    let bytecode = if bytecode.legacy_jump_table().is_some() {
+        bytecode
+    } else {
+        analysed = to_analysed(bytecode.clone());
+        &analysed
+    };
+    let code = bytecode.original_byte_slice();
+    let is_label = |pc: usize| {
+        code.get(pc) == Some(&JUMPDEST)
+            && bytecode
+                .legacy_jump_table()
+                .is_some_and(|jump_table| jump_table.is_valid(pc))
+    };
+
+    let mut out = String::new();
+    let mut data = Vec::new();
+    let mut i = 0;
+    while i < code.len() {
+        let opcode = code[i];
+        let push_size = if OpCode::is_push_by_op(opcode) {
+            (opcode - PUSH1 + 1) as usize
+        } else {
+            0
+        };
+        if OpCode::new(opcode).is_none() || i + 1 + push_size > code.len() {
+            let end = if push_size == 0 { i + 1 } else { code.len() };
+            data.extend_from_slice(&code[i..end]);
+            i = end;
+            continue;
+        }
+        if !data.is_empty() {
+            let _ = writeln!(out, "    data 0x{}", hex::encode(&data));
+            data.clear();
+        }
+        if is_label(i) {
+            let _ = writeln!(out, "L{i}:");
+        }
+
+        let name = OpCode::name_by_op(opcode);
+        let immediate = &code[i + 1..i + 1 + push_size];
+        let _ = match push_size {
+            0 => writeln!(out, "    {name}"),
+            2 if is_label(u16::from_be_bytes([immediate[0], immediate[1]]) as usize) => {
+                let target = u16::from_be_bytes([immediate[0], immediate[1]]);
+                writeln!(out, "    {name} :L{target}")
+            }
+            _ => writeln!(out, "    {name} 0x{}", hex::encode(immediate)),
+        };
+        i += 1 + push_size;
+    }
+    if !data.is_empty() {
+        let _ = writeln!(out, "    data 0x{}", hex::encode(&data));
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use revm_primitives::bytes;
+
+    #[test]
+    fn assemble_labels_and_push() {
+        let source = "
+            PUSH 10         ; counter
+        loop:
+            JUMPDEST
+            PUSH1 0x01
+            SWAP1
+            SUB
+            DUP1
+            PUSH :loop
+            JUMPI
+            PUSH 0x1234
+            STOP
+            data 0xdeadbeef
+        ";
+        let bytecode = assemble(source).unwrap();
+        assert_eq!(
+            bytecode.original_bytes(),
+            bytes!("600a5b60019003806100025761123400deadbeef")
+        );
+        assert!(bytecode.legacy_jump_table().unwrap().is_valid(2));
+    }
+
+    #[test]
+    fn disassemble_round_trip() {
+        let bytecode = Bytecode::new_raw(bytes!("600a5b600190038061000257611234000c0d5b61"));
+        let source = disassemble(&bytecode);
+        assert_eq!(
+            source,
+            "    PUSH1 0x0a
+L2:
+    JUMPDEST
+    PUSH1 0x01
+    SWAP1
+    SUB
+    DUP1
+    PUSH2 :L2
+    JUMPI
+    PUSH2 0x1234
+    STOP
+    data 0x0c0d
+L18:
+    JUMPDEST
+    data 0x61
+"
+        );
+        assert_eq!(
+            assemble(&source).unwrap().original_bytes(),
+            bytecode.original_bytes()
+        );
+    }
+
+    #[test]
+    fn assemble_errors() {
+        let error = assemble("PUSH :missing").unwrap_err();
+        assert_eq!(error.line, 1);
+        assert_eq!(
+            error.kind,
+            AssemblyErrorKind::UnknownLabel("missing".into())
+        );
+        assert_eq!(
+            assemble("STOP\nFOO").unwrap_err(),
+            AssemblyError {
+                line: 2,
+                kind: AssemblyErrorKind::UnknownOpcode("FOO".into())
+            }
+        );
+        assert_eq!(
+            assemble("PUSH1 0x100").unwrap_err().kind,
+            AssemblyErrorKind::InvalidImmediate("0x100".into())
+        );
+        assert_eq!(
+            assemble("a:\na:").unwrap_err().kind,
+            AssemblyErrorKind::DuplicateLabel("a".into())
+        );
+    }
+}
@@ -0,0 +1,582 @@
+//! Textual assembly of EOF containers.
+//!
+//! Every code section starts with its types, followed by one instruction per line.
+//! Relative jumps reference labels, immediates of the other instructions are numbers:
+//!
+//! ```text
+//! code inputs=0 outputs=128 max_stack=4
+//!     PUSH0
+//!     RJUMPI :L8
+//!     DATALOADN 0
+//!     POP
+//! L8:
+//!     PUSH0
+//!     PUSH0
+//!     PUSH0
+//!     PUSH0
+//!     EOFCREATE 0
+//!     STOP
+//! container {
+//!     code inputs=0 outputs=128 max_stack=0
+//!         INVALID
+//! }
+//! data 0x0102
+//! ```
+//!
+//! Nested containers are written as blocks when they can be decoded and as hex otherwise.
+//! Bytes that are not part of a well formed instruction are written with `BYTES`, and jump
+//! offsets that do not point to an instruction are written as signed numbers. The data line
+//! takes an optional `size=` if the data section is shorter than the size in the header.
+//! Comments start with `;`.
+
+use super::*;
+use core::fmt::Write;
+use revm_primitives::{
+    eof::{EofBody, TypesSection},
+    hex, Eof, U256,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Prints the EOF container in the textual assembly format.
+///
+/// Assembling the output with [`assemble_eof`] returns the same container.
+pub fn print_eof(eof: &Eof) -> String {
+    let mut out = String::new();
+    write_eof(&mut out, eof, 0);
+    out
+}
+
+fn write_eof(out: &mut String, eof: &Eof, indent: usize) {
+    let pad = " ".repeat(indent);
+    for (types, code) in eof.body.types_section.iter().zip(&eof.body.code_section) {
+        let _ = writeln!(
+            out,
+            "{pad}code inputs={} outputs={} max_stack={}",
+            types.inputs, types.outputs, types.max_stack_size
+        );
+        write_code(out, code, indent);
+    }
+    for container in &eof.body.container_section {
+        if let Ok(eof) = Eof::decode(container.clone()) {
+            let _ = writeln!(out, "{pad}container {{");
+            write_eof(out, &eof, indent + 4);
+            let _ = writeln!(out, "{pad}}}");
+        } else {
+            let _ = writeln!(out, "{pad}container 0x{}", hex::encode(container));
+        }
+    }
+    let data = &eof.body.data_section;
+    if data.len() != eof.header.data_size as usize {
+        let _ = writeln!(
+            out,
+            "{pad}data 0x{} size={}",
+            hex::encode(data),
+            eof.header.data_size
+        );
+    } else if !data.is_empty() {
+        let _ = writeln!(out, "{pad}data 0x{}", hex::encode(data));
+    }
+}
+
+/// Instruction of the code section, or bytes that are not part of a well formed instruction.
+enum Instruction<'a> {
+    Op { opcode: u8, immediate: &'a [u8] },
+    Bytes(&'a [u8]),
+}
+
+/// Splits the code into instructions, returning them with their offsets.
+fn split_code(code: &[u8]) -> Vec<(usize, Instruction<'_>)> {
+    let mut instructions = Vec::new();
+    let mut i = 0;
+    while i < code.len() {
+        let opcode = code[i];
+        let Some(info) = OPCODE_INFO_JUMPTABLE[opcode as usize] else {
+            instructions.push((i, Instruction::Bytes(&code[i..i + 1])));
+            i += 1;
+            continue;
+        };
+        let mut immediate_size = info.immediate_size() as usize;
+        if opcode == RJUMPV {
+            if let Some(max_index) = code.get(i + 1) {
+                immediate_size += (*max_index as usize + 1) * 2;
+            }
+        }
+        if i + 1 + immediate_size > code.len() {
+            instructions.push((i, Instruction::Bytes(&code[i..])));
+            break;
+        }
+        let immediate = &code[i + 1..i + 1 + immediate_size];
+        instructions.push((i, Instruction::Op { opcode, immediate }));
+        i += 1 + immediate_size;
+    }
+    instructions
+}
+
+/// Returns the relative jump offsets of the instruction, with the offset they are
+/// relative to.
+fn relative_jumps(offset: usize, opcode: u8, immediate: &[u8]) -> Vec<(isize, usize)> {
+    let base = offset + 1 + immediate.len();
+    let offsets = match opcode {
+        RJUMP | RJUMPI => immediate,
+        RJUMPV => &immediate[1..],
+        _ => return Vec::new(),
+    };
+    offsets
+        .chunks(2)
+        .map(|offset| (i16::from_be_bytes([offset[0], offset[1]]) as isize, base))
+        .collect()
+}
+
+fn write_code(out: &mut String, code: &[u8], indent: usize) {
+    let pad = " ".repeat(indent);
+    let instructions = split_code(code);
+
+    // labels are only used for jumps into the start of an instruction.
+    let starts: BTreeSet<usize> = instructions
+        .iter()
+        .filter(|(_, instruction)| matches!(instruction, Instruction::Op { .. }))
+        .map(|(offset, _)| *offset)
+        .collect();
+    let target = |relative: isize, base: usize| {
+        base.checked_add_signed(relative)
+            .filter(|target| starts.contains(target))
+    };
+    let mut labels = BTreeSet::new();
+    for (offset, instruction) in &instructions {
+        if let Instruction::Op { opcode, immediate } = instruction {
+            for (relative, base) in relative_jumps(*offset, *opcode, immediate) {
+                labels.extend(target(relative, base));
+            }
+        }
+    }
+
+    for (offset, instruction) in &instructions {
+        if labels.contains(offset) {
+            let _ = writeln!(out, "{pad}L{offset}:");
+        }
+        let (opcode, immediate) = match instruction {
+            Instruction::Op { opcode, immediate } => (*opcode, *immediate),
+            Instruction::Bytes(bytes) => {
+                let _ = writeln!(out, "{pad}    BYTES 0x{}", hex::encode(bytes));
+                continue;
+            }
+        };
+        let name = OpCode::name_by_op(opcode);
+        let _ = match opcode {
+            RJUMP | RJUMPI | RJUMPV => {
+                let targets: Vec<String> = relative_jumps(*offset, opcode, immediate)
+                    .into_iter()
+                    .map(|(relative, base)| match target(relative, base) {
+                        Some(target) => std::format!(":L{target}"),
+                        None => relative.to_string(),
+                    })
+                    .collect();
+                writeln!(out, "{pad}    {name} {}", targets.join(","))
+            }
+            DATALOADN | CALLF | JUMPF => writeln!(
+                out,
+                "{pad}    {name} {}",
+                u16::from_be_bytes([immediate[0], immediate[1]])
+            ),
+            _ if immediate.len() == 1 => writeln!(out, "{pad}    {name} {}", immediate[0]),
+            _ if !immediate.is_empty() => {
+                writeln!(out, "{pad}    {name} 0x{}", hex::encode(immediate))
+            }
+            _ => writeln!(out, "{pad}    {name}"),
+        };
+    }
+}
+
+/// Error while assembling the EOF container, with the line where it occurred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EofAssemblyError {
+    /// Line number, starting from one.
+    pub line: usize,
+    /// Kind of the error.
+    pub kind: EofAssemblyErrorKind,
+}
+
+/// Kind of the [`EofAssemblyError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EofAssemblyErrorKind {
+    /// Opcode name is not known.
+    UnknownOpcode(String),
+    /// Immediate is missing, malformed or does not fit the instruction.
+    InvalidImmediate(String),
+    /// Jump to a label that is not defined in the code section.
+    UnknownLabel(String),
+    /// Label is defined twice in the code section.
+    DuplicateLabel(String),
+    /// Relative jump to the label does not fit into two bytes.
+    JumpOutOfRange(String),
+    /// Instruction or label appears before the first code section.
+    InstructionOutsideCode,
+    /// Code section types are malformed.
+    InvalidTypes(String),
+    /// Hex bytes are malformed.
+    InvalidHex(String),
+    /// Line can't be parsed.
+    UnexpectedLine(String),
+    /// Nested container is not closed.
+    UnclosedContainer,
+}
+
+impl fmt::Display for EofAssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            EofAssemblyErrorKind::UnknownOpcode(name) => write!(f, "unknown opcode {name}"),
+            EofAssemblyErrorKind::InvalidImmediate(s) => write!(f, "invalid immediate {s}"),
+            EofAssemblyErrorKind::UnknownLabel(label) => write!(f, "unknown label {label}"),
+            EofAssemblyErrorKind::DuplicateLabel(label) => write!(f, "duplicate label {label}"),
+            EofAssemblyErrorKind::JumpOutOfRange(label) => {
+                write!(f, "jump to {label} out of range")
+            }
+            EofAssemblyErrorKind::InstructionOutsideCode => {
+                f.write_str("instruction outside of code section")
+            }
+            EofAssemblyErrorKind::InvalidTypes(s) => write!(f, "invalid code section types {s}"),
+            EofAssemblyErrorKind::InvalidHex(s) => write!(f, "invalid hex {s}"),
+            EofAssemblyErrorKind::UnexpectedLine(s) => write!(f, "unexpected line {s}"),
+            EofAssemblyErrorKind::UnclosedContainer => f.write_str("unclosed container"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EofAssemblyError {}
+
+/// Assembles the EOF container from the textual assembly format.
+///
+/// Containers are not validated, so invalid containers can be written for tests.
+pub fn assemble_eof(source: &str) -> Result<Eof, EofAssemblyError> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.split(';').next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty());
+    let (eof, closed) = assemble_container(&mut lines)?;
+    if let Some(line) = closed {
+        return Err(EofAssemblyError {
+            line,
+            kind: EofAssemblyErrorKind::UnexpectedLine("}".into()),
+        });
+    }
+    Ok(eof)
+}
+
+/// Assembles the container until the end of input or closing brace, returning the line
+/// of the closing brace.
+fn assemble_container<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+) -> Result<(Eof, Option<usize>), EofAssemblyError> {
+    let mut body = EofBody {
+        is_data_filled: true,
+        ..Default::default()
+    };
+    let mut data_size = None;
+    let mut code: Option<CodeAssembler> = None;
+    let mut closed = None;
+
+    while let Some((line, text)) = lines.next() {
+        let error = |kind| EofAssemblyError { line, kind };
+        let (keyword, args) = text
+            .split_once(char::is_whitespace)
+            .map(|(keyword, args)| (keyword, args.trim()))
+            .unwrap_or((text, ""));
+        match keyword {
+            "}" => {
+                closed = Some(line);
+                break;
+            }
+            "code" => {
+                if let Some(code) = code.take() {
+                    body.code_section.push(code.finish()?.into());
+                }
+                body.types_section.push(parse_types(args).map_err(error)?);
+                code = Some(CodeAssembler::default());
+            }
+            "container" if args == "{" => {
+                let (container, closed) = assemble_container(lines)?;
+                if closed.is_none() {
+                    return Err(error(EofAssemblyErrorKind::UnclosedContainer));
+                }
+                body.container_section.push(container.raw);
+            }
+            "container" => body
+                .container_section
+                .push(parse_hex(args).map_err(error)?.into()),
+            "data" => {
+                let (data, size) = match args.split_once(char::is_whitespace) {
+                    Some((data, size)) => (data, Some(size.trim())),
+                    None => (args, None),
+                };
+                body.data_section = parse_hex(data).map_err(error)?.into();
+                if let Some(size) = size {
+                    let size = size
+                        .strip_prefix("size=")
+                        .and_then(|size| parse_number(size).ok())
+                        .and_then(|size| u16::try_from(size).ok())
+                        .ok_or_else(|| {
+                            error(EofAssemblyErrorKind::UnexpectedLine(text.to_string()))
+                        })?;
+                    data_size = Some(size);
+                }
+            }
+            _ => {
+                let Some(code) = code.as_mut() else {
+                    return Err(error(EofAssemblyErrorKind::InstructionOutsideCode));
+                };
+                if let Some(label) = text.strip_suffix(':') {
+                    code.label(line, label)?;
+                } else {
+                    code.instruction(line, keyword, args)?;
+                }
+            }
+        }
+    }
+    if let Some(code) = code {
+        body.code_section.push(code.finish()?.into());
+    }
+
+    let mut eof = body.into_eof();
+    if let Some(data_size) = data_size {
+        eof.header.data_size = data_size;
+        eof.body.is_data_filled = eof.body.data_section.len() == data_size as usize;
+        eof.raw = eof.encode_slow();
+    }
+    Ok((eof, closed))
+}
+
+fn parse_types(args: &str) -> Result<TypesSection, EofAssemblyErrorKind> {
+    let mut types = TypesSection::default();
+    for arg in args.split_whitespace() {
+        let invalid = || EofAssemblyErrorKind::InvalidTypes(arg.to_string());
+        let (key, value) = arg.split_once('=').ok_or_else(invalid)?;
+        let value = parse_number(value).map_err(|_| invalid())?;
+        match key {
+            "inputs" => types.inputs = value.try_into().map_err(|_| invalid())?,
+            "outputs" => types.outputs = value.try_into().map_err(|_| invalid())?,
+            "max_stack" => types.max_stack_size = value.try_into().map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(types)
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>, EofAssemblyErrorKind> {
+    hex::decode(s).map_err(|_| EofAssemblyErrorKind::InvalidHex(s.to_string()))
+}
+
+/// Parses decimal or `0x` prefixed hex number.
+fn parse_number(s: &str) -> Result<u64, EofAssemblyErrorKind> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| EofAssemblyErrorKind::InvalidImmediate(s.to_string()))
+}
+
+/// Relative jump to the label that is resolved at the end of the code section.
+struct Fixup {
+    line: usize,
+    /// Position of the jump offset in the code.
+    position: usize,
+    /// Position the jump is relative to.
+    base: usize,
+    label: String,
+}
+
+#[derive(Default)]
+struct CodeAssembler {
+    code: Vec<u8>,
+    labels: BTreeMap<String, usize>,
+    fixups: Vec<Fixup>,
+}
+
+impl CodeAssembler {
+    fn label(&mut self, line: usize, label: &str) -> Result<(), EofAssemblyError> {
+        if self
+            .labels
+            .insert(label.to_string(), self.code.len())
+            .is_some()
+        {
+            return Err(EofAssemblyError {
+                line,
+                kind: EofAssemblyErrorKind::DuplicateLabel(label.to_string()),
+            });
+        }
+        Ok(())
+    }
+
+    fn instruction(&mut self, line: usize, name: &str, args: &str) -> Result<(), EofAssemblyError> {
+        let error = |kind| EofAssemblyError { line, kind };
+        let invalid = || error(EofAssemblyErrorKind::InvalidImmediate(args.to_string()));
+
+        if name == "BYTES" {
+            self.code
+                .extend(parse_hex(args.trim_start_matches("0x")).map_err(error)?);
+            return Ok(());
+        }
+        let opcode = OpCode::parse(name)
+            .ok_or_else(|| error(EofAssemblyErrorKind::UnknownOpcode(name.to_string())))?;
+        self.code.push(opcode.get());
+
+        match opcode.get() {
+            op @ PUSH1..=PUSH32 => {
+                let size = (op - PUSH1 + 1) as usize;
+                let value: U256 = args.parse().map_err(|_| invalid())?;
+                if value.bit_len() > size * 8 {
+                    return Err(invalid());
+                }
+                self.code
+                    .extend_from_slice(&value.to_be_bytes::<32>()[32 - size..]);
+            }
+            RJUMP | RJUMPI | RJUMPV => {
+                let targets: Vec<&str> = args.split(',').map(str::trim).collect();
+                if opcode.get() == RJUMPV {
+                    let max_index = u8::try_from(targets.len() - 1).map_err(|_| invalid())?;
+                    self.code.push(max_index);
+                } else if targets.len() != 1 {
+                    return Err(invalid());
+                }
+                let base = self.code.len() + targets.len() * 2;
+                for target in targets {
+                    if let Some(label) = target.strip_prefix(':') {
+                        self.fixups.push(Fixup {
+                            line,
+                            position: self.code.len(),
+                            base,
+                            label: label.to_string(),
+                        });
+                        self.code.extend_from_slice(&[0, 0]);
+                    } else {
+                        let offset: i16 = target.parse().map_err(|_| invalid())?;
+                        self.code.extend_from_slice(&offset.to_be_bytes());
+                    }
+                }
+            }
+            DATALOADN | CALLF | JUMPF => {
+                let value =
+                    u16::try_from(parse_number(args).map_err(error)?).map_err(|_| invalid())?;
+                self.code.extend_from_slice(&value.to_be_bytes());
+            }
+            _ if opcode.info().immediate_size() == 1 => {
+                let value =
+                    u8::try_from(parse_number(args).map_err(error)?).map_err(|_| invalid())?;
+                self.code.push(value);
+            }
+            _ if !args.is_empty() => return Err(invalid()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>, EofAssemblyError> {
+        for fixup in &self.fixups {
+            let error = |kind| EofAssemblyError {
+                line: fixup.line,
+                kind,
+            };
+            let target = *self
+                .labels
+                .get(&fixup.label)
+                .ok_or_else(|| error(EofAssemblyErrorKind::UnknownLabel(fixup.label.clone())))?;
+            let offset = i16::try_from(target as isize - fixup.base as isize)
+                .map_err(|_| error(EofAssemblyErrorKind::JumpOutOfRange(fixup.label.clone())))?;
+            self.code[fixup.position..fixup.position + 2].copy_from_slice(&offset.to_be_bytes());
+        }
+        Ok(self.code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use revm_primitives::bytes;
+
+    #[test]
+    fn assemble_print_round_trip() {
+        let source = "\
+code inputs=0 outputs=128 max_stack=4
+    PUSH0
+    RJUMPI :L8
+    DATALOADN 0
+    POP
+L8:
+    PUSH0
+    PUSH0
+    PUSH0
+    PUSH0
+    EOFCREATE 0
+    RJUMPV :L8,-1
+    CALLF 1
+    STOP
+code inputs=1 outputs=1 max_stack=1
+    RETF
+container {
+    code inputs=0 outputs=128 max_stack=0
+        INVALID
+}
+container 0x00
+data 0x0102 size=4
+";
+        let eof = assemble_eof(source).unwrap();
+        assert_eq!(eof.body.code_section.len(), 2);
+        let container = EofBody {
+            types_section: vec![TypesSection::new(0, 0x80, 0)],
+            code_section: vec![bytes!("fe")],
+            is_data_filled: true,
+            ..Default::default()
+        }
+        .into_eof();
+        assert_eq!(eof.body.container_section[0], container.raw);
+        assert_eq!(eof.header.data_size, 4);
+        assert_eq!(print_eof(&eof), source);
+        assert_eq!(Eof::decode(eof.raw.clone()).unwrap(), eof);
+    }
+
+    #[test]
+    fn print_malformed_code() {
+        // RJUMP into immediate bytes, unknown opcode and truncated PUSH2.
+        let eof = EofBody {
+            types_section: vec![TypesSection::default()],
+            code_section: vec![bytes!("e0fffe0c6101")],
+            is_data_filled: true,
+            ..Default::default()
+        }
+        .into_eof();
+        let printed = print_eof(&eof);
+        assert_eq!(
+            printed,
+            "code inputs=0 outputs=0 max_stack=0\n    RJUMP -2\n    BYTES 0x0c\n    BYTES 0x6101\n"
+        );
+        assert_eq!(assemble_eof(&printed).unwrap(), eof);
+    }
+
+    #[test]
+    fn assemble_errors() {
+        let error = assemble_eof("code\n    RJUMP :missing").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            error.kind,
+            EofAssemblyErrorKind::UnknownLabel("missing".into())
+        );
+        assert_eq!(
+            assemble_eof("PUSH1 1").unwrap_err().kind,
+            EofAssemblyErrorKind::InstructionOutsideCode
+        );
+        assert_eq!(
+            assemble_eof("code\n    PUSH1 0x100").unwrap_err().kind,
+            EofAssemblyErrorKind::InvalidImmediate("0x100".into())
+        );
+        assert_eq!(
+            assemble_eof("code\ncontainer {\ncode").unwrap_err().kind,
+            EofAssemblyErrorKind::UnclosedContainer
+        );
+    }
+}
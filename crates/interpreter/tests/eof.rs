@@ -1,4 +1,7 @@
-use revm_interpreter::analysis::{validate_raw_eof, EofError};
+use revm_interpreter::{
+    analysis::{validate_raw_eof, EofError},
+    opcode::eof_assembly::{assemble_eof, print_eof},
+};
 use revm_primitives::{Bytes, Eof};
 use serde::Deserialize;
 use std::{
@@ -95,6 +98,30 @@ fn eof_validation_eip4200() {
     run_test(&eof_tests);
 }
 
+/// Printing and assembling back every decodable container returns the same bytes.
+#[test]
+fn eof_assembly_round_trip() {
+    let eof_tests = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/EOFTests");
+    for test_file in find_all_json_tests(&eof_tests) {
+        let s = std::fs::read_to_string(test_file).unwrap();
+        let suite: TestSuite = serde_json::from_str(&s).unwrap();
+        for (name, test_unit) in suite.0 {
+            for (vector_name, test_vector) in test_unit.vectors {
+                let Ok(eof) = Eof::decode(test_vector.code.clone()) else {
+                    continue;
+                };
+                let printed = print_eof(&eof);
+                let assembled = assemble_eof(&printed).unwrap();
+                assert_eq!(
+                    assembled.raw, test_vector.code,
+                    "{name} - {vector_name}\n{printed}"
+                );
+                assert_eq!(print_eof(&assembled), printed);
+            }
+        }
+    }
+}
+
 pub fn run_test(path: &Path) {
     let test_files = find_all_json_tests(path);
     let mut test_sum = 0;
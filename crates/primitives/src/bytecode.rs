@@ -4,7 +4,10 @@ pub mod legacy;
 pub use eof::Eof;
 pub use legacy::{JumpTable, LegacyAnalyzedBytecode};
 
-use crate::{keccak256, Bytes, B256, KECCAK_EMPTY};
+use crate::{
+    eip7702::{Eip7702Bytecode, EIP7702_MAGIC_BYTES},
+    keccak256, Address, Bytes, B256, KECCAK_EMPTY,
+};
 
 /// State of the [`Bytecode`] analysis.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -16,6 +19,8 @@ pub enum Bytecode {
     LegacyAnalyzed(LegacyAnalyzedBytecode),
     /// Ethereum Object Format
     Eof(Eof),
+    /// EIP-7702 delegation designator.
+    Eip7702(Eip7702Bytecode),
 }
 
 impl Default for Bytecode {
@@ -66,12 +71,40 @@ impl Bytecode {
         matches!(self, Self::Eof(_))
     }
 
+    /// Return reference to the EIP-7702 delegation if bytecode is a delegation designator.
+    #[inline]
+    pub const fn eip7702(&self) -> Option<&Eip7702Bytecode> {
+        match self {
+            Self::Eip7702(eip7702) => Some(eip7702),
+            _ => None,
+        }
+    }
+
+    /// Return true if bytecode is EIP-7702 delegation designator.
+    #[inline]
+    pub const fn is_eip7702(&self) -> bool {
+        matches!(self, Self::Eip7702(_))
+    }
+
     /// Creates a new raw [`Bytecode`].
+    ///
+    /// If the bytes are a valid EIP-7702 delegation designator, [`Bytecode::Eip7702`] is returned.
     #[inline]
     pub fn new_raw(bytecode: Bytes) -> Self {
+        if bytecode.starts_with(&EIP7702_MAGIC_BYTES) {
+            if let Ok(eip7702) = Eip7702Bytecode::new_raw(bytecode.clone()) {
+                return Self::Eip7702(eip7702);
+            }
+        }
         Self::LegacyRaw(bytecode)
     }
 
+    /// Creates a new EIP-7702 [`Bytecode`] that delegates to the given address.
+    #[inline]
+    pub fn new_eip7702(address: Address) -> Self {
+        Self::Eip7702(Eip7702Bytecode::new(address))
+    }
+
     /// Create new checked bytecode.
     ///
     /// # Safety
@@ -102,12 +135,15 @@ impl Bytecode {
                 .body
                 .code(0)
                 .expect("Valid EOF has at least one code section"),
+            Self::Eip7702(eip7702) => eip7702.raw(),
         }
     }
 
     /// Returns false if bytecode can't be executed in Interpreter.
+    ///
+    /// EIP-7702 bytecode is never executed, the code of the delegated account is used instead.
     pub fn is_execution_ready(&self) -> bool {
-        !matches!(self, Self::LegacyRaw(_))
+        !matches!(self, Self::LegacyRaw(_) | Self::Eip7702(_))
     }
 
     /// Returns bytes
@@ -117,6 +153,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes.clone(),
             Self::LegacyAnalyzed(analyzed) => analyzed.bytecode().clone(),
             Self::Eof(eof) => eof.raw().clone(),
+            Self::Eip7702(eip7702) => eip7702.raw().clone(),
         }
     }
 
@@ -127,6 +164,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes,
             Self::LegacyAnalyzed(analyzed) => analyzed.bytecode(),
             Self::Eof(eof) => eof.raw(),
+            Self::Eip7702(eip7702) => eip7702.raw(),
         }
     }
 
@@ -137,6 +175,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes.clone(),
             Self::LegacyAnalyzed(analyzed) => analyzed.original_bytes(),
             Self::Eof(eof) => eof.raw().clone(),
+            Self::Eip7702(eip7702) => eip7702.raw().clone(),
         }
     }
 
@@ -147,6 +186,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes,
             Self::LegacyAnalyzed(analyzed) => analyzed.original_byte_slice(),
             Self::Eof(eof) => eof.raw(),
+            Self::Eip7702(eip7702) => eip7702.raw(),
         }
     }
 
@@ -157,6 +197,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes.len(),
             Self::LegacyAnalyzed(analyzed) => analyzed.original_len(),
             Self::Eof(eof) => eof.size(),
+            Self::Eip7702(eip7702) => eip7702.raw().len(),
         }
     }
 
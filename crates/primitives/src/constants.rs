@@ -26,6 +26,19 @@ pub const BLOCKHASH_SERVE_WINDOW: usize = 8192;
 /// This is named `HISTORY_STORAGE_ADDRESS` in the EIP.
 pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("25a219378dad9b3503c8268c9ca836a52427a4fb");
 
+/// EIP-4788: Beacon block root in the EVM
+///
+/// The address of the beacon roots contract.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// The caller address of system calls, used as `tx.origin` and `msg.sender`.
+///
+/// System calls are executed at block level and are not accounted as transactions.
+pub const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+
+/// Gas limit of the system call.
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
 /// EIP-3860: Limit and meter initcode
 ///
 /// Limit of maximum initcode size is `2 * MAX_CODE_SIZE`.
@@ -0,0 +1,30 @@
+//! [EIP-7702]: Set EOA account code.
+//!
+//! [EIP-7702]: https://eips.ethereum.org/EIPS/eip-7702
+
+mod authorization_list;
+mod bytecode;
+
+pub use authorization_list::{
+    Authorization, AuthorizationList, RecoveredAuthorization, SignedAuthorization,
+};
+pub use bytecode::{
+    Eip7702Bytecode, Eip7702DecodeError, EIP7702_BYTECODE_LEN, EIP7702_MAGIC, EIP7702_MAGIC_BYTES,
+    EIP7702_VERSION,
+};
+
+use crate::{uint, U256};
+
+/// Base cost of updating authorized account.
+pub const PER_AUTH_BASE_COST: u64 = 12500;
+
+/// Cost of creating authorized account that was previously empty.
+pub const PER_EMPTY_ACCOUNT_COST: u64 = 25000;
+
+/// Magic byte that is prepended to the RLP encoded authorization tuple before hashing.
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// Half of the secp256k1 curve order, signatures with higher `s` value are malleable
+/// and are rejected as specified in EIP-2.
+pub const SECP256K1N_HALF: U256 =
+    uint!(0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF5D576E7357A4501DDFE92F46681B20A0_U256);
@@ -0,0 +1,227 @@
+use super::{AUTHORIZATION_MAGIC, SECP256K1N_HALF};
+use crate::{keccak256, Address, B256, U256};
+use std::vec::Vec;
+
+/// Unsigned authorization tuple `[chain_id, address, nonce]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Authorization {
+    /// Chain ID the authorization is valid on, zero means it is valid on all chains.
+    pub chain_id: U256,
+    /// Address of the contract whose code the authority delegates to.
+    pub address: Address,
+    /// Expected nonce of the authority account.
+    pub nonce: u64,
+}
+
+impl Authorization {
+    /// Returns the hash that is signed by the authority:
+    /// `keccak256(AUTHORIZATION_MAGIC || rlp([chain_id, address, nonce]))`.
+    pub fn signature_hash(&self) -> B256 {
+        let mut payload = Vec::with_capacity(64);
+        rlp_encode_uint(&mut payload, &self.chain_id.to_be_bytes::<32>());
+        rlp_encode_bytes(&mut payload, self.address.as_slice());
+        rlp_encode_uint(&mut payload, &self.nonce.to_be_bytes());
+
+        let mut buffer = Vec::with_capacity(payload.len() + 3);
+        buffer.push(AUTHORIZATION_MAGIC);
+        rlp_encode_header(&mut buffer, 0xc0, payload.len());
+        buffer.extend_from_slice(&payload);
+        keccak256(buffer)
+    }
+
+    /// Attaches a signature to the authorization.
+    pub fn into_signed(self, y_parity: u8, r: U256, s: U256) -> SignedAuthorization {
+        SignedAuthorization {
+            inner: self,
+            y_parity,
+            r,
+            s,
+        }
+    }
+}
+
+/// Authorization tuple signed by the authority, as found in the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedAuthorization {
+    /// Signed authorization tuple.
+    pub inner: Authorization,
+    /// Signature parity, used as the recovery id.
+    pub y_parity: u8,
+    /// Signature `r` value.
+    pub r: U256,
+    /// Signature `s` value.
+    pub s: U256,
+}
+
+impl SignedAuthorization {
+    /// Returns the hash that was signed. See [`Authorization::signature_hash`].
+    #[inline]
+    pub fn signature_hash(&self) -> B256 {
+        self.inner.signature_hash()
+    }
+
+    /// Returns the compact 64 byte signature `r || s` and the recovery id.
+    ///
+    /// Returns `None` if the recovery id is not `0` or `1`, or if `s` is in the upper half of
+    /// the curve order, in which case the authorization has to be skipped.
+    pub fn compact_signature(&self) -> Option<([u8; 64], u8)> {
+        if self.y_parity > 1 || self.s > SECP256K1N_HALF {
+            return None;
+        }
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(&self.r.to_be_bytes::<32>());
+        sig[32..].copy_from_slice(&self.s.to_be_bytes::<32>());
+        Some((sig, self.y_parity))
+    }
+}
+
+/// Authorization with the recovered authority.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecoveredAuthorization {
+    /// Authorization tuple.
+    pub inner: Authorization,
+    /// Recovered signer of the authorization, `None` if the signature is invalid.
+    pub authority: Option<Address>,
+}
+
+impl RecoveredAuthorization {
+    /// Creates a new recovered authorization without checking the signature.
+    pub fn new_unchecked(inner: Authorization, authority: Option<Address>) -> Self {
+        Self { inner, authority }
+    }
+}
+
+/// Authorization list of the EIP-7702 transaction.
+///
+/// Authorities are either recovered by the caller or recovered by the EVM before the list is
+/// applied.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AuthorizationList {
+    /// Signed authorizations, authorities are recovered when the list is applied.
+    Signed(Vec<SignedAuthorization>),
+    /// Authorizations with already recovered authorities.
+    Recovered(Vec<RecoveredAuthorization>),
+}
+
+impl AuthorizationList {
+    /// Returns number of authorizations in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Signed(list) => list.len(),
+            Self::Recovered(list) => list.len(),
+        }
+    }
+
+    /// Returns true if the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns recovered authorizations, signed ones are recovered with the given function.
+    pub fn recovered_with<F>(&self, recover: F) -> Vec<RecoveredAuthorization>
+    where
+        F: Fn(&SignedAuthorization) -> Option<Address>,
+    {
+        match self {
+            Self::Signed(list) => list
+                .iter()
+                .map(|signed| RecoveredAuthorization {
+                    inner: signed.inner.clone(),
+                    authority: recover(signed),
+                })
+                .collect(),
+            Self::Recovered(list) => list.clone(),
+        }
+    }
+}
+
+impl From<Vec<SignedAuthorization>> for AuthorizationList {
+    fn from(list: Vec<SignedAuthorization>) -> Self {
+        Self::Signed(list)
+    }
+}
+
+impl From<Vec<RecoveredAuthorization>> for AuthorizationList {
+    fn from(list: Vec<RecoveredAuthorization>) -> Self {
+        Self::Recovered(list)
+    }
+}
+
+/// Encodes big endian integer bytes as a RLP string without leading zeros.
+fn rlp_encode_uint(out: &mut Vec<u8>, be_bytes: &[u8]) {
+    let start = be_bytes
+        .iter()
+        .position(|b| *b != 0)
+        .unwrap_or(be_bytes.len());
+    rlp_encode_bytes(out, &be_bytes[start..]);
+}
+
+/// Encodes bytes as a RLP string.
+fn rlp_encode_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        out.push(bytes[0]);
+    } else {
+        rlp_encode_header(out, 0x80, bytes.len());
+        out.extend_from_slice(bytes);
+    }
+}
+
+/// Encodes RLP header, `offset` is `0x80` for strings and `0xc0` for lists.
+fn rlp_encode_header(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let start = len_bytes.iter().position(|b| *b != 0).unwrap();
+        out.push(offset + 55 + (len_bytes.len() - start) as u8);
+        out.extend_from_slice(&len_bytes[start..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address;
+
+    #[test]
+    fn authorization_signature_hash() {
+        let auth = Authorization {
+            chain_id: U256::from(1),
+            address: address!("0000000000000000000000000000000000001000"),
+            nonce: 0,
+        };
+        // keccak256(0x05 || 0xd7 01 94 <address> 80)
+        let mut buffer = vec![AUTHORIZATION_MAGIC, 0xd7, 0x01, 0x94];
+        buffer.extend_from_slice(auth.address.as_slice());
+        buffer.push(0x80);
+        assert_eq!(auth.signature_hash(), keccak256(&buffer));
+    }
+
+    #[test]
+    fn long_list_header() {
+        // max chain id (33 bytes), address (21 bytes) and max nonce (9 bytes).
+        let mut out = Vec::new();
+        rlp_encode_header(&mut out, 0xc0, 63);
+        assert_eq!(out, vec![0xf8, 63]);
+
+        let mut out = Vec::new();
+        rlp_encode_header(&mut out, 0xc0, 55);
+        assert_eq!(out, vec![0xf7]);
+    }
+
+    #[test]
+    fn reject_high_s() {
+        let signed = Authorization::default().into_signed(0, U256::from(1), U256::MAX);
+        assert_eq!(signed.compact_signature(), None);
+        let signed = Authorization::default().into_signed(2, U256::from(1), U256::from(1));
+        assert_eq!(signed.compact_signature(), None);
+        let signed = Authorization::default().into_signed(1, U256::from(1), SECP256K1N_HALF);
+        assert!(signed.compact_signature().is_some());
+    }
+}
@@ -1,11 +1,13 @@
+mod budget;
 pub mod handler_cfg;
 
+pub use budget::{BudgetClock, ExecutionBudget, TimeLimit};
 pub use handler_cfg::{CfgEnvWithHandlerCfg, EnvWithHandlerCfg, HandlerCfg};
 
 use crate::{
-    calc_blob_gasprice, Account, Address, Bytes, InvalidHeader, InvalidTransaction, Spec, SpecId,
-    B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK, MAX_INITCODE_SIZE, U256,
-    VERSIONED_HASH_VERSION_KZG,
+    calc_blob_gasprice, Account, Address, AuthorizationList, Bytes, InvalidHeader,
+    InvalidTransaction, Spec, SpecId, B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK,
+    MAX_INITCODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
 };
 use core::cmp::{min, Ordering};
 use core::hash::Hash;
@@ -189,6 +191,23 @@ impl Env {
             }
         }
 
+        // EIP-7702: Set EOA account code
+        if let Some(authorization_list) = &self.tx.authorization_list {
+            if !SPEC::enabled(SpecId::PRAGUE) {
+                return Err(InvalidTransaction::AuthorizationListNotSupported);
+            }
+
+            // Set code transaction can't be a create transaction or carry blobs.
+            if self.tx.transact_to.is_create() || self.tx.max_fee_per_blob_gas.is_some() {
+                return Err(InvalidTransaction::AuthorizationListInvalidFields);
+            }
+
+            // there must be at least one authorization
+            if authorization_list.is_empty() {
+                return Err(InvalidTransaction::EmptyAuthorizationList);
+            }
+        }
+
         Ok(())
     }
 
@@ -201,7 +220,16 @@ impl Env {
         // EIP-3607: Reject transactions from senders with deployed code
         // This EIP is introduced after london but there was no collision in past
         // so we can leave it enabled always
-        if !self.cfg.is_eip3607_disabled() && account.info.code_hash != KECCAK_EMPTY {
+        //
+        // EIP-7702: Senders whose code is a delegation designator are still allowed.
+        if !self.cfg.is_eip3607_disabled()
+            && account.info.code_hash != KECCAK_EMPTY
+            && !account
+                .info
+                .code
+                .as_ref()
+                .is_some_and(|code| code.is_eip7702())
+        {
             return Err(InvalidTransaction::RejectCallerWithCode);
         }
 
@@ -265,11 +293,18 @@ pub struct CfgEnv {
     /// Bytecode that is created with CREATE/CREATE2 is by default analysed and jumptable is created.
     /// This is very beneficial for testing and speeds up execution of that bytecode if called multiple times.
     ///
+    /// With [AnalysisKind::Decode] legacy bytecode is executed from the pre-decoded instruction stream.
+    ///
     /// Default: Analyse
     pub perf_analyse_created_bytecodes: AnalysisKind,
     /// If some it will effects EIP-170: Contract code size limit. Useful to increase this because of tests.
     /// By default it is 0x6000 (~25kb).
     pub limit_contract_code_size: Option<usize>,
+    /// Budget of the transaction execution, checked in the interpreter loop.
+    ///
+    /// By default it is unlimited.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub execution_budget: ExecutionBudget,
     /// A hard memory limit in bytes beyond which [crate::result::OutOfGasError::Memory] cannot be resized.
     ///
     /// In cases where the gas limit may be extraordinarily high, it is recommended to set this to
@@ -379,6 +414,7 @@ impl Default for CfgEnv {
             chain_id: 1,
             perf_analyse_created_bytecodes: AnalysisKind::default(),
             limit_contract_code_size: None,
+            execution_budget: ExecutionBudget::default(),
             #[cfg(feature = "c-kzg")]
             kzg_settings: crate::kzg::EnvKzgSettings::Default,
             #[cfg(feature = "memory_limit")]
@@ -438,6 +474,14 @@ pub struct BlockEnv {
     ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
+    /// The root of the parent beacon block.
+    ///
+    /// Used by the beacon roots system call, see [`crate::BEACON_ROOTS_ADDRESS`].
+    ///
+    /// Incorporated as part of the Cancun upgrade via [EIP-4788].
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    pub parent_beacon_block_root: Option<B256>,
 }
 
 impl BlockEnv {
@@ -488,6 +532,7 @@ impl Default for BlockEnv {
             difficulty: U256::ZERO,
             prevrandao: Some(B256::ZERO),
             blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(0)),
+            parent_beacon_block_root: None,
         }
     }
 }
@@ -549,6 +594,16 @@ pub struct TxEnv {
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub max_fee_per_blob_gas: Option<U256>,
 
+    /// List of authorizations, each signed by an authority that delegates the code of its
+    /// account to the authorized address.
+    ///
+    /// Presence of the list means that this is a set code transaction.
+    ///
+    /// Incorporated as part of the Prague upgrade via [EIP-7702].
+    ///
+    /// [EIP-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    pub authorization_list: Option<AuthorizationList>,
+
     #[cfg_attr(feature = "serde", serde(flatten))]
     #[cfg(feature = "optimism")]
     /// Optimism fields.
@@ -593,6 +648,7 @@ impl Default for TxEnv {
             access_list: Vec::new(),
             blob_hashes: Vec::new(),
             max_fee_per_blob_gas: None,
+            authorization_list: None,
             #[cfg(feature = "optimism")]
             optimism: OptimismFields::default(),
         }
@@ -715,6 +771,16 @@ pub enum AnalysisKind {
     /// Perform bytecode analysis.
     #[default]
     Analyse,
+    /// Perform bytecode analysis and execute legacy bytecode from the pre-decoded instruction
+    /// stream that is cached per code hash.
+    ///
+    /// Setting it in [CfgEnv::perf_analyse_created_bytecodes] enables it for all executed
+    /// bytecode, the pre-decoded stream is not used with an inspector or with a limited
+    /// [CfgEnv::execution_budget].
+    ///
+    /// Stack and control flow instructions (PUSH, DUP, SWAP, POP, JUMP, JUMPI and JUMPDEST)
+    /// are executed natively, so their overrides in a custom instruction table are ignored.
+    Decode,
 }
 
 #[cfg(test)]
@@ -732,6 +798,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_tx_authorization_list() {
+        let mut env = Env::default();
+        env.tx.authorization_list = Some(AuthorizationList::Signed(vec![]));
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::AuthorizationListNotSupported)
+        );
+        assert_eq!(
+            env.validate_tx::<crate::PragueSpec>(),
+            Err(InvalidTransaction::EmptyAuthorizationList)
+        );
+
+        env.tx.authorization_list = Some(AuthorizationList::Signed(vec![Default::default()]));
+        assert_eq!(env.validate_tx::<crate::PragueSpec>(), Ok(()));
+
+        env.tx.transact_to = TransactTo::Create;
+        assert_eq!(
+            env.validate_tx::<crate::PragueSpec>(),
+            Err(InvalidTransaction::AuthorizationListInvalidFields)
+        );
+    }
+
     #[test]
     fn test_validate_tx_access_list() {
         let mut env = Env::default();
@@ -0,0 +1,106 @@
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::sync::Arc;
+
+/// Clock that returns the time elapsed since an arbitrary fixed point in time.
+///
+/// With `std` it can be created from [std::time::Instant]:
+/// `Arc::new(move || start.elapsed())`.
+pub type BudgetClock = Arc<dyn Fn() -> Duration + Send + Sync>;
+
+/// Wall-clock time limit of the execution.
+#[derive(Clone)]
+pub struct TimeLimit {
+    /// Clock used to measure the execution time.
+    pub clock: BudgetClock,
+    /// Maximum execution time of the transaction.
+    pub max_duration: Duration,
+}
+
+impl fmt::Debug for TimeLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeLimit")
+            .field("max_duration", &self.max_duration)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Execution budget of a transaction.
+///
+/// Limits are checked in the interpreter loop, a transaction that exceeds them halts with
+/// [HaltReason::ExecutionInterrupted](crate::HaltReason::ExecutionInterrupted). Instructions
+/// are counted over all call frames of the transaction, while the time limit and the cancel flag
+/// are checked every [ExecutionBudget::CHECK_INTERVAL] instructions.
+///
+/// Default budget is unlimited.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionBudget {
+    /// Maximum number of instructions executed by the transaction.
+    pub max_instructions: Option<u64>,
+    /// Wall-clock time limit of the transaction.
+    pub time_limit: Option<TimeLimit>,
+    /// Flag that cancels the execution when it is set.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+// Implement PartialEq manually because the clock and the cancel flag do not implement it.
+impl PartialEq for ExecutionBudget {
+    fn eq(&self, other: &Self) -> bool {
+        let time_limit_eq = match (&self.time_limit, &other.time_limit) {
+            (Some(a), Some(b)) => {
+                Arc::ptr_eq(&a.clock, &b.clock) && a.max_duration == b.max_duration
+            }
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        let cancel_eq = match (&self.cancel, &other.cancel) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        self.max_instructions == other.max_instructions && time_limit_eq && cancel_eq
+    }
+}
+
+impl Eq for ExecutionBudget {}
+
+impl ExecutionBudget {
+    /// Number of instructions after which the time limit and the cancel flag are checked.
+    pub const CHECK_INTERVAL: u64 = 1024;
+
+    /// Sets the maximum number of executed instructions.
+    pub fn with_max_instructions(mut self, max_instructions: u64) -> Self {
+        self.max_instructions = Some(max_instructions);
+        self
+    }
+
+    /// Sets the wall-clock time limit measured by the clock.
+    pub fn with_time_limit(mut self, clock: BudgetClock, max_duration: Duration) -> Self {
+        self.time_limit = Some(TimeLimit {
+            clock,
+            max_duration,
+        });
+        self
+    }
+
+    /// Sets the flag that cancels the execution.
+    pub fn with_cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Returns true if no limit is set.
+    #[inline]
+    pub fn is_unlimited(&self) -> bool {
+        self.max_instructions.is_none() && self.time_limit.is_none() && self.cancel.is_none()
+    }
+
+    /// Returns true if the cancel flag is set.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
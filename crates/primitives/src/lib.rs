@@ -10,12 +10,14 @@ extern crate alloc as std;
 mod bytecode;
 mod constants;
 pub mod db;
+pub mod eip7702;
 pub mod env;
 
 #[cfg(feature = "c-kzg")]
 pub mod kzg;
 pub mod precompile;
 pub mod result;
+mod revert;
 pub mod specification;
 pub mod state;
 pub mod utilities;
@@ -26,6 +28,9 @@ pub use alloy_primitives::{
 pub use bitvec;
 pub use bytecode::*;
 pub use constants::*;
+pub use eip7702::{
+    Authorization, AuthorizationList, Eip7702Bytecode, RecoveredAuthorization, SignedAuthorization,
+};
 pub use env::*;
 
 cfg_if::cfg_if! {
@@ -41,6 +46,7 @@ cfg_if::cfg_if! {
 pub use kzg::{EnvKzgSettings, KzgSettings};
 pub use precompile::*;
 pub use result::*;
+pub use revert::*;
 pub use specification::*;
 pub use state::*;
 pub use utilities::*;
@@ -1,4 +1,4 @@
-use crate::{Address, Bytes, EvmState, Log, U256};
+use crate::{Address, Bytes, EvmState, Log, RevertDecoder, RevertReason, U256};
 use core::fmt;
 use std::{boxed::Box, string::String, vec::Vec};
 
@@ -90,6 +90,21 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns the decoded revert reason if the execution reverted.
+    ///
+    /// See [`RevertDecoder`] for decoding custom errors.
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        self.revert_reason_with(&RevertDecoder::default())
+    }
+
+    /// Returns the revert reason decoded with the given decoder if the execution reverted.
+    pub fn revert_reason_with(&self, decoder: &RevertDecoder) -> Option<RevertReason> {
+        match self {
+            Self::Revert { output, .. } => decoder.decode(output),
+            _ => None,
+        }
+    }
+
     /// Returns the gas used.
     pub fn gas_used(&self) -> u64 {
         match *self {
@@ -144,6 +159,8 @@ pub enum EVMError<DBError> {
     Header(InvalidHeader),
     /// Database error.
     Database(DBError),
+    /// Execution was suspended by an instruction outside of the resumable execution.
+    Suspended,
     /// Custom error.
     ///
     /// Useful for handler registers where custom logic would want to return their own custom error.
@@ -157,7 +174,7 @@ impl<DBError: std::error::Error + 'static> std::error::Error for EVMError<DBErro
             Self::Transaction(e) => Some(e),
             Self::Header(e) => Some(e),
             Self::Database(e) => Some(e),
-            Self::Custom(_) => None,
+            Self::Suspended | Self::Custom(_) => None,
         }
     }
 }
@@ -168,6 +185,7 @@ impl<DBError: fmt::Display> fmt::Display for EVMError<DBError> {
             Self::Transaction(e) => write!(f, "transaction validation error: {e}"),
             Self::Header(e) => write!(f, "header validation error: {e}"),
             Self::Database(e) => write!(f, "database error: {e}"),
+            Self::Suspended => f.write_str("execution was suspended"),
             Self::Custom(e) => f.write_str(e),
         }
     }
@@ -250,6 +268,12 @@ pub enum InvalidTransaction {
     BlobVersionNotSupported,
     /// EOF crate should have `to` address
     EofCrateShouldHaveToAddress,
+    /// EIP-7702 is not enabled.
+    AuthorizationListNotSupported,
+    /// EIP-7702 transaction has invalid fields set.
+    AuthorizationListInvalidFields,
+    /// Empty Authorization List is not allowed.
+    EmptyAuthorizationList,
     /// System transactions are not supported post-regolith hardfork.
     ///
     /// Before the Regolith hardfork, there was a special field in the `Deposit` transaction
@@ -341,6 +365,11 @@ impl fmt::Display for InvalidTransaction {
             }
             Self::BlobVersionNotSupported => write!(f, "blob version not supported"),
             Self::EofCrateShouldHaveToAddress => write!(f, "EOF crate should have `to` address"),
+            Self::AuthorizationListNotSupported => write!(f, "authorization list not supported"),
+            Self::AuthorizationListInvalidFields => {
+                write!(f, "authorization list tx has invalid fields")
+            }
+            Self::EmptyAuthorizationList => write!(f, "empty authorization list"),
             #[cfg(feature = "optimism")]
             Self::DepositSystemTxPostRegolith => {
                 write!(
@@ -367,6 +396,8 @@ pub enum InvalidHeader {
     PrevrandaoNotSet,
     /// `excess_blob_gas` is not set for Cancun and above.
     ExcessBlobGasNotSet,
+    /// `parent_beacon_block_root` is not set for Cancun and above.
+    ParentBeaconBlockRootNotSet,
 }
 
 #[cfg(feature = "std")]
@@ -377,6 +408,7 @@ impl fmt::Display for InvalidHeader {
         match self {
             Self::PrevrandaoNotSet => write!(f, "`prevrandao` not set"),
             Self::ExcessBlobGasNotSet => write!(f, "`excess_blob_gas` not set"),
+            Self::ParentBeaconBlockRootNotSet => write!(f, "`parent_beacon_block_root` not set"),
         }
     }
 }
@@ -420,11 +452,26 @@ pub enum HaltReason {
     OutOfFunds,
     CallTooDeep,
 
+    /// Execution was interrupted because its budget was exceeded or it was cancelled.
+    ExecutionInterrupted(InterruptReason),
+
     /* Optimism errors */
     #[cfg(feature = "optimism")]
     FailedDeposit,
 }
 
+/// Reason of the interrupted execution, see [ExecutionBudget](crate::ExecutionBudget).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterruptReason {
+    /// Maximum number of instructions was executed.
+    InstructionLimit,
+    /// Wall-clock time limit was exceeded.
+    TimeLimit,
+    /// Execution was cancelled.
+    Cancelled,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutOfGasError {
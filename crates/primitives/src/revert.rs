@@ -0,0 +1,264 @@
+use crate::{keccak256, Bytes, FixedBytes, HashMap, U256};
+use core::fmt;
+use std::string::String;
+
+/// Selector of the Solidity `Error(string)` error.
+pub const ERROR_SELECTOR: FixedBytes<4> = FixedBytes([0x08, 0xc3, 0x79, 0xa0]);
+
+/// Selector of the Solidity `Panic(uint256)` error.
+pub const PANIC_SELECTOR: FixedBytes<4> = FixedBytes([0x4e, 0x48, 0x7b, 0x71]);
+
+/// Decoded revert data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RevertReason {
+    /// Revert with the `Error(string)` error, e.g. `require(false, "reason")`.
+    Error(String),
+    /// Revert with the `Panic(uint256)` error, e.g. failed `assert`.
+    Panic(PanicCode),
+    /// Revert with a custom error.
+    CustomError {
+        /// Selector of the error.
+        selector: FixedBytes<4>,
+        /// Signature of the error, if it was registered in the [`RevertDecoder`].
+        signature: Option<String>,
+        /// ABI encoded arguments of the error.
+        data: Bytes,
+    },
+}
+
+impl RevertReason {
+    /// Decodes the revert data without any registered custom error signatures.
+    pub fn decode(output: &[u8]) -> Option<Self> {
+        RevertDecoder::default().decode(output)
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(reason) => f.write_str(reason),
+            Self::Panic(code) => code.fmt(f),
+            Self::CustomError {
+                signature: Some(signature),
+                ..
+            } => f.write_str(signature),
+            Self::CustomError { selector, .. } => write!(f, "custom error {selector}"),
+        }
+    }
+}
+
+/// Solidity panic code, the argument of the `Panic(uint256)` error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PanicCode {
+    /// Generic compiler inserted panic.
+    Generic,
+    /// Failed `assert`.
+    Assert,
+    /// Arithmetic underflow or overflow outside of an `unchecked` block.
+    ArithmeticOverflow,
+    /// Division or modulo by zero.
+    DivisionByZero,
+    /// Conversion of a too big or negative value into an enum.
+    InvalidEnumValue,
+    /// Access to an incorrectly encoded storage byte array.
+    InvalidStorageByteArray,
+    /// `.pop()` on an empty array.
+    EmptyArrayPop,
+    /// Out-of-bounds access of an array or a slice.
+    ArrayOutOfBounds,
+    /// Too much memory allocated or too large array created.
+    OutOfMemory,
+    /// Call of a zero-initialized internal function.
+    UninitializedFunction,
+    /// Panic code not known to the compiler.
+    Unknown(U256),
+}
+
+impl PanicCode {
+    /// Returns the numeric value of the panic code.
+    pub fn code(&self) -> U256 {
+        U256::from(match self {
+            Self::Generic => 0x00,
+            Self::Assert => 0x01,
+            Self::ArithmeticOverflow => 0x11,
+            Self::DivisionByZero => 0x12,
+            Self::InvalidEnumValue => 0x21,
+            Self::InvalidStorageByteArray => 0x22,
+            Self::EmptyArrayPop => 0x31,
+            Self::ArrayOutOfBounds => 0x32,
+            Self::OutOfMemory => 0x41,
+            Self::UninitializedFunction => 0x51,
+            Self::Unknown(code) => return *code,
+        })
+    }
+}
+
+impl From<U256> for PanicCode {
+    fn from(code: U256) -> Self {
+        match code.try_into() {
+            Ok(0x00u64) => Self::Generic,
+            Ok(0x01) => Self::Assert,
+            Ok(0x11) => Self::ArithmeticOverflow,
+            Ok(0x12) => Self::DivisionByZero,
+            Ok(0x21) => Self::InvalidEnumValue,
+            Ok(0x22) => Self::InvalidStorageByteArray,
+            Ok(0x31) => Self::EmptyArrayPop,
+            Ok(0x32) => Self::ArrayOutOfBounds,
+            Ok(0x41) => Self::OutOfMemory,
+            Ok(0x51) => Self::UninitializedFunction,
+            _ => Self::Unknown(code),
+        }
+    }
+}
+
+impl fmt::Display for PanicCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Generic => write!(f, "generic panic"),
+            Self::Assert => write!(f, "assert(false)"),
+            Self::ArithmeticOverflow => write!(f, "arithmetic underflow or overflow"),
+            Self::DivisionByZero => write!(f, "division or modulo by zero"),
+            Self::InvalidEnumValue => write!(f, "enum overflow"),
+            Self::InvalidStorageByteArray => {
+                write!(f, "invalid encoded storage byte array accessed")
+            }
+            Self::EmptyArrayPop => {
+                write!(f, "out-of-bounds array access; popping on an empty array")
+            }
+            Self::ArrayOutOfBounds => write!(f, "out-of-bounds access of an array or bytesslice"),
+            Self::OutOfMemory => write!(f, "out of memory"),
+            Self::UninitializedFunction => write!(f, "uninitialized function"),
+            Self::Unknown(code) => write!(f, "unknown panic code: {code:#x}"),
+        }
+    }
+}
+
+/// Decoder of the revert data.
+///
+/// Decodes `Error(string)` and `Panic(uint256)` errors, and resolves the signatures of
+/// custom errors that were registered with [`RevertDecoder::register`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RevertDecoder {
+    signatures: HashMap<FixedBytes<4>, String>,
+}
+
+impl RevertDecoder {
+    /// Creates new decoder without registered custom errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the signature of a custom error, e.g. `InsufficientBalance(uint256,uint256)`.
+    ///
+    /// Returns the selector of the error.
+    pub fn register(&mut self, signature: impl Into<String>) -> FixedBytes<4> {
+        let signature = signature.into();
+        let selector = FixedBytes::from_slice(&keccak256(signature.as_bytes())[..4]);
+        self.signatures.insert(selector, signature);
+        selector
+    }
+
+    /// Returns the registered signature of the custom error.
+    pub fn signature(&self, selector: &FixedBytes<4>) -> Option<&str> {
+        self.signatures.get(selector).map(String::as_str)
+    }
+
+    /// Decodes the revert data.
+    ///
+    /// Returns `None` if the data is too short to contain a selector or if the
+    /// `Error(string)` or `Panic(uint256)` arguments are malformed.
+    pub fn decode(&self, output: &[u8]) -> Option<RevertReason> {
+        let selector = FixedBytes::<4>::from_slice(output.get(..4)?);
+        let data = &output[4..];
+        match selector {
+            ERROR_SELECTOR => decode_error_string(data).map(RevertReason::Error),
+            PANIC_SELECTOR => {
+                let code = U256::try_from_be_slice(data.get(..32)?)?;
+                Some(RevertReason::Panic(code.into()))
+            }
+            _ => Some(RevertReason::CustomError {
+                selector,
+                signature: self.signature(&selector).map(String::from),
+                data: Bytes::copy_from_slice(data),
+            }),
+        }
+    }
+}
+
+/// Decodes the ABI encoded `string` argument of the `Error(string)` error.
+fn decode_error_string(data: &[u8]) -> Option<String> {
+    let offset = usize::try_from(U256::try_from_be_slice(data.get(..32)?)?).ok()?;
+    let len_end = offset.checked_add(32)?;
+    let len = usize::try_from(U256::try_from_be_slice(data.get(offset..len_end)?)?).ok()?;
+    let reason = data.get(len_end..len_end.checked_add(len)?)?;
+    String::from_utf8(reason.to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bytes, hex};
+    use std::string::ToString;
+
+    /// `Error("x")` revert data.
+    const REVERT_DATA: Bytes = bytes!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000017800000000000000000000000000000000000000000000000000000000000000");
+
+    #[test]
+    fn decode_error() {
+        assert_eq!(
+            RevertReason::decode(&REVERT_DATA),
+            Some(RevertReason::Error("x".into()))
+        );
+        assert_eq!(RevertReason::decode(&REVERT_DATA[..40]), None);
+        assert_eq!(RevertReason::decode(&[]), None);
+    }
+
+    #[test]
+    fn decode_panic() {
+        let output =
+            hex!("4e487b710000000000000000000000000000000000000000000000000000000000000011");
+        let reason = RevertReason::decode(&output).unwrap();
+        assert_eq!(reason, RevertReason::Panic(PanicCode::ArithmeticOverflow));
+        assert_eq!(reason.to_string(), "arithmetic underflow or overflow");
+
+        let output =
+            hex!("4e487b710000000000000000000000000000000000000000000000000000000000000099");
+        let reason = RevertReason::decode(&output).unwrap();
+        assert_eq!(
+            reason,
+            RevertReason::Panic(PanicCode::Unknown(U256::from(0x99)))
+        );
+        assert_eq!(reason.to_string(), "unknown panic code: 0x99");
+
+        for code in [
+            0x00, 0x01, 0x11, 0x12, 0x21, 0x22, 0x31, 0x32, 0x41, 0x51, 0x99,
+        ] {
+            assert_eq!(PanicCode::from(U256::from(code)).code(), U256::from(code));
+        }
+    }
+
+    #[test]
+    fn decode_custom_error() {
+        let mut decoder = RevertDecoder::new();
+        let selector = decoder.register("InsufficientBalance(uint256,uint256)");
+        assert_eq!(selector, FixedBytes(hex!("cf479181")));
+
+        let mut output = selector.to_vec();
+        output.extend_from_slice(&[0; 64]);
+        let reason = decoder.decode(&output).unwrap();
+        assert_eq!(reason.to_string(), "InsufficientBalance(uint256,uint256)");
+        assert_eq!(
+            reason,
+            RevertReason::CustomError {
+                selector,
+                signature: Some("InsufficientBalance(uint256,uint256)".into()),
+                data: Bytes::from_static(&[0; 64]),
+            }
+        );
+
+        let reason = RevertReason::decode(&output).unwrap();
+        assert_eq!(reason.to_string(), "custom error 0xcf479181");
+    }
+}
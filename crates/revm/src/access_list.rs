@@ -0,0 +1,160 @@
+//! Access list generation, equivalent of the `eth_createAccessList` RPC method.
+
+use crate::{
+    inspector_handle_register,
+    inspectors::AccessListInspector,
+    primitives::{db::Database, Address, EVMError, EnvWithHandlerCfg, ExecutionResult, U256},
+    Evm,
+};
+use std::{format, vec::Vec};
+
+/// Maximum number of re-executions with the generated access list before
+/// [create_access_list] gives up.
+pub const MAX_ACCESS_LIST_ITERATIONS: usize = 16;
+
+/// Result of the access list generation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListResult {
+    /// Generated access list.
+    pub access_list: Vec<(Address, Vec<U256>)>,
+    /// Gas used by the transaction with the generated access list.
+    pub gas_used: u64,
+    /// Gas used by the transaction without an access list.
+    pub gas_used_without_access_list: u64,
+    /// Result of the transaction executed with the generated access list.
+    pub result: ExecutionResult,
+}
+
+/// Generates the access list of the transaction.
+///
+/// Transaction is executed without an access list first, then re-executed with the
+/// generated access list until the list stops changing. Access list of the transaction
+/// is used as a seed of the generated list.
+///
+/// Returns [EVMError::Custom] if the list does not converge in
+/// [MAX_ACCESS_LIST_ITERATIONS] re-executions.
+///
+/// State changes are not committed to the database.
+pub fn create_access_list<DB: Database>(
+    db: &mut DB,
+    env: EnvWithHandlerCfg,
+) -> Result<AccessListResult, EVMError<DB::Error>> {
+    let seed = env.tx.access_list.clone();
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .with_external_context(AccessListInspector::new(&seed))
+        .with_env_with_handler_cfg(env)
+        .modify_tx_env(|tx| tx.access_list.clear())
+        .append_handler_register(inspector_handle_register)
+        .build();
+
+    let gas_used_without_access_list = evm.transact()?.result.gas_used();
+    let mut access_list = evm.context.external.access_list();
+    for _ in 0..MAX_ACCESS_LIST_ITERATIONS {
+        evm.context.external = AccessListInspector::new(&access_list);
+        evm.context.evm.env.tx.access_list.clone_from(&access_list);
+        let result = evm.transact()?.result;
+
+        let new_access_list = evm.context.external.access_list();
+        if new_access_list == access_list {
+            return Ok(AccessListResult {
+                access_list,
+                gas_used: result.gas_used(),
+                gas_used_without_access_list,
+                result,
+            });
+        }
+        access_list = new_access_list;
+    }
+    Err(EVMError::Custom(format!(
+        "access list did not converge in {MAX_ACCESS_LIST_ITERATIONS} iterations"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{address, AccountInfo, Bytecode, Env, HandlerCfg, SpecId, TransactTo},
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+    const CONTRACT: Address = address!("2000000000000000000000000000000000000000");
+    const OTHER: Address = address!("3000000000000000000000000000000000000000");
+    const COINBASE: Address = address!("4000000000000000000000000000000000000000");
+
+    fn push_address(code: &mut Vec<u8>, address: Address) {
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(address.as_slice());
+    }
+
+    /// Contract reads a slot and balances of other account, caller, coinbase and a precompile.
+    fn setup(spec_id: SpecId) -> (CacheDB<EmptyDB>, EnvWithHandlerCfg) {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(OTHER, AccountInfo::from_balance(U256::from(1)));
+
+        // SLOAD(1), BALANCE(OTHER), BALANCE(CALLER), BALANCE(COINBASE), BALANCE(ECRECOVER)
+        let mut code = vec![opcode::PUSH1, 1, opcode::SLOAD, opcode::POP];
+        for address in [
+            OTHER,
+            CALLER,
+            COINBASE,
+            address!("0000000000000000000000000000000000000001"),
+        ] {
+            push_address(&mut code, address);
+            code.extend_from_slice(&[opcode::BALANCE, opcode::POP]);
+        }
+        let code = Bytecode::new_raw(code.into());
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut env = Env::default();
+        env.block.coinbase = COINBASE;
+        env.tx.caller = CALLER;
+        env.tx.transact_to = TransactTo::Call(CONTRACT);
+        env.tx.gas_limit = 100_000;
+        (
+            db,
+            EnvWithHandlerCfg::new(Box::new(env), HandlerCfg::new(spec_id)),
+        )
+    }
+
+    #[test]
+    fn generate_access_list() {
+        let (mut db, env) = setup(SpecId::CANCUN);
+        let result = create_access_list(&mut db, env).unwrap();
+        assert_eq!(
+            result.access_list,
+            vec![(CONTRACT, vec![U256::from(1)]), (OTHER, vec![])]
+        );
+        assert!(result.result.is_success());
+        // Access list costs 2400 gas per address and 1900 per storage key,
+        // while the cold account access costs 2600 gas and the cold slot 2100.
+        assert_eq!(
+            result.gas_used,
+            result.gas_used_without_access_list + 2 * 2400 + 1900 - (2600 - 100) - (2100 - 100)
+        );
+        // state is not committed.
+        assert!(db.accounts[&CALLER].storage.is_empty());
+        assert_eq!(db.accounts[&CALLER].info.nonce, 0);
+    }
+
+    #[test]
+    fn coinbase_before_shanghai() {
+        let (mut db, env) = setup(SpecId::MERGE);
+        let result = create_access_list(&mut db, env).unwrap();
+        assert_eq!(
+            result.access_list,
+            vec![
+                (CONTRACT, vec![U256::from(1)]),
+                (OTHER, vec![]),
+                (COINBASE, vec![])
+            ]
+        );
+    }
+}
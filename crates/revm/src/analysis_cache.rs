@@ -0,0 +1,374 @@
+//! Analysed bytecode cache that is shared by EVM instances.
+
+use crate::{
+    interpreter::analysis::{to_analysed, validate_raw_eof, EofError},
+    primitives::{eof::TypesSection, Bytecode, Bytes, HashMap, B256},
+};
+use core::{
+    fmt,
+    mem::size_of,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// Thread-safe cache of the analysed bytecodes keyed by code hash.
+///
+/// Legacy bytecode is cached with its jump table and EOF bytecode with the result of its
+/// validation. Cache is bounded by the total size of the cached entries, that is the padded
+/// bytecode with its jump table or the EOF container with its decoded sections, when the bound is
+/// exceeded the entries that were not used since they were last checked are evicted first.
+///
+/// Clones share the entries and the metrics, so one cache can be set with
+/// [EvmBuilder::with_analysis_cache](crate::EvmBuilder::with_analysis_cache) to all
+/// [Evm](crate::Evm) instances of a worker pool.
+#[derive(Clone)]
+pub struct AnalysisCache {
+    inner: Arc<Inner>,
+}
+
+/// Hit and miss metrics of the [AnalysisCache].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AnalysisCacheMetrics {
+    /// Number of lookups that found the bytecode in the cache.
+    pub hits: u64,
+    /// Number of lookups that analysed the bytecode.
+    pub misses: u64,
+    /// Number of evicted entries.
+    pub evictions: u64,
+    /// Number of cached entries.
+    pub entries: usize,
+    /// Total size of the cached entries in bytes.
+    pub size: usize,
+}
+
+struct Inner {
+    max_size: usize,
+    entries: RwLock<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<B256, Entry>,
+    /// Code hashes in the eviction order.
+    queue: VecDeque<B256>,
+    size: usize,
+}
+
+struct Entry {
+    value: Cached,
+    size: usize,
+    /// Whether the entry was used since it was last checked for eviction.
+    referenced: AtomicBool,
+}
+
+#[derive(Clone)]
+enum Cached {
+    Legacy(Bytecode),
+    Eof(Result<Bytecode, EofError>),
+}
+
+impl Cached {
+    /// Returns the number of bytes that the entry holds.
+    fn size(&self) -> usize {
+        match self {
+            Self::Legacy(Bytecode::LegacyAnalyzed(analysed)) => {
+                analysed.bytecode().len() + analysed.jump_table().as_slice().len()
+            }
+            Self::Legacy(bytecode) => bytecode.len(),
+            Self::Eof(Ok(Bytecode::Eof(eof))) => {
+                let sections = eof.body.code_section.len() + eof.body.container_section.len();
+                eof.raw.len()
+                    + eof.header.size()
+                    + eof.body.types_section.len() * size_of::<TypesSection>()
+                    + sections * size_of::<Bytes>()
+            }
+            Self::Eof(_) => size_of::<EofError>(),
+        }
+    }
+}
+
+impl fmt::Debug for AnalysisCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnalysisCache")
+            .field("max_size", &self.inner.max_size)
+            .field("metrics", &self.metrics())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AnalysisCache {
+    /// Creates a cache bounded by the total size of the cached entries in bytes.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                max_size,
+                entries: RwLock::default(),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                evictions: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Returns the analysed legacy bytecode with the code hash.
+    ///
+    /// Raw legacy bytecode is analysed and cached on a miss, other bytecodes are returned
+    /// as they are.
+    pub fn analysed(&self, hash: B256, bytecode: Bytecode) -> Bytecode {
+        if !matches!(bytecode, Bytecode::LegacyRaw(_)) {
+            return bytecode;
+        }
+        if let Some(Cached::Legacy(bytecode)) = self.get(&hash) {
+            return bytecode;
+        }
+        let analysed = to_analysed(bytecode);
+        self.insert(hash, Cached::Legacy(analysed.clone()));
+        analysed
+    }
+
+    /// Returns the result of the validation of the EOF bytecode with the code hash.
+    ///
+    /// Bytecode is validated and the result is cached on a miss. It is used for EOF code that
+    /// the database returns as raw bytes.
+    pub fn validate_eof(&self, hash: B256, bytecode: Bytes) -> Result<Bytecode, EofError> {
+        if let Some(Cached::Eof(result)) = self.get(&hash) {
+            return result;
+        }
+        let result = validate_raw_eof(bytecode).map(Bytecode::Eof);
+        self.insert(hash, Cached::Eof(result.clone()));
+        result
+    }
+
+    /// Returns the metrics of the cache.
+    pub fn metrics(&self) -> AnalysisCacheMetrics {
+        let entries = self.read();
+        AnalysisCacheMetrics {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            evictions: self.inner.evictions.load(Ordering::Relaxed),
+            entries: entries.map.len(),
+            size: entries.size,
+        }
+    }
+
+    /// Removes all entries, the metrics are kept.
+    pub fn clear(&self) {
+        *self
+            .inner
+            .entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Entries::default();
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Entries> {
+        self.inner
+            .entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get(&self, hash: &B256) -> Option<Cached> {
+        let value = self.read().map.get(hash).map(|entry| {
+            entry.referenced.store(true, Ordering::Relaxed);
+            entry.value.clone()
+        });
+        let counter = if value.is_some() {
+            &self.inner.hits
+        } else {
+            &self.inner.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    fn insert(&self, hash: B256, value: Cached) {
+        let size = value.size();
+        if size > self.inner.max_size {
+            return;
+        }
+        let mut guard = self
+            .inner
+            .entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let entries = &mut *guard;
+        // Other thread could have analysed the same bytecode.
+        if entries.map.contains_key(&hash) {
+            return;
+        }
+        entries.size += size;
+        entries.queue.push_back(hash);
+        entries.map.insert(
+            hash,
+            Entry {
+                value,
+                size,
+                referenced: AtomicBool::new(false),
+            },
+        );
+
+        // Entries that were used get a second chance and are moved to the back of the queue.
+        while entries.size > self.inner.max_size {
+            let Some(hash) = entries.queue.pop_front() else {
+                break;
+            };
+            let entry = &entries.map[&hash];
+            if entry.referenced.swap(false, Ordering::Relaxed) {
+                entries.queue.push_back(hash);
+            } else {
+                let size = entry.size;
+                entries.map.remove(&hash);
+                entries.size -= size;
+                self.inner.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        primitives::{address, hex, keccak256, SpecId, TransactTo},
+        Evm,
+    };
+
+    fn legacy(code: &[u8]) -> (B256, Bytecode) {
+        (keccak256(code), Bytecode::new_raw(code.to_vec().into()))
+    }
+
+    #[test]
+    fn hit_and_miss() {
+        let cache = AnalysisCache::new(1024);
+        let (hash, code) = legacy(&[0x60, 0x03, 0x56, 0x5b]);
+
+        let analysed = cache.analysed(hash, code.clone());
+        assert!(analysed.legacy_jump_table().unwrap().is_valid(3));
+        assert_eq!(cache.analysed(hash, code), analysed);
+        // 4 bytes of code padded with 33 zero bytes and 5 bytes of the jump table.
+        assert_eq!(analysed.bytecode().len(), 37);
+        assert_eq!(
+            cache.metrics(),
+            AnalysisCacheMetrics {
+                hits: 1,
+                misses: 1,
+                evictions: 0,
+                entries: 1,
+                size: 42,
+            }
+        );
+
+        // Analysed bytecode is not cached.
+        cache.analysed(B256::ZERO, analysed);
+        assert_eq!(cache.metrics().entries, 1);
+    }
+
+    #[test]
+    fn eviction() {
+        // Each analysed entry takes 42 bytes.
+        let cache = AnalysisCache::new(84);
+        let codes = [[0x01; 4], [0x02; 4], [0x03; 4]].map(|code| legacy(&code));
+        cache.analysed(codes[0].0, codes[0].1.clone());
+        cache.analysed(codes[1].0, codes[1].1.clone());
+        // First entry is used, so the second one is evicted.
+        cache.analysed(codes[0].0, codes[0].1.clone());
+        cache.analysed(codes[2].0, codes[2].1.clone());
+
+        let metrics = cache.metrics();
+        assert_eq!(
+            (metrics.evictions, metrics.entries, metrics.size),
+            (1, 2, 84)
+        );
+        cache.analysed(codes[0].0, codes[0].1.clone());
+        cache.analysed(codes[1].0, codes[1].1.clone());
+        assert_eq!((cache.metrics().hits, cache.metrics().misses), (2, 4));
+
+        // Bytecode that is larger than the cache is not cached.
+        let (hash, code) = legacy(&[0x00; 64]);
+        cache.analysed(hash, code);
+        assert!(!cache.read().map.contains_key(&hash));
+    }
+
+    #[test]
+    fn eof_validation() {
+        let cache = AnalysisCache::new(1024);
+        let code = Bytes::from_static(&[0xEF, 0x00]);
+        let hash = keccak256(&code);
+        let result = cache.validate_eof(hash, code.clone());
+        assert!(result.is_err());
+        assert_eq!(cache.validate_eof(hash, code), result);
+        assert_eq!((cache.metrics().hits, cache.metrics().misses), (1, 1));
+    }
+
+    #[test]
+    fn shared_by_evms() {
+        let cache = AnalysisCache::new(1024);
+        // PUSH1 0x01, PUSH1 0x00, SSTORE
+        let bytecode = Bytecode::new_raw([0x60, 0x01, 0x60, 0x00, 0x55].into());
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let cache = cache.clone();
+                let bytecode = bytecode.clone();
+                scope.spawn(move || {
+                    let mut evm = Evm::builder()
+                        .with_db(BenchmarkDB::new_bytecode(bytecode))
+                        .with_analysis_cache(cache)
+                        .modify_tx_env(|tx| {
+                            tx.caller = address!("1000000000000000000000000000000000000000");
+                            tx.transact_to = TransactTo::Call(address!(
+                                "0000000000000000000000000000000000000000"
+                            ));
+                        })
+                        .build();
+                    for _ in 0..4 {
+                        assert!(evm.transact().unwrap().result.is_success());
+                    }
+                });
+            }
+        });
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.entries, 1);
+        assert_eq!(metrics.hits + metrics.misses, 16);
+        assert!(metrics.misses <= 4);
+    }
+
+    #[test]
+    fn raw_eof_code() {
+        // EOF container with the code PUSH1 0x01, PUSH1 0x00, SSTORE, STOP
+        let bytecode =
+            Bytecode::new_raw(hex!("ef000101000402000100060400000000800002600160005500").into());
+        let transact = |cache: Option<AnalysisCache>| {
+            let mut evm = Evm::builder()
+                .with_db(BenchmarkDB::new_bytecode(bytecode.clone()))
+                .with_spec_id(SpecId::PRAGUE)
+                .modify_tx_env(|tx| {
+                    tx.caller = address!("1000000000000000000000000000000000000000");
+                    tx.transact_to =
+                        TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                })
+                .build();
+            if let Some(cache) = cache {
+                evm = evm.modify().with_analysis_cache(cache).build();
+            }
+            evm.transact().unwrap().result
+        };
+
+        // Raw code is analysed as legacy code with or without the cache.
+        let cache = AnalysisCache::new(1024);
+        let expected = transact(None);
+        assert!(!expected.is_success());
+        for _ in 0..2 {
+            assert_eq!(transact(Some(cache.clone())), expected);
+        }
+        assert_eq!((cache.metrics().hits, cache.metrics().misses), (1, 1));
+    }
+}
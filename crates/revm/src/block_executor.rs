@@ -0,0 +1,439 @@
+//! Block level execution on top of [`State`].
+
+use crate::{
+    db::{states::bundle_state::BundleRetention, BundleState, Database, State},
+    primitives::{
+        alloy_primitives::Bloom, Address, BlockEnv, EVMError, Log, SpecId, TxEnv,
+        MAX_BLOB_GAS_PER_BLOCK,
+    },
+    Evm,
+};
+use core::fmt;
+use std::vec::Vec;
+
+/// Withdrawal of the validator balance from the consensus layer.
+///
+/// Introduced in Shanghai with [EIP-4895].
+///
+/// [EIP-4895]: https://eips.ethereum.org/EIPS/eip-4895
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier issued by consensus layer.
+    pub index: u64,
+    /// Index of validator associated with withdrawal.
+    pub validator_index: u64,
+    /// Target address for withdrawn ether.
+    pub address: Address,
+    /// Value of the withdrawal in gwei.
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    /// Returns the withdrawal amount in wei.
+    #[inline]
+    pub fn amount_wei(&self) -> u128 {
+        self.amount as u128 * 1_000_000_000
+    }
+}
+
+/// Receipt of the executed transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    /// Status of the transaction, `true` if it was successful.
+    pub success: bool,
+    /// Gas used by the transaction.
+    pub gas_used: u64,
+    /// Gas used in the block up to and including this transaction.
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction.
+    pub logs: Vec<Log>,
+    /// Bloom filter of the logs.
+    pub logs_bloom: Bloom,
+}
+
+/// Output of the executed block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockExecutionOutput {
+    /// Receipts of the executed transactions, in order of execution.
+    pub receipts: Vec<Receipt>,
+    /// Gas used by all transactions of the block.
+    pub gas_used: u64,
+    /// Blob gas used by all transactions of the block.
+    pub blob_gas_used: u64,
+    /// Bloom filter of all logs in the block.
+    pub logs_bloom: Bloom,
+    /// State changes and reverts of the block.
+    pub bundle: BundleState,
+}
+
+/// Errors that can happen while executing a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockExecutionError<DBError> {
+    /// Transaction gas limit is higher than the gas left in the block.
+    BlockGasLimitExceeded {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Gas limit of the transaction.
+        transaction_gas_limit: u64,
+        /// Gas left in the block.
+        block_available_gas: u64,
+    },
+    /// Blob gas of the transaction is higher than the blob gas left in the block.
+    BlobGasLimitExceeded {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Blob gas of the transaction.
+        transaction_blob_gas: u64,
+        /// Blob gas left in the block.
+        block_available_blob_gas: u64,
+    },
+    /// Transaction failed validation or execution.
+    Transaction {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// EVM error.
+        error: EVMError<DBError>,
+    },
+    /// Pre or post block system call failed.
+    SystemCall(EVMError<DBError>),
+    /// Database error.
+    Database(DBError),
+}
+
+#[cfg(feature = "std")]
+impl<DBError: std::error::Error + 'static> std::error::Error for BlockExecutionError<DBError> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transaction { error, .. } | Self::SystemCall(error) => Some(error),
+            Self::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl<DBError: fmt::Display> fmt::Display for BlockExecutionError<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BlockGasLimitExceeded {
+                index,
+                transaction_gas_limit,
+                block_available_gas,
+            } => write!(
+                f,
+                "transaction {index} gas limit {transaction_gas_limit} is more than \
+                 block available gas {block_available_gas}"
+            ),
+            Self::BlobGasLimitExceeded {
+                index,
+                transaction_blob_gas,
+                block_available_blob_gas,
+            } => write!(
+                f,
+                "transaction {index} blob gas {transaction_blob_gas} is more than \
+                 block available blob gas {block_available_blob_gas}"
+            ),
+            Self::Transaction { index, error } => write!(f, "transaction {index} error: {error}"),
+            Self::SystemCall(e) => write!(f, "system call error: {e}"),
+            Self::Database(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+/// Executes whole blocks over the [`State`] database.
+///
+/// Block execution applies the pre-block system calls, executes and commits all
+/// transactions, applies withdrawals and merges the transitions into the [`BundleState`].
+/// Block can be executed at once with [`BlockExecutor::execute_block`] or step by step with
+/// [`BlockExecutor::begin_block`], [`BlockExecutor::execute_transaction`] and
+/// [`BlockExecutor::finish_block`].
+///
+/// [`State`] needs to be built with [`crate::StateBuilder::with_bundle_update`] for
+/// the bundle to be created. Block and uncle rewards of pre-merge blocks are not applied.
+pub struct BlockExecutor<'a, EXT, DB: Database> {
+    /// EVM used to execute the block.
+    pub evm: Evm<'a, EXT, State<DB>>,
+    /// Output of the block that is being executed.
+    output: BlockExecutionOutput,
+}
+
+impl<EXT, DB: Database> fmt::Debug for BlockExecutor<'_, EXT, DB>
+where
+    EXT: fmt::Debug,
+    DB: fmt::Debug,
+    DB::Error: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockExecutor")
+            .field("evm", &self.evm)
+            .field("output", &self.output)
+            .finish()
+    }
+}
+
+impl<'a, EXT, DB: Database> BlockExecutor<'a, EXT, DB> {
+    /// Creates new block executor.
+    pub fn new(evm: Evm<'a, EXT, State<DB>>) -> Self {
+        Self {
+            evm,
+            output: BlockExecutionOutput::default(),
+        }
+    }
+
+    /// Returns the inner EVM.
+    pub fn into_evm(self) -> Evm<'a, EXT, State<DB>> {
+        self.evm
+    }
+
+    /// Executes the block and returns the receipts and the bundle state.
+    ///
+    /// On error, the [`State`] may contain changes of already executed
+    /// transactions and should be discarded.
+    pub fn execute_block(
+        &mut self,
+        block: BlockEnv,
+        transactions: impl IntoIterator<Item = TxEnv>,
+        withdrawals: &[Withdrawal],
+    ) -> Result<BlockExecutionOutput, BlockExecutionError<DB::Error>> {
+        self.begin_block(block)?;
+        for tx in transactions {
+            self.execute_transaction(tx)?;
+        }
+        self.finish_block(withdrawals)
+    }
+
+    /// Starts the execution of the block and applies the pre-block system calls.
+    pub fn begin_block(&mut self, block: BlockEnv) -> Result<(), BlockExecutionError<DB::Error>> {
+        self.output = BlockExecutionOutput::default();
+        let spec_id = self.evm.spec_id();
+        *self.evm.block_mut() = block;
+        self.evm
+            .db_mut()
+            .set_state_clear_flag(spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+
+        self.evm
+            .apply_pre_block_calls()
+            .map_err(BlockExecutionError::SystemCall)?;
+        Ok(())
+    }
+
+    /// Executes and commits the transaction of the started block and returns its receipt.
+    ///
+    /// Transaction that exceeds the gas left in the block or that fails validation is not
+    /// executed and the block can be continued with the next transaction.
+    pub fn execute_transaction(
+        &mut self,
+        tx: TxEnv,
+    ) -> Result<&Receipt, BlockExecutionError<DB::Error>> {
+        let index = self.output.receipts.len();
+        let block_gas_limit = self.evm.block().gas_limit.saturating_to::<u64>();
+        let block_available_gas = block_gas_limit - self.output.gas_used;
+        if tx.gas_limit > block_available_gas {
+            return Err(BlockExecutionError::BlockGasLimitExceeded {
+                index,
+                transaction_gas_limit: tx.gas_limit,
+                block_available_gas,
+            });
+        }
+        let transaction_blob_gas = tx.get_total_blob_gas();
+        let block_available_blob_gas = MAX_BLOB_GAS_PER_BLOCK - self.output.blob_gas_used;
+        if transaction_blob_gas > block_available_blob_gas {
+            return Err(BlockExecutionError::BlobGasLimitExceeded {
+                index,
+                transaction_blob_gas,
+                block_available_blob_gas,
+            });
+        }
+
+        *self.evm.tx_mut() = tx;
+        let result = self
+            .evm
+            .transact_commit()
+            .map_err(|error| BlockExecutionError::Transaction { index, error })?;
+
+        let output = &mut self.output;
+        output.gas_used += result.gas_used();
+        output.blob_gas_used += transaction_blob_gas;
+
+        let success = result.is_success();
+        let gas_used = result.gas_used();
+        let logs = result.into_logs();
+        let mut logs_bloom = Bloom::ZERO;
+        for log in &logs {
+            logs_bloom.accrue_log(log);
+        }
+        output.logs_bloom.accrue_bloom(&logs_bloom);
+        output.receipts.push(Receipt {
+            success,
+            gas_used,
+            cumulative_gas_used: output.gas_used,
+            logs,
+            logs_bloom,
+        });
+        Ok(output.receipts.last().expect("receipt is pushed"))
+    }
+
+    /// Applies the withdrawals and returns the output of the started block.
+    pub fn finish_block(
+        &mut self,
+        withdrawals: &[Withdrawal],
+    ) -> Result<BlockExecutionOutput, BlockExecutionError<DB::Error>> {
+        // EIP-4895: Beacon chain push withdrawals as operations
+        if self.evm.spec_id().is_enabled_in(SpecId::SHANGHAI) {
+            self.evm
+                .db_mut()
+                .increment_balances(withdrawals.iter().map(|w| (w.address, w.amount_wei())))
+                .map_err(BlockExecutionError::Database)?;
+        }
+
+        let state = self.evm.db_mut();
+        state.merge_transitions(BundleRetention::Reverts);
+
+        Ok(BlockExecutionOutput {
+            bundle: state.take_bundle(),
+            ..core::mem::take(&mut self.output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode::{LOG0, PUSH1},
+        primitives::{address, AccountInfo, Bytecode, TransactTo, U256},
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+    const LOGGER: Address = address!("2000000000000000000000000000000000000000");
+    const RECIPIENT: Address = address!("3000000000000000000000000000000000000000");
+
+    fn executor() -> BlockExecutor<'static, (), CacheDB<EmptyDB>> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            CALLER,
+            AccountInfo::from_balance(U256::from(1_000_000_000_000_000u64)),
+        );
+        let code = Bytecode::new_raw([PUSH1, 0x00, PUSH1, 0x00, LOG0].into());
+        db.insert_account_info(
+            LOGGER,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        let state = State::builder()
+            .with_database(db)
+            .with_bundle_update()
+            .build();
+        BlockExecutor::new(
+            Evm::builder()
+                .with_db(state)
+                .with_spec_id(SpecId::SHANGHAI)
+                .build(),
+        )
+    }
+
+    fn tx(nonce: u64, to: Address, value: u64) -> TxEnv {
+        TxEnv {
+            caller: CALLER,
+            gas_limit: 100_000,
+            transact_to: TransactTo::Call(to),
+            value: U256::from(value),
+            nonce: Some(nonce),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn execute_block() {
+        let mut executor = executor();
+        let withdrawal = Withdrawal {
+            address: RECIPIENT,
+            amount: 1,
+            ..Default::default()
+        };
+        let output = executor
+            .execute_block(
+                BlockEnv::default(),
+                [tx(0, RECIPIENT, 1), tx(1, LOGGER, 0)],
+                &[withdrawal],
+            )
+            .unwrap();
+
+        assert_eq!(output.receipts.len(), 2);
+        let [transfer, log] = &output.receipts[..] else {
+            unreachable!()
+        };
+        assert!(transfer.success);
+        assert_eq!(transfer.cumulative_gas_used, 21_000);
+        assert!(transfer.logs.is_empty());
+        assert_eq!(transfer.logs_bloom, Bloom::ZERO);
+        assert!(log.success);
+        assert_eq!(log.cumulative_gas_used, 21_000 + log.gas_used);
+        assert_eq!(log.logs.len(), 1);
+        assert_ne!(log.logs_bloom, Bloom::ZERO);
+        assert_eq!(output.logs_bloom, log.logs_bloom);
+        assert_eq!(output.gas_used, log.cumulative_gas_used);
+
+        let recipient = output.bundle.account(&RECIPIENT).unwrap();
+        assert_eq!(
+            recipient.info.as_ref().unwrap().balance,
+            U256::from(1_000_000_001u64)
+        );
+        assert_eq!(
+            output
+                .bundle
+                .account(&CALLER)
+                .unwrap()
+                .info
+                .as_ref()
+                .unwrap()
+                .nonce,
+            2
+        );
+    }
+
+    #[test]
+    fn block_gas_limit_exceeded() {
+        let mut executor = executor();
+        let block = BlockEnv {
+            gas_limit: U256::from(110_000),
+            ..Default::default()
+        };
+        let err = executor
+            .execute_block(block, [tx(0, RECIPIENT, 1), tx(1, RECIPIENT, 1)], &[])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BlockExecutionError::BlockGasLimitExceeded {
+                index: 1,
+                transaction_gas_limit: 100_000,
+                block_available_gas: 89_000,
+            }
+        );
+    }
+
+    #[test]
+    fn execute_transaction_after_rejected() {
+        let mut executor = executor();
+        let block = BlockEnv {
+            gas_limit: U256::from(110_000),
+            ..Default::default()
+        };
+        executor.begin_block(block).unwrap();
+        let too_much_gas = TxEnv {
+            gas_limit: 200_000,
+            ..tx(0, RECIPIENT, 1)
+        };
+        assert!(matches!(
+            executor.execute_transaction(too_much_gas),
+            Err(BlockExecutionError::BlockGasLimitExceeded { index: 0, .. })
+        ));
+        let receipt = executor.execute_transaction(tx(0, RECIPIENT, 1)).unwrap();
+        assert_eq!(receipt.cumulative_gas_used, 21_000);
+
+        let output = executor.finish_block(&[]).unwrap();
+        assert_eq!(output.receipts.len(), 1);
+        assert_eq!(output.gas_used, 21_000);
+    }
+}
@@ -412,6 +412,13 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         self
     }
 
+    /// Sets the analysed bytecode cache, it can be shared with other [`Evm`] instances.
+    #[cfg(feature = "std")]
+    pub fn with_analysis_cache(mut self, cache: crate::AnalysisCache) -> Self {
+        self.context.evm.analysis_cache = Some(cache);
+        self
+    }
+
     /// Clears Environment of EVM.
     pub fn with_clear_env(mut self) -> Self {
         self.context.evm.env.clear();
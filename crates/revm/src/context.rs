@@ -7,7 +7,7 @@ pub use context_precompiles::{
     ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
 };
 pub use evm_context::EvmContext;
-pub use inner_evm_context::InnerEvmContext;
+pub use inner_evm_context::{InnerEvmContext, MAX_DECODED_BYTECODES};
 use revm_interpreter::as_usize_saturated;
 
 use crate::{
@@ -132,7 +132,7 @@ impl<EXT, DB: Database> Host for Context<EXT, DB> {
 
     fn load_account(&mut self, address: Address) -> Option<LoadAccountResult> {
         self.evm
-            .load_account_exist(address)
+            .load_account_delegated(address)
             .map_err(|e| self.evm.error = Err(e))
             .ok()
     }
@@ -144,14 +144,14 @@ impl<EXT, DB: Database> Host for Context<EXT, DB> {
             .ok()
     }
 
-    fn code(&mut self, address: Address) -> Option<(Bytecode, bool)> {
+    fn code(&mut self, address: Address) -> Option<(Bytecode, LoadAccountResult)> {
         self.evm
             .code(address)
             .map_err(|e| self.evm.error = Err(e))
             .ok()
     }
 
-    fn code_hash(&mut self, address: Address) -> Option<(B256, bool)> {
+    fn code_hash(&mut self, address: Address) -> Option<(B256, LoadAccountResult)> {
         self.evm
             .code_hash(address)
             .map_err(|e| self.evm.error = Err(e))
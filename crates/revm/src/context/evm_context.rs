@@ -6,7 +6,7 @@ use crate::{
     interpreter::{
         return_ok, CallInputs, Contract, Gas, InstructionResult, Interpreter, InterpreterResult,
     },
-    primitives::{Address, Bytes, EVMError, Env, HashSet, U256},
+    primitives::{Address, Bytecode, Bytes, EVMError, Env, HashSet, U256},
     ContextPrecompiles, FrameOrResult, CALL_STACK_LIMIT,
 };
 use core::{
@@ -166,8 +166,23 @@ impl<DB: Database> EvmContext<DB> {
             .inner
             .journaled_state
             .load_code(inputs.bytecode_address, &mut self.inner.db)?;
-        let code_hash = account.info.code_hash();
-        let bytecode = account.info.code.clone().unwrap_or_default();
+        let mut code_hash = account.info.code_hash();
+        let mut bytecode = account.info.code.clone().unwrap_or_default();
+
+        // EIP-7702: Execute the code of the delegated account.
+        // Delegated account is already warm as it is loaded when call gas is calculated.
+        if let Bytecode::Eip7702(eip7702_bytecode) = bytecode {
+            let (account, _) = self
+                .inner
+                .journaled_state
+                .load_code(eip7702_bytecode.address(), &mut self.inner.db)?;
+            code_hash = account.info.code_hash();
+            bytecode = account.info.code.clone().unwrap_or_default();
+            // Delegation chains are not followed, designator is executed as legacy code.
+            if let Bytecode::Eip7702(eip7702_bytecode) = bytecode {
+                bytecode = Bytecode::LegacyRaw(eip7702_bytecode.raw);
+            }
+        }
 
         // Create subroutine checkpoint
         let checkpoint = self.journaled_state.checkpoint();
@@ -205,6 +220,10 @@ impl<DB: Database> EvmContext<DB> {
                 inputs.return_memory_offset.clone(),
             ))
         } else if !bytecode.is_empty() {
+            #[cfg(feature = "std")]
+            if let Some(cache) = &self.inner.analysis_cache {
+                bytecode = cache.analysed(code_hash, bytecode);
+            }
             let contract =
                 Contract::new_with_context(inputs.input.clone(), bytecode, Some(code_hash), inputs);
             // Create interpreter and executes call and push new CallStackFrame.
@@ -227,7 +246,7 @@ pub(crate) mod test_utils {
     use crate::{
         db::{CacheDB, EmptyDB},
         journaled_state::JournaledState,
-        primitives::{address, SpecId, B256},
+        primitives::{address, HashMap, SpecId, B256},
     };
 
     /// Mock caller address.
@@ -280,6 +299,11 @@ pub(crate) mod test_utils {
                 journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
                 db,
                 error: Ok(()),
+                eip7702_refund: 0,
+                budget: None,
+                decoded_bytecodes: HashMap::new(),
+                #[cfg(feature = "std")]
+                analysis_cache: None,
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
             },
@@ -295,6 +319,11 @@ pub(crate) mod test_utils {
                 journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
                 db,
                 error: Ok(()),
+                eip7702_refund: 0,
+                budget: None,
+                decoded_bytecodes: HashMap::new(),
+                #[cfg(feature = "std")]
+                analysis_cache: None,
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
             },
@@ -1,20 +1,23 @@
 use crate::{
     db::Database,
     interpreter::{
-        analysis::to_analysed, gas, return_ok, Contract, CreateInputs, EOFCreateInput, Gas,
-        InstructionResult, Interpreter, InterpreterResult, LoadAccountResult, SStoreResult,
-        SelfDestructResult, MAX_CODE_SIZE,
+        analysis::to_analysed, gas, return_ok, BudgetTracker, Contract, CreateInputs,
+        DecodedBytecode, EOFCreateInput, Gas, InstructionResult, Interpreter, InterpreterResult,
+        LoadAccountResult, SStoreResult, SelfDestructResult, MAX_CODE_SIZE,
     },
     journaled_state::JournaledState,
     primitives::{
         keccak256, Account, Address, AnalysisKind, Bytecode, Bytes, CreateScheme, EVMError, Env,
-        Eof, HashSet, Spec,
+        Eof, HashMap, HashSet, Spec,
         SpecId::{self, *},
         B256, U256,
     },
     FrameOrResult, JournalCheckpoint, CALL_STACK_LIMIT,
 };
-use std::boxed::Box;
+use std::{boxed::Box, sync::Arc};
+
+/// Maximum number of the cached pre-decoded bytecodes, see [InnerEvmContext::decoded_bytecodes].
+pub const MAX_DECODED_BYTECODES: usize = 4096;
 
 /// EVM contexts contains data that EVM needs for execution.
 #[derive(Debug)]
@@ -28,6 +31,17 @@ pub struct InnerEvmContext<DB: Database> {
     pub db: DB,
     /// Error that happened during execution.
     pub error: Result<(), EVMError<DB::Error>>,
+    /// Gas refund of the EIP-7702 authorization list applied in the current transaction.
+    pub eip7702_refund: i64,
+    /// Execution budget of the current transaction, it is started by the first executed frame.
+    pub budget: Option<BudgetTracker>,
+    /// Pre-decoded legacy bytecodes by code hash, used with [AnalysisKind::Decode].
+    ///
+    /// It is cleared when it reaches [MAX_DECODED_BYTECODES] entries.
+    pub decoded_bytecodes: HashMap<B256, Arc<DecodedBytecode>>,
+    /// Analysed bytecode cache that can be shared with other EVM instances.
+    #[cfg(feature = "std")]
+    pub analysis_cache: Option<crate::AnalysisCache>,
     /// Used as temporary value holder to store L1 block info.
     #[cfg(feature = "optimism")]
     pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
@@ -43,6 +57,11 @@ where
             journaled_state: self.journaled_state.clone(),
             db: self.db.clone(),
             error: self.error.clone(),
+            eip7702_refund: self.eip7702_refund,
+            budget: self.budget.clone(),
+            decoded_bytecodes: self.decoded_bytecodes.clone(),
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache.clone(),
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info.clone(),
         }
@@ -56,6 +75,11 @@ impl<DB: Database> InnerEvmContext<DB> {
             journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
             db,
             error: Ok(()),
+            eip7702_refund: 0,
+            budget: None,
+            decoded_bytecodes: HashMap::new(),
+            #[cfg(feature = "std")]
+            analysis_cache: None,
             #[cfg(feature = "optimism")]
             l1_block_info: None,
         }
@@ -69,6 +93,11 @@ impl<DB: Database> InnerEvmContext<DB> {
             journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
             db,
             error: Ok(()),
+            eip7702_refund: 0,
+            budget: None,
+            decoded_bytecodes: HashMap::new(),
+            #[cfg(feature = "std")]
+            analysis_cache: None,
             #[cfg(feature = "optimism")]
             l1_block_info: None,
         }
@@ -84,6 +113,11 @@ impl<DB: Database> InnerEvmContext<DB> {
             journaled_state: self.journaled_state,
             db,
             error: Ok(()),
+            eip7702_refund: self.eip7702_refund,
+            budget: self.budget,
+            decoded_bytecodes: self.decoded_bytecodes,
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache,
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info,
         }
@@ -152,6 +186,32 @@ impl<DB: Database> InnerEvmContext<DB> {
             .load_account_exist(address, &mut self.db)
     }
 
+    /// Load account and, if its code is an EIP-7702 delegation designator, the delegated account.
+    ///
+    /// Delegated account is loaded so it becomes warm and its coldness is returned inside
+    /// [`LoadAccountResult::is_delegate_account_cold`].
+    #[inline]
+    pub fn load_account_delegated(
+        &mut self,
+        address: Address,
+    ) -> Result<LoadAccountResult, EVMError<DB::Error>> {
+        let spec = self.spec_id();
+        let mut account_load = self.load_account_exist(address)?;
+        if !spec.is_enabled_in(PRAGUE) {
+            return Ok(account_load);
+        }
+
+        let (account, _) = self.journaled_state.load_code(address, &mut self.db)?;
+        if let Some(Bytecode::Eip7702(code)) = &account.info.code {
+            let delegated_address = code.address();
+            let (_, is_cold) = self
+                .journaled_state
+                .load_account(delegated_address, &mut self.db)?;
+            account_load.is_delegate_account_cold = Some(is_cold);
+        }
+        Ok(account_load)
+    }
+
     /// Return account balance and is_cold flag.
     #[inline]
     pub fn balance(&mut self, address: Address) -> Result<(U256, bool), EVMError<DB::Error>> {
@@ -160,22 +220,82 @@ impl<DB: Database> InnerEvmContext<DB> {
             .map(|(acc, is_cold)| (acc.info.balance, is_cold))
     }
 
-    /// Return account code and if address is cold loaded.
+    /// Returns the pre-decoded bytecode of the contract if [AnalysisKind::Decode] is enabled.
+    ///
+    /// Only analysed legacy bytecode with known hash is decoded, decoded bytecode is cached
+    /// by its code hash. Init code of CREATE has zero hash, so it is not decoded.
+    pub fn decoded_bytecode(&mut self, contract: &Contract) -> Option<Arc<DecodedBytecode>> {
+        if self.env.cfg.perf_analyse_created_bytecodes != AnalysisKind::Decode {
+            return None;
+        }
+        let (Some(hash), Bytecode::LegacyAnalyzed(bytecode)) = (contract.hash, &contract.bytecode)
+        else {
+            return None;
+        };
+        if hash == B256::ZERO {
+            return None;
+        }
+        if self.decoded_bytecodes.len() >= MAX_DECODED_BYTECODES
+            && !self.decoded_bytecodes.contains_key(&hash)
+        {
+            self.decoded_bytecodes.clear();
+        }
+        Some(
+            self.decoded_bytecodes
+                .entry(hash)
+                .or_insert_with(|| Arc::new(DecodedBytecode::new(bytecode)))
+                .clone(),
+        )
+    }
+
+    /// Return account code and the result of the account load.
+    ///
+    /// Code of the delegated account is returned for the EIP-7702 delegated accounts, the
+    /// delegated account is loaded with [InnerEvmContext::load_account_delegated].
     #[inline]
-    pub fn code(&mut self, address: Address) -> Result<(Bytecode, bool), EVMError<DB::Error>> {
+    pub fn code(
+        &mut self,
+        address: Address,
+    ) -> Result<(Bytecode, LoadAccountResult), EVMError<DB::Error>> {
+        let (address, load) = self.code_address(address)?;
         self.journaled_state
             .load_code(address, &mut self.db)
-            .map(|(a, is_cold)| (a.info.code.clone().unwrap(), is_cold))
+            .map(|(a, _)| (a.info.code.clone().unwrap(), load))
     }
 
-    /// Get code hash of address.
+    /// Get code hash of address and the result of the account load.
+    ///
+    /// Code hash of the delegated account is returned for the EIP-7702 delegated accounts.
     #[inline]
-    pub fn code_hash(&mut self, address: Address) -> Result<(B256, bool), EVMError<DB::Error>> {
-        let (acc, is_cold) = self.journaled_state.load_code(address, &mut self.db)?;
+    pub fn code_hash(
+        &mut self,
+        address: Address,
+    ) -> Result<(B256, LoadAccountResult), EVMError<DB::Error>> {
+        let (address, load) = self.code_address(address)?;
+        let (acc, _) = self.journaled_state.load_code(address, &mut self.db)?;
         if acc.is_empty() {
-            return Ok((B256::ZERO, is_cold));
+            return Ok((B256::ZERO, load));
+        }
+        Ok((acc.info.code_hash, load))
+    }
+
+    /// Loads the account with its delegated account and returns the address of the account
+    /// whose code is executed at the address.
+    ///
+    /// It is the delegated address if the account has the EIP-7702 delegation designator.
+    fn code_address(
+        &mut self,
+        address: Address,
+    ) -> Result<(Address, LoadAccountResult), EVMError<DB::Error>> {
+        let load = self.load_account_delegated(address)?;
+        if load.is_delegate_account_cold.is_none() {
+            return Ok((address, load));
+        }
+        let (account, _) = self.journaled_state.load_code(address, &mut self.db)?;
+        match &account.info.code {
+            Some(Bytecode::Eip7702(code)) => Ok((code.address(), load)),
+            _ => Ok((address, load)),
         }
-        Ok((acc.info.code_hash, is_cold))
     }
 
     /// Load storage slot, if storage is not present inside the account then it will be loaded from database.
@@ -494,7 +614,7 @@ impl<DB: Database> InnerEvmContext<DB> {
         // Do analysis of bytecode straight away.
         let bytecode = match self.env.cfg.perf_analyse_created_bytecodes {
             AnalysisKind::Raw => Bytecode::new_raw(interpreter_result.output.clone()),
-            AnalysisKind::Analyse => {
+            AnalysisKind::Analyse | AnalysisKind::Decode => {
                 to_analysed(Bytecode::new_raw(interpreter_result.output.clone()))
             }
         };
@@ -1,20 +1,37 @@
 //! [Database] implementations.
 
-//#[cfg(feature = "alloydb")]
-//pub mod alloydb;
+#[cfg(feature = "alloydb")]
+pub mod alloydb;
+#[cfg(feature = "asyncdb")]
+pub mod async_db;
+#[cfg(feature = "diskdb")]
+pub mod diskdb;
 pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 pub mod ethersdb;
 pub mod in_memory_db;
+#[cfg(all(test, any(feature = "ethersdb", feature = "alloydb")))]
+mod mock_rpc;
+pub mod snapshot;
 pub mod states;
 
 pub use crate::primitives::db::*;
-//#[cfg(feature = "alloydb")]
-//pub use alloydb::AlloyDB;
+#[cfg(feature = "alloydb")]
+pub use alloydb::AlloyDB;
+#[cfg(feature = "asyncdb")]
+pub use async_db::{DatabaseAsync, DatabaseAsyncRef, WrapDatabaseAsync};
+#[cfg(feature = "diskdb")]
+pub use diskdb::{DiskDB, DiskDBError};
 pub use emptydb::{EmptyDB, EmptyDBTyped};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
 pub use in_memory_db::*;
+pub use snapshot::{DatabaseSnapshot, SnapshotId};
+#[cfg(feature = "state-root")]
+pub use states::{
+    log_rlp_hash, ordered_trie_root, state_merkle_trie_root, storage_root, trie_root, TrieState,
+    EMPTY_ROOT_HASH,
+};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
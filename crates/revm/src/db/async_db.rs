@@ -0,0 +1,218 @@
+use crate::{
+    db::{Database, DatabaseRef},
+    primitives::{AccountInfo, Address, Bytecode, B256, U256},
+};
+use core::future::Future;
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
+
+/// The async EVM database interface.
+///
+/// Contains the same methods as [Database], but it returns [Future] type.
+///
+/// Use [WrapDatabaseAsync] to provide [Database] implementation for a type that only
+/// implements this trait.
+pub trait DatabaseAsync {
+    /// The database error type.
+    type Error: Send;
+
+    /// Get basic account information.
+    fn basic_async(
+        &mut self,
+        address: Address,
+    ) -> impl Future<Output = Result<Option<AccountInfo>, Self::Error>> + Send;
+
+    /// Get account code by its hash.
+    fn code_by_hash_async(
+        &mut self,
+        code_hash: B256,
+    ) -> impl Future<Output = Result<Bytecode, Self::Error>> + Send;
+
+    /// Get storage value of address at index.
+    fn storage_async(
+        &mut self,
+        address: Address,
+        index: U256,
+    ) -> impl Future<Output = Result<U256, Self::Error>> + Send;
+
+    /// Get block hash by block number.
+    fn block_hash_async(
+        &mut self,
+        number: U256,
+    ) -> impl Future<Output = Result<B256, Self::Error>> + Send;
+}
+
+/// The async EVM database interface.
+///
+/// Contains the same methods as [DatabaseRef], but it returns [Future] type.
+///
+/// Use [WrapDatabaseAsync] to provide [DatabaseRef] implementation for a type that only
+/// implements this trait.
+pub trait DatabaseAsyncRef {
+    /// The database error type.
+    type Error: Send;
+
+    /// Get basic account information.
+    fn basic_async_ref(
+        &self,
+        address: Address,
+    ) -> impl Future<Output = Result<Option<AccountInfo>, Self::Error>> + Send;
+
+    /// Get account code by its hash.
+    fn code_by_hash_async_ref(
+        &self,
+        code_hash: B256,
+    ) -> impl Future<Output = Result<Bytecode, Self::Error>> + Send;
+
+    /// Get storage value of address at index.
+    fn storage_async_ref(
+        &self,
+        address: Address,
+        index: U256,
+    ) -> impl Future<Output = Result<U256, Self::Error>> + Send;
+
+    /// Get block hash by block number.
+    fn block_hash_async_ref(
+        &self,
+        number: U256,
+    ) -> impl Future<Output = Result<B256, Self::Error>> + Send;
+}
+
+/// Wraps a [DatabaseAsync] or [DatabaseAsyncRef] to provide a [Database] implementation.
+///
+/// Futures are run on a runtime owned by the wrapper, so it can be used outside of a tokio
+/// runtime. Inside a multi-threaded runtime the future is run on the current runtime with
+/// [tokio::task::block_in_place], inside a current-thread runtime, which can't be blocked,
+/// it is run on a scoped thread.
+#[derive(Debug)]
+pub struct WrapDatabaseAsync<T> {
+    db: T,
+    rt: OwnedRuntime,
+}
+
+impl<T> WrapDatabaseAsync<T> {
+    /// Wraps the async database with a new current-thread runtime.
+    pub fn new(db: T) -> std::io::Result<Self> {
+        let rt = Builder::new_current_thread().enable_all().build()?;
+        Ok(Self::with_runtime(db, rt))
+    }
+
+    /// Wraps the async database with the runtime used outside of tokio runtimes.
+    pub fn with_runtime(db: T, rt: Runtime) -> Self {
+        Self {
+            db,
+            rt: OwnedRuntime(Some(rt)),
+        }
+    }
+
+    /// Returns the reference to the async database.
+    pub fn inner(&self) -> &T {
+        &self.db
+    }
+
+    /// Returns the mutable reference to the async database.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.db
+    }
+
+    /// Consumes the wrapper and returns the async database.
+    pub fn into_inner(self) -> T {
+        self.db
+    }
+
+    #[inline]
+    fn block_on<F>(rt: &Runtime, f: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        block_on(rt, f)
+    }
+}
+
+/// Runs the future to completion, on the current runtime if there is one, otherwise on the
+/// given runtime.
+#[inline]
+pub(crate) fn block_on<F>(rt: &Runtime, f: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    match Handle::try_current() {
+        Ok(handle) => match handle.runtime_flavor() {
+            // tokio doesn't allow the current_thread runtime to block_in_place.
+            RuntimeFlavor::CurrentThread => {
+                std::thread::scope(|s| s.spawn(|| rt.block_on(f)).join().unwrap())
+            }
+            _ => tokio::task::block_in_place(move || handle.block_on(f)),
+        },
+        Err(_) => rt.block_on(f),
+    }
+}
+
+impl<T: DatabaseAsync> Database for WrapDatabaseAsync<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.basic_async(address))
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.code_by_hash_async(code_hash))
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.storage_async(address, index))
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.block_hash_async(number))
+    }
+}
+
+impl<T: DatabaseAsyncRef> DatabaseRef for WrapDatabaseAsync<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.basic_async_ref(address))
+    }
+
+    #[inline]
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.code_by_hash_async_ref(code_hash))
+    }
+
+    #[inline]
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.storage_async_ref(address, index))
+    }
+
+    #[inline]
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        Self::block_on(self.rt.get(), self.db.block_hash_async_ref(number))
+    }
+}
+
+/// Runtime that is shut down in the background on drop, so the wrapper can be dropped inside
+/// of an async context.
+#[derive(Debug)]
+struct OwnedRuntime(Option<Runtime>);
+
+impl OwnedRuntime {
+    #[inline]
+    fn get(&self) -> &Runtime {
+        self.0.as_ref().expect("runtime is only taken on drop")
+    }
+}
+
+impl Drop for OwnedRuntime {
+    fn drop(&mut self) {
+        if let Some(rt) = self.0.take() {
+            rt.shutdown_background();
+        }
+    }
+}
@@ -0,0 +1,592 @@
+use crate::{
+    db::states::{PlainStateReverts, PlainStorageRevert, RevertToSlot, StateChangeset},
+    primitives::{
+        Account, AccountInfo, Address, Bytecode, Bytes, Eof, HashMap, B256, KECCAK_EMPTY, U256,
+    },
+    Database, DatabaseCommit, DatabaseRef,
+};
+use core::fmt;
+use redb::{ReadableTable, Table, TableDefinition, WriteTransaction};
+use std::{boxed::Box, path::Path, sync::Arc, vec::Vec};
+
+/// Table with byte keys and values.
+type BytesTable = TableDefinition<'static, &'static [u8], &'static [u8]>;
+
+/// Address to balance, nonce and code hash.
+const ACCOUNTS: BytesTable = TableDefinition::new("accounts");
+/// Address and slot to value.
+const STORAGE: BytesTable = TableDefinition::new("storage");
+/// Code hash to original bytecode.
+const CONTRACTS: BytesTable = TableDefinition::new("contracts");
+/// Block number to block hash.
+const BLOCK_HASHES: TableDefinition<'static, u64, &'static [u8]> =
+    TableDefinition::new("block_hashes");
+/// Block number and address to account before the block, empty if it did not exist.
+const ACCOUNT_REVERTS: BytesTable = TableDefinition::new("account_reverts");
+/// Block number, address and slot to value before the block, empty if the storage was
+/// destroyed.
+const STORAGE_REVERTS: BytesTable = TableDefinition::new("storage_reverts");
+/// Block number and address of the storages wiped by the block.
+const WIPED_STORAGE: TableDefinition<'static, &'static [u8], ()> =
+    TableDefinition::new("wiped_storage");
+
+/// Length of the encoded [AccountInfo].
+const ACCOUNT_LEN: usize = 32 + 8 + 32;
+
+/// Error of the [DiskDB].
+#[derive(Debug)]
+pub enum DiskDBError {
+    /// Error of the underlying storage.
+    Redb(Box<redb::Error>),
+    /// Code of the account is not in the database.
+    MissingCode(B256),
+    /// Stored value is not valid.
+    Corrupted,
+}
+
+impl fmt::Display for DiskDBError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Redb(e) => write!(f, "storage error: {e}"),
+            Self::MissingCode(hash) => write!(f, "missing code with hash {hash}"),
+            Self::Corrupted => f.write_str("corrupted database"),
+        }
+    }
+}
+
+impl std::error::Error for DiskDBError {}
+
+macro_rules! impl_from_redb_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for DiskDBError {
+                fn from(e: $error) -> Self {
+                    Self::Redb(Box::new(e.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_redb_error!(
+    redb::Error,
+    redb::DatabaseError,
+    redb::TransactionError,
+    redb::TableError,
+    redb::StorageError,
+    redb::CommitError
+);
+
+/// A [Database] implementation that persists the state on disk.
+///
+/// It is backed by the embedded [redb] key-value store, so the state survives restarts and
+/// is not limited by the memory. Changes are written with [DatabaseCommit::commit] after
+/// each transaction, or per block with [DiskDB::write_plain_state] and
+/// [DiskDB::write_reverts] from [BundleState::into_plain_state_and_reverts].
+///
+/// Block hashes that are not in the database are zero.
+///
+/// [BundleState::into_plain_state_and_reverts]: crate::db::BundleState::into_plain_state_and_reverts
+#[derive(Clone)]
+pub struct DiskDB {
+    db: Arc<redb::Database>,
+}
+
+impl fmt::Debug for DiskDB {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiskDB").finish_non_exhaustive()
+    }
+}
+
+impl DiskDB {
+    /// Opens the database at the path, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DiskDBError> {
+        let db = redb::Database::create(path)?;
+        let tx = db.begin_write()?;
+        tx.open_table(ACCOUNTS)?;
+        tx.open_table(STORAGE)?;
+        tx.open_table(CONTRACTS)?;
+        tx.open_table(BLOCK_HASHES)?;
+        tx.open_table(ACCOUNT_REVERTS)?;
+        tx.open_table(STORAGE_REVERTS)?;
+        tx.open_table(WIPED_STORAGE)?;
+        tx.commit()?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Inserts the account info and its code, storage is not changed.
+    pub fn insert_account_info(
+        &self,
+        address: Address,
+        info: AccountInfo,
+    ) -> Result<(), DiskDBError> {
+        let tx = self.db.begin_write()?;
+        write_account(&tx, address, Some(info))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Inserts the storage slot of the account.
+    pub fn insert_account_storage(
+        &self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> Result<(), DiskDBError> {
+        let tx = self.db.begin_write()?;
+        write_slot(&mut tx.open_table(STORAGE)?, address, slot, value)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Inserts the hash of the block.
+    pub fn insert_block_hash(&self, number: u64, hash: B256) -> Result<(), DiskDBError> {
+        let tx = self.db.begin_write()?;
+        tx.open_table(BLOCK_HASHES)?
+            .insert(number, hash.as_slice())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Commits the changes of the transaction.
+    ///
+    /// Like [DatabaseCommit::commit] but returns the error instead of panicking.
+    pub fn try_commit(&self, changes: HashMap<Address, Account>) -> Result<(), DiskDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut storage = tx.open_table(STORAGE)?;
+            for (address, account) in changes {
+                if !account.is_touched() {
+                    continue;
+                }
+                if account.is_selfdestructed() {
+                    write_account(&tx, address, None)?;
+                    wipe_storage(&mut storage, address)?;
+                    continue;
+                }
+                if account.is_created() {
+                    wipe_storage(&mut storage, address)?;
+                }
+                for (slot, value) in account.storage.into_iter().filter(|(_, v)| v.is_changed()) {
+                    write_slot(&mut storage, address, slot, value.present_value())?;
+                }
+                write_account(&tx, address, Some(account.info))?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Writes the state changes of [BundleState::into_plain_state].
+    ///
+    /// [BundleState::into_plain_state]: crate::db::BundleState::into_plain_state
+    pub fn write_plain_state(&self, changeset: StateChangeset) -> Result<(), DiskDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut contracts = tx.open_table(CONTRACTS)?;
+            for (hash, bytecode) in changeset.contracts {
+                contracts.insert(hash.as_slice(), bytecode.original_byte_slice())?;
+            }
+            let mut storage = tx.open_table(STORAGE)?;
+            for changes in changeset.storage {
+                if changes.wipe_storage {
+                    wipe_storage(&mut storage, changes.address)?;
+                }
+                for (slot, value) in changes.storage {
+                    write_slot(&mut storage, changes.address, slot, value)?;
+                }
+            }
+        }
+        for (address, info) in changeset.accounts {
+            write_account(&tx, address, info)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Writes the reverts of the blocks starting with `first_block`, one entry of
+    /// [PlainStateReverts] per block.
+    pub fn write_reverts(
+        &self,
+        first_block: u64,
+        reverts: PlainStateReverts,
+    ) -> Result<(), DiskDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut account_reverts = tx.open_table(ACCOUNT_REVERTS)?;
+            for (block, accounts) in (first_block..).zip(reverts.accounts) {
+                for (address, info) in accounts {
+                    let value = info.as_ref().map(encode_account).unwrap_or_default();
+                    account_reverts.insert(
+                        block_address_key(block, address).as_slice(),
+                        value.as_slice(),
+                    )?;
+                }
+            }
+            let mut storage_reverts = tx.open_table(STORAGE_REVERTS)?;
+            let mut wiped_storage = tx.open_table(WIPED_STORAGE)?;
+            for (block, storage) in (first_block..).zip(reverts.storage) {
+                for revert in storage {
+                    let key = block_address_key(block, revert.address);
+                    if revert.wiped {
+                        wiped_storage.insert(key.as_slice(), ())?;
+                    }
+                    for (slot, value) in revert.storage_revert {
+                        let mut slot_key = key.clone();
+                        slot_key.extend_from_slice(&slot.to_be_bytes::<32>());
+                        let value = match value {
+                            RevertToSlot::Some(value) => value.to_be_bytes::<32>().to_vec(),
+                            RevertToSlot::Destroyed => Vec::new(),
+                        };
+                        storage_reverts.insert(slot_key.as_slice(), value.as_slice())?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the reverts of the block written with [DiskDB::write_reverts], with one
+    /// entry for the block.
+    pub fn block_reverts(&self, block: u64) -> Result<PlainStateReverts, DiskDBError> {
+        let tx = self.db.begin_read()?;
+        let (start, end) = (block.to_be_bytes(), (block + 1).to_be_bytes());
+        let range = start.as_slice()..end.as_slice();
+
+        let mut accounts = Vec::new();
+        for entry in tx.open_table(ACCOUNT_REVERTS)?.range(range.clone())? {
+            let (key, value) = entry?;
+            let address = Address::from_slice(&key.value()[8..]);
+            let info = match value.value() {
+                [] => None,
+                value => Some(decode_account(value)?),
+            };
+            accounts.push((address, info));
+        }
+
+        let mut storage: Vec<PlainStorageRevert> = Vec::new();
+        for entry in tx.open_table(WIPED_STORAGE)?.range(range.clone())? {
+            let (key, _) = entry?;
+            storage.push(PlainStorageRevert {
+                address: Address::from_slice(&key.value()[8..]),
+                wiped: true,
+                storage_revert: Vec::new(),
+            });
+        }
+        for entry in tx.open_table(STORAGE_REVERTS)?.range(range)? {
+            let (key, value) = entry?;
+            let key = key.value();
+            let address = Address::from_slice(&key[8..28]);
+            let slot = U256::from_be_slice(&key[28..]);
+            let value = match value.value() {
+                [] => RevertToSlot::Destroyed,
+                value => RevertToSlot::Some(U256::from_be_slice(value)),
+            };
+            match storage.iter_mut().find(|revert| revert.address == address) {
+                Some(revert) => revert.storage_revert.push((slot, value)),
+                None => storage.push(PlainStorageRevert {
+                    address,
+                    wiped: false,
+                    storage_revert: vec![(slot, value)],
+                }),
+            }
+        }
+
+        Ok(PlainStateReverts {
+            accounts: vec![accounts],
+            storage: vec![storage],
+        })
+    }
+}
+
+/// Writes the account and its code, or removes the account if `None`.
+fn write_account(
+    tx: &WriteTransaction,
+    address: Address,
+    info: Option<AccountInfo>,
+) -> Result<(), DiskDBError> {
+    let mut accounts = tx.open_table(ACCOUNTS)?;
+    let Some(info) = info else {
+        accounts.remove(address.as_slice())?;
+        return Ok(());
+    };
+    if let Some(code) = info.code.as_ref().filter(|code| !code.is_empty()) {
+        tx.open_table(CONTRACTS)?
+            .insert(info.code_hash.as_slice(), code.original_byte_slice())?;
+    }
+    accounts.insert(address.as_slice(), encode_account(&info).as_slice())?;
+    Ok(())
+}
+
+/// Writes the storage slot, zero values are removed.
+fn write_slot(
+    storage: &mut Table<'_, &'static [u8], &'static [u8]>,
+    address: Address,
+    slot: U256,
+    value: U256,
+) -> Result<(), DiskDBError> {
+    let key = storage_key(address, slot);
+    if value.is_zero() {
+        storage.remove(key.as_slice())?;
+    } else {
+        storage.insert(key.as_slice(), value.to_be_bytes::<32>().as_slice())?;
+    }
+    Ok(())
+}
+
+/// Removes all storage slots of the account.
+fn wipe_storage(
+    storage: &mut Table<'_, &'static [u8], &'static [u8]>,
+    address: Address,
+) -> Result<(), DiskDBError> {
+    let (start, end) = (
+        storage_key(address, U256::ZERO),
+        storage_key(address, U256::MAX),
+    );
+    let mut keys = Vec::new();
+    for entry in storage.range(start.as_slice()..=end.as_slice())? {
+        keys.push(entry?.0.value().to_vec());
+    }
+    for key in keys {
+        storage.remove(key.as_slice())?;
+    }
+    Ok(())
+}
+
+fn storage_key(address: Address, slot: U256) -> [u8; 52] {
+    let mut key = [0; 52];
+    key[..20].copy_from_slice(address.as_slice());
+    key[20..].copy_from_slice(&slot.to_be_bytes::<32>());
+    key
+}
+
+fn block_address_key(block: u64, address: Address) -> Vec<u8> {
+    let mut key = block.to_be_bytes().to_vec();
+    key.extend_from_slice(address.as_slice());
+    key
+}
+
+fn encode_account(info: &AccountInfo) -> Vec<u8> {
+    let mut value = Vec::with_capacity(ACCOUNT_LEN);
+    value.extend_from_slice(&info.balance.to_be_bytes::<32>());
+    value.extend_from_slice(&info.nonce.to_be_bytes());
+    value.extend_from_slice(info.code_hash.as_slice());
+    value
+}
+
+/// Decodes the account without its code.
+fn decode_account(value: &[u8]) -> Result<AccountInfo, DiskDBError> {
+    if value.len() != ACCOUNT_LEN {
+        return Err(DiskDBError::Corrupted);
+    }
+    Ok(AccountInfo {
+        balance: U256::from_be_slice(&value[..32]),
+        nonce: u64::from_be_bytes(value[32..40].try_into().unwrap()),
+        code_hash: B256::from_slice(&value[40..]),
+        code: None,
+    })
+}
+
+impl DatabaseRef for DiskDB {
+    type Error = DiskDBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let tx = self.db.begin_read()?;
+        let Some(value) = tx.open_table(ACCOUNTS)?.get(address.as_slice())? else {
+            return Ok(None);
+        };
+        let mut info = decode_account(value.value())?;
+        if info.code_hash != KECCAK_EMPTY {
+            info.code = Some(self.code_by_hash_ref(info.code_hash)?);
+        }
+        Ok(Some(info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY || code_hash == B256::ZERO {
+            return Ok(Bytecode::default());
+        }
+        let tx = self.db.begin_read()?;
+        let code = tx
+            .open_table(CONTRACTS)?
+            .get(code_hash.as_slice())?
+            .ok_or(DiskDBError::MissingCode(code_hash))?;
+        let code = Bytes::copy_from_slice(code.value());
+        if code.starts_with(&[0xEF, 0x00]) {
+            let eof = Eof::decode(code).map_err(|_| DiskDBError::Corrupted)?;
+            return Ok(Bytecode::Eof(eof));
+        }
+        Ok(Bytecode::new_raw(code))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let tx = self.db.begin_read()?;
+        let value = tx
+            .open_table(STORAGE)?
+            .get(storage_key(address, index).as_slice())?;
+        Ok(value
+            .map(|value| U256::from_be_slice(value.value()))
+            .unwrap_or_default())
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        let Ok(number) = u64::try_from(number) else {
+            return Ok(B256::ZERO);
+        };
+        let tx = self.db.begin_read()?;
+        let hash = tx.open_table(BLOCK_HASHES)?.get(number)?;
+        Ok(hash
+            .map(|hash| B256::from_slice(hash.value()))
+            .unwrap_or_default())
+    }
+}
+
+impl Database for DiskDB {
+    type Error = DiskDBError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl DatabaseCommit for DiskDB {
+    /// Commits the changes of the transaction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the changes can't be written, use [DiskDB::try_commit] to handle the error.
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.try_commit(changes)
+            .expect("failed to commit changes to the disk database");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{states::bundle_state::BundleRetention, OriginalValuesKnown, State},
+        primitives::{address, hex, TransactTo},
+        Evm,
+    };
+
+    struct TempPath(std::path::PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("revm-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// PUSH1 0, SLOAD, PUSH1 1, ADD, PUSH1 0, SSTORE, STOP
+    const COUNTER_CODE: &[u8] = &hex!("600054600101600055 00");
+    const CONTRACT: Address = address!("0000000000000000000000000000000000001000");
+
+    fn counter_evm<DB: Database>(db: DB) -> Evm<'static, (), DB> {
+        Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.transact_to = TransactTo::Call(CONTRACT);
+                tx.gas_limit = 100_000;
+            })
+            .build()
+    }
+
+    #[test]
+    fn commit_and_reopen() {
+        let path = TempPath::new("diskdb-commit");
+        let code = Bytecode::new_raw(COUNTER_CODE.into());
+        {
+            let db = DiskDB::open(&path.0).unwrap();
+            db.insert_account_info(
+                CONTRACT,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code.clone()),
+            )
+            .unwrap();
+            db.insert_block_hash(1, B256::with_last_byte(1)).unwrap();
+            let mut evm = counter_evm(db);
+            evm.transact_commit().unwrap();
+            evm.transact_commit().unwrap();
+        }
+
+        let mut db = DiskDB::open(&path.0).unwrap();
+        assert_eq!(db.storage(CONTRACT, U256::ZERO).unwrap(), U256::from(2));
+        let info = db.basic(CONTRACT).unwrap().unwrap();
+        assert_eq!(info.code.unwrap().original_bytes(), code.original_bytes());
+        assert_eq!(
+            db.block_hash(U256::from(1)).unwrap(),
+            B256::with_last_byte(1)
+        );
+        assert_eq!(db.block_hash(U256::from(2)).unwrap(), B256::ZERO);
+    }
+
+    #[test]
+    fn write_plain_state_and_reverts() {
+        let path = TempPath::new("diskdb-bundle");
+        let code = Bytecode::new_raw(COUNTER_CODE.into());
+        let db = DiskDB::open(&path.0).unwrap();
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        )
+        .unwrap();
+
+        let state = State::builder()
+            .with_database_ref(db.clone())
+            .with_bundle_update()
+            .build();
+        let mut evm = counter_evm(state);
+        evm.transact_commit().unwrap();
+        evm.db_mut().merge_transitions(BundleRetention::Reverts);
+        let bundle = evm.db_mut().take_bundle();
+        let (changeset, reverts) = bundle.into_plain_state_and_reverts(OriginalValuesKnown::Yes);
+        db.write_plain_state(changeset).unwrap();
+        db.write_reverts(7, reverts).unwrap();
+
+        assert_eq!(db.storage_ref(CONTRACT, U256::ZERO).unwrap(), U256::from(1));
+        let reverts = db.block_reverts(7).unwrap();
+        assert_eq!(
+            reverts.storage,
+            vec![vec![PlainStorageRevert {
+                address: CONTRACT,
+                wiped: false,
+                storage_revert: vec![(U256::ZERO, RevertToSlot::Some(U256::ZERO))],
+            }]]
+        );
+        // caller did not exist before the block.
+        assert_eq!(
+            reverts.accounts,
+            vec![vec![(
+                address!("0000000000000000000000000000000000000001"),
+                None
+            )]]
+        );
+        assert!(db.block_reverts(8).unwrap().storage[0].is_empty());
+    }
+}
@@ -2,86 +2,70 @@ use std::sync::Arc;
 
 use ethers_core::types::{Block, BlockId, TxHash, H160 as eH160, H256, U64 as eU64};
 use ethers_providers::Middleware;
-use tokio::runtime::{Builder, Handle, RuntimeFlavor};
 
+use tokio::runtime::Builder;
+
+use crate::db::{async_db::block_on, DatabaseAsync, DatabaseAsyncRef};
 use crate::primitives::{AccountInfo, Address, Bytecode, B256, KECCAK_EMPTY, U256};
-use crate::{Database, DatabaseRef};
 
+/// An ethers-powered async REVM database.
+///
+/// Use [WrapDatabaseAsync](crate::db::WrapDatabaseAsync) to use it as a [Database](crate::Database).
 #[derive(Debug, Clone)]
 pub struct EthersDB<M: Middleware> {
     client: Arc<M>,
-    block_number: Option<BlockId>,
+    block_number: BlockId,
 }
 
 impl<M: Middleware> EthersDB<M> {
     /// create ethers db connector inputs are url and block on what we are basing our database (None for latest)
+    ///
+    /// The latest block number is fetched by blocking on the client, use [EthersDB::latest] inside
+    /// of async code.
     pub fn new(client: Arc<M>, block_number: Option<BlockId>) -> Option<Self> {
-        let block_number: Option<BlockId> = if block_number.is_some() {
-            block_number
-        } else {
-            Some(BlockId::from(
-                Self::block_on(client.get_block_number()).ok()?,
-            ))
+        let block_number = match block_number {
+            Some(block_number) => block_number,
+            None => {
+                let rt = Builder::new_current_thread().enable_all().build().ok()?;
+                BlockId::from(block_on(&rt, client.get_block_number()).ok()?)
+            }
         };
+        Some(Self::with_block_number(client, block_number))
+    }
 
-        Some(Self {
+    /// create ethers db connector based on the given block
+    pub fn with_block_number(client: Arc<M>, block_number: BlockId) -> Self {
+        Self {
             client,
             block_number,
-        })
+        }
     }
 
-    /// internal utility function to call tokio feature and wait for output
-    #[inline]
-    fn block_on<F>(f: F) -> F::Output
-    where
-        F: core::future::Future + Send,
-        F::Output: Send,
-    {
-        match Handle::try_current() {
-            Ok(handle) => match handle.runtime_flavor() {
-                // This essentially equals to tokio::task::spawn_blocking because tokio doesn't
-                // allow current_thread runtime to block_in_place
-                RuntimeFlavor::CurrentThread => std::thread::scope(move |s| {
-                    s.spawn(move || {
-                        Builder::new_current_thread()
-                            .enable_all()
-                            .build()
-                            .unwrap()
-                            .block_on(f)
-                    })
-                    .join()
-                    .unwrap()
-                }),
-                _ => tokio::task::block_in_place(move || handle.block_on(f)),
-            },
-            Err(_) => Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .unwrap()
-                .block_on(f),
-        }
+    /// create ethers db connector based on the latest block
+    pub async fn latest(client: Arc<M>) -> Result<Self, M::Error> {
+        let block_number = client.get_block_number().await?;
+        Ok(Self::with_block_number(client, BlockId::from(block_number)))
     }
 
     /// set block number on which upcoming queries will be based
     #[inline]
     pub fn set_block_number(&mut self, block_number: BlockId) {
-        self.block_number = Some(block_number);
+        self.block_number = block_number;
     }
 }
 
-impl<M: Middleware> DatabaseRef for EthersDB<M> {
+impl<M: Middleware> DatabaseAsyncRef for EthersDB<M> {
     type Error = M::Error;
 
-    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+    async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         let add = eH160::from(address.0 .0);
+        let block_number = Some(self.block_number);
 
-        let f = async {
-            let nonce = self.client.get_transaction_count(add, self.block_number);
-            let balance = self.client.get_balance(add, self.block_number);
-            let code = self.client.get_code(add, self.block_number);
-            tokio::join!(nonce, balance, code)
-        };
-        let (nonce, balance, code) = Self::block_on(f);
+        let (nonce, balance, code) = tokio::join!(
+            self.client.get_transaction_count(add, block_number),
+            self.client.get_balance(add, block_number),
+            self.client.get_code(add, block_number)
+        );
 
         let balance = U256::from_limbs(balance?.0);
         let nonce = nonce?.as_u64();
@@ -90,86 +74,94 @@ impl<M: Middleware> DatabaseRef for EthersDB<M> {
         Ok(Some(AccountInfo::new(balance, nonce, code_hash, bytecode)))
     }
 
-    fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+    async fn code_by_hash_async_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
         panic!("Should not be called. Code is already loaded");
         // not needed because we already load code with basic info
     }
 
-    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+    async fn storage_async_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
         let add = eH160::from(address.0 .0);
         let index = H256::from(index.to_be_bytes());
-        let slot_value: H256 =
-            Self::block_on(self.client.get_storage_at(add, index, self.block_number))?;
+        let slot_value: H256 = self
+            .client
+            .get_storage_at(add, index, Some(self.block_number))
+            .await?;
         Ok(U256::from_be_bytes(slot_value.to_fixed_bytes()))
     }
 
-    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+    async fn block_hash_async_ref(&self, number: U256) -> Result<B256, Self::Error> {
         // saturate usize
         if number > U256::from(u64::MAX) {
             return Ok(KECCAK_EMPTY);
         }
         // We know number <= u64::MAX so unwrap is safe
         let number = eU64::from(u64::try_from(number).unwrap());
-        let block: Option<Block<TxHash>> =
-            Self::block_on(self.client.get_block(BlockId::from(number)))?;
+        let block: Option<Block<TxHash>> = self.client.get_block(BlockId::from(number)).await?;
         // If number is given, the block is supposed to be finalized so unwrap is safe too.
         Ok(B256::new(block.unwrap().hash.unwrap().0))
     }
 }
 
-impl<M: Middleware> Database for EthersDB<M> {
+impl<M: Middleware> DatabaseAsync for EthersDB<M> {
     type Error = M::Error;
 
     #[inline]
-    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
-        <Self as DatabaseRef>::basic_ref(self, address)
+    async fn basic_async(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_async_ref(address).await
     }
 
     #[inline]
-    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
-        <Self as DatabaseRef>::code_by_hash_ref(self, code_hash)
+    async fn code_by_hash_async(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_async_ref(code_hash).await
     }
 
     #[inline]
-    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
-        <Self as DatabaseRef>::storage_ref(self, address, index)
+    async fn storage_async(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_async_ref(address, index).await
     }
 
     #[inline]
-    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
-        <Self as DatabaseRef>::block_hash_ref(self, number)
+    async fn block_hash_async(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_async_ref(number).await
     }
 }
 
-// Run tests with `cargo test -- --nocapture` to see print statements
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::{mock_rpc::mock_server, DatabaseRef, WrapDatabaseAsync};
     use ethers_providers::{Http, Provider};
 
-    //#[test]
-    fn _can_get_basic() {
-        let client = Provider::<Http>::try_from(
-            "https://mainnet.infura.io/v3/c60b0bb42f8a4c6481ecd229eddaca27",
-        )
-        .unwrap();
-        let client = Arc::new(client);
-
-        let ethersdb = EthersDB::new(
-            Arc::clone(&client), // public infura mainnet
-            Some(BlockId::from(16148323)),
-        )
-        .unwrap();
-
-        // ETH/USDT pair on Uniswap V2
-        let address = "0x0d4a11d5EEaaC28EC3F61d100daF4d40471f1852"
-            .parse::<eH160>()
-            .unwrap();
-        let address = address.as_fixed_bytes().into();
-
-        let acc_info = ethersdb.basic_ref(address).unwrap().unwrap();
-
-        // check if not empty
-        assert!(acc_info.exists());
+    fn mock_db() -> WrapDatabaseAsync<EthersDB<Provider<Http>>> {
+        let client = Provider::<Http>::try_from(mock_server()).unwrap();
+        WrapDatabaseAsync::new(EthersDB::with_block_number(
+            Arc::new(client),
+            BlockId::from(1),
+        ))
+        .unwrap()
+    }
+
+    fn assert_state(db: &WrapDatabaseAsync<EthersDB<Provider<Http>>>) {
+        let address = Address::with_last_byte(1);
+        let info = db.basic_ref(address).unwrap().unwrap();
+        assert_eq!(info.nonce, 5);
+        assert_eq!(info.balance, U256::from(100));
+        assert_eq!(info.code.unwrap().original_byte_slice(), &[0x60, 0x00]);
+        assert_eq!(db.storage_ref(address, U256::ZERO).unwrap(), U256::from(42));
+    }
+
+    #[test]
+    fn outside_runtime() {
+        assert_state(&mock_db());
+    }
+
+    #[tokio::test]
+    async fn current_thread_runtime() {
+        assert_state(&mock_db());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn multi_thread_runtime() {
+        assert_state(&mock_db());
     }
 }
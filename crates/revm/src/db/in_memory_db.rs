@@ -1,4 +1,4 @@
-use super::{DatabaseCommit, DatabaseRef, EmptyDB};
+use super::{DatabaseCommit, DatabaseRef, DatabaseSnapshot, EmptyDB, SnapshotId};
 use crate::primitives::{
     hash_map::Entry, Account, AccountInfo, Address, Bytecode, HashMap, Log, B256, KECCAK_EMPTY,
     U256,
@@ -33,6 +33,12 @@ pub struct CacheDB<ExtDB> {
     ///
     /// Note: this is read-only, data is never written to this database.
     pub db: ExtDB,
+    /// Changes committed since each snapshot, see [DatabaseSnapshot].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    snapshots: Vec<CacheSnapshot>,
+    /// Identifier of the next snapshot.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    next_snapshot_id: SnapshotId,
 }
 
 impl<ExtDB: Default> Default for CacheDB<ExtDB> {
@@ -52,6 +58,8 @@ impl<ExtDB> CacheDB<ExtDB> {
             logs: Vec::default(),
             block_hashes: HashMap::new(),
             db,
+            snapshots: Vec::new(),
+            next_snapshot_id: 0,
         }
     }
 
@@ -133,6 +141,9 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
             if !account.is_touched() {
                 continue;
             }
+            if let Some(snapshot) = self.snapshots.last_mut() {
+                snapshot.record(address, &account, &mut self.accounts);
+            }
             if account.is_selfdestructed() {
                 let db_account = self.accounts.entry(address).or_default();
                 db_account.storage.clear();
@@ -165,6 +176,109 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
     }
 }
 
+/// Snapshots only record the changes applied by [DatabaseCommit::commit], accounts and storage
+/// inserted directly into the cache are not reverted.
+impl<ExtDB> DatabaseSnapshot for CacheDB<ExtDB> {
+    fn snapshot(&mut self) -> SnapshotId {
+        let id = self.next_snapshot_id;
+        self.next_snapshot_id += 1;
+        self.snapshots.push(CacheSnapshot {
+            id,
+            logs: self.logs.len(),
+            ..Default::default()
+        });
+        id
+    }
+
+    fn revert_to_snapshot(&mut self, id: SnapshotId) -> bool {
+        let Some(index) = self.snapshots.iter().position(|snapshot| snapshot.id == id) else {
+            return false;
+        };
+        for snapshot in self.snapshots.drain(index..).rev() {
+            self.logs.truncate(snapshot.logs);
+            for (address, account) in snapshot.accounts {
+                let Some(account) = account else {
+                    self.accounts.remove(&address);
+                    continue;
+                };
+                let db_account = self.accounts.entry(address).or_default();
+                db_account.info = account.info;
+                db_account.account_state = account.account_state;
+                if let Some(storage) = account.cleared_storage {
+                    db_account.storage = storage;
+                }
+                for (slot, value) in account.storage {
+                    match value {
+                        Some(value) => db_account.storage.insert(slot, value),
+                        None => db_account.storage.remove(&slot),
+                    };
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Values of the cache before the changes committed after a snapshot.
+#[derive(Debug, Clone, Default)]
+struct CacheSnapshot {
+    /// Identifier of the snapshot.
+    id: SnapshotId,
+    /// Accounts before their first change, `None` if the account was not cached.
+    accounts: HashMap<Address, Option<AccountSnapshot>>,
+    /// Number of logs when the snapshot was taken.
+    logs: usize,
+}
+
+#[derive(Debug, Clone)]
+struct AccountSnapshot {
+    info: AccountInfo,
+    account_state: AccountState,
+    /// Storage slots before their first change, `None` if the slot was not cached.
+    storage: HashMap<U256, Option<U256>>,
+    /// Storage before it was cleared, slot changes after it are not recorded.
+    cleared_storage: Option<HashMap<U256, U256>>,
+}
+
+impl CacheSnapshot {
+    /// Records the values changed by the committed account.
+    ///
+    /// Storage that is cleared by the commit is moved into the snapshot.
+    fn record(
+        &mut self,
+        address: Address,
+        account: &Account,
+        accounts: &mut HashMap<Address, DbAccount>,
+    ) {
+        let Some(db_account) = accounts.get_mut(&address) else {
+            self.accounts.entry(address).or_insert(None);
+            return;
+        };
+        let snapshot = self.accounts.entry(address).or_insert_with(|| {
+            Some(AccountSnapshot {
+                info: db_account.info.clone(),
+                account_state: db_account.account_state.clone(),
+                storage: HashMap::new(),
+                cleared_storage: None,
+            })
+        });
+        // account is removed on revert, or its whole storage is already recorded.
+        let Some(snapshot) = snapshot.as_mut().filter(|s| s.cleared_storage.is_none()) else {
+            return;
+        };
+        if account.is_selfdestructed() || account.is_created() {
+            snapshot.cleared_storage = Some(core::mem::take(&mut db_account.storage));
+        } else {
+            for slot in account.storage.keys() {
+                snapshot
+                    .storage
+                    .entry(*slot)
+                    .or_insert_with(|| db_account.storage.get(slot).copied());
+            }
+        }
+    }
+}
+
 impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     type Error = ExtDB::Error;
 
@@ -373,6 +487,29 @@ impl Database for BenchmarkDB {
     type Error = Infallible;
     /// Get basic account information.
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    /// Get account code by its hash
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    /// Get storage value of address at index.
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    // History related
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl DatabaseRef for BenchmarkDB {
+    type Error = Infallible;
+    /// Get basic account information.
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         if address == Address::ZERO {
             return Ok(Some(AccountInfo {
                 nonce: 1,
@@ -393,17 +530,17 @@ impl Database for BenchmarkDB {
     }
 
     /// Get account code by its hash
-    fn code_by_hash(&mut self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+    fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
         Ok(Bytecode::default())
     }
 
     /// Get storage value of address at index.
-    fn storage(&mut self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+    fn storage_ref(&self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
         Ok(U256::default())
     }
 
     // History related
-    fn block_hash(&mut self, _number: U256) -> Result<B256, Self::Error> {
+    fn block_hash_ref(&self, _number: U256) -> Result<B256, Self::Error> {
         Ok(B256::default())
     }
 }
@@ -0,0 +1,72 @@
+//! Mock JSON-RPC server of the provider backed database tests.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+/// Results of the mock JSON-RPC server by method.
+const RESULTS: &[(&str, &str)] = &[
+    ("eth_getTransactionCount", r#""0x5""#),
+    ("eth_getBalance", r#""0x64""#),
+    ("eth_getCode", r#""0x6000""#),
+    (
+        "eth_getStorageAt",
+        r#""0x000000000000000000000000000000000000000000000000000000000000002a""#,
+    ),
+];
+
+/// Starts the mock JSON-RPC server and returns its url.
+pub(crate) fn mock_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            std::thread::spawn(move || serve(stream));
+        }
+    });
+    url
+}
+
+/// Answers the HTTP requests of the connection until it is closed.
+fn serve(mut stream: TcpStream) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    loop {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim_end().to_ascii_lowercase();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(length) = line.strip_prefix("content-length:") {
+                content_length = length.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let body = String::from_utf8(body).unwrap();
+
+        let id: String = body
+            .split("\"id\":")
+            .nth(1)
+            .unwrap()
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        let result = RESULTS
+            .iter()
+            .find(|(method, _)| body.contains(&format!("\"method\":\"{method}\"")))
+            .map_or("null", |(_, result)| result);
+        let response = format!(r#"{{"jsonrpc":"2.0","id":{id},"result":{result}}}"#);
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{response}",
+            response.len()
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,24 @@
+//! Snapshots of the committed state that can be reverted.
+
+/// Identifier of a snapshot.
+///
+/// Identifiers are increasing and are not reused by the database, so a reverted or discarded
+/// snapshot can not be mistaken for a later one.
+pub type SnapshotId = usize;
+
+/// Database that can snapshot its committed state and revert to it.
+///
+/// Snapshots record the changes applied by [DatabaseCommit](super::DatabaseCommit) after the
+/// snapshot was taken, so they are cheap to take and to revert, and can span any number of
+/// transactions. Snapshots are nested, reverting to a snapshot discards it and all snapshots
+/// taken after it.
+pub trait DatabaseSnapshot {
+    /// Takes a snapshot of the committed state.
+    fn snapshot(&mut self) -> SnapshotId;
+
+    /// Reverts the changes committed since the snapshot was taken.
+    ///
+    /// Returns `false` if the snapshot does not exist, it was already reverted or a previous
+    /// snapshot was reverted.
+    fn revert_to_snapshot(&mut self, id: SnapshotId) -> bool;
+}
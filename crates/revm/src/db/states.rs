@@ -1,4 +1,6 @@
 pub mod account_status;
+#[cfg(feature = "state-root")]
+mod account_trie;
 pub mod bundle_account;
 pub mod bundle_state;
 pub mod cache;
@@ -8,6 +10,8 @@ pub mod plain_account;
 pub mod reverts;
 pub mod state;
 pub mod state_builder;
+#[cfg(feature = "state-root")]
+pub mod state_root;
 pub mod transition_account;
 pub mod transition_state;
 
@@ -22,5 +26,10 @@ pub use plain_account::{PlainAccount, StorageSlot, StorageWithOriginalValues};
 pub use reverts::{AccountRevert, RevertToSlot};
 pub use state::{DBBox, State, StateDBBox};
 pub use state_builder::StateBuilder;
+#[cfg(feature = "state-root")]
+pub use state_root::{
+    log_rlp_hash, ordered_trie_root, state_merkle_trie_root, storage_root, trie_root, TrieState,
+    EMPTY_ROOT_HASH,
+};
 pub use transition_account::TransitionAccount;
 pub use transition_state::TransitionState;
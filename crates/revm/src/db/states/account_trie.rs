@@ -0,0 +1,380 @@
+//! Merkle Patricia Trie of the accounts that keeps the hashes of its nodes.
+
+use crate::primitives::{keccak256, Address, B256};
+use alloy_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use core::mem;
+use std::{boxed::Box, vec, vec::Vec};
+
+/// Merkle Patricia Trie keyed by the hashed address.
+///
+/// Nodes cache their references, so only the nodes on the paths of the changed leaves are
+/// rehashed by [`AccountTrie::rehash`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(super) struct AccountTrie {
+    root: Option<Box<TrieNode>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TrieNode {
+    node: Node,
+    /// Reference of the node in its parent, `None` if the node was changed since the last
+    /// rehash.
+    reference: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node {
+    Leaf {
+        path: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        path: Vec<u8>,
+        child: Box<TrieNode>,
+    },
+    /// Keys have the same length, so branches never hold a value.
+    Branch {
+        children: [Option<Box<TrieNode>>; 16],
+    },
+}
+
+impl AccountTrie {
+    /// Sets the RLP encoded account of the address.
+    pub(super) fn insert(&mut self, address: &Address, value: Vec<u8>) {
+        insert(&mut self.root, &nibbles(address), value);
+    }
+
+    /// Removes the account of the address.
+    pub(super) fn remove(&mut self, address: &Address) {
+        remove(&mut self.root, &nibbles(address));
+    }
+
+    /// Hashes the nodes that were changed since the last rehash.
+    pub(super) fn rehash(&mut self) {
+        if let Some(root) = &mut self.root {
+            root.rehash();
+        }
+    }
+
+    /// Returns the root hash, the trie has to be rehashed after the last change.
+    pub(super) fn root(&self) -> B256 {
+        let Some(root) = &self.root else {
+            return super::EMPTY_ROOT_HASH;
+        };
+        let reference = root
+            .reference
+            .as_deref()
+            .expect("trie is rehashed after the last change");
+        // Root is hashed even if its encoding is shorter than the hash.
+        if reference.len() == B256::len_bytes() + 1 {
+            B256::from_slice(&reference[1..])
+        } else {
+            keccak256(reference)
+        }
+    }
+}
+
+/// Returns the nibbles of the hashed address.
+fn nibbles(address: &Address) -> Vec<u8> {
+    keccak256(address)
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn leaf(path: &[u8], value: Vec<u8>) -> Box<TrieNode> {
+    TrieNode::new(Node::Leaf {
+        path: path.to_vec(),
+        value,
+    })
+}
+
+/// Returns the extension to the child, or the child itself if the path is empty.
+fn extension(path: &[u8], child: Box<TrieNode>) -> Box<TrieNode> {
+    if path.is_empty() {
+        return child;
+    }
+    TrieNode::new(Node::Extension {
+        path: path.to_vec(),
+        child,
+    })
+}
+
+fn branch(children: [Option<Box<TrieNode>>; 16]) -> Box<TrieNode> {
+    TrieNode::new(Node::Branch { children })
+}
+
+/// Inserts the value to the subtrie, returns true if the subtrie was changed.
+fn insert(slot: &mut Option<Box<TrieNode>>, path: &[u8], value: Vec<u8>) -> bool {
+    match slot {
+        Some(node) => insert_node(node, path, value),
+        None => {
+            *slot = Some(leaf(path, value));
+            true
+        }
+    }
+}
+
+fn insert_node(node: &mut Box<TrieNode>, path: &[u8], value: Vec<u8>) -> bool {
+    let changed = match &mut node.node {
+        Node::Leaf {
+            path: leaf_path,
+            value: leaf_value,
+        } => {
+            if leaf_path.as_slice() == path {
+                if *leaf_value == value {
+                    return false;
+                }
+                *leaf_value = value;
+                true
+            } else {
+                let common = common_prefix(leaf_path, path);
+                let mut children: [Option<Box<TrieNode>>; 16] = Default::default();
+                children[leaf_path[common] as usize] =
+                    Some(leaf(&leaf_path[common + 1..], mem::take(leaf_value)));
+                children[path[common] as usize] = Some(leaf(&path[common + 1..], value));
+                *node = extension(&path[..common], branch(children));
+                return true;
+            }
+        }
+        Node::Extension {
+            path: extension_path,
+            child,
+        } => {
+            if let Some(rest) = path.strip_prefix(extension_path.as_slice()) {
+                insert_node(child, rest, value)
+            } else {
+                let common = common_prefix(extension_path, path);
+                let placeholder = Node::Branch {
+                    children: Default::default(),
+                };
+                let Node::Extension {
+                    path: extension_path,
+                    child,
+                } = mem::replace(&mut node.node, placeholder)
+                else {
+                    unreachable!()
+                };
+                let mut children: [Option<Box<TrieNode>>; 16] = Default::default();
+                children[extension_path[common] as usize] =
+                    Some(extension(&extension_path[common + 1..], child));
+                children[path[common] as usize] = Some(leaf(&path[common + 1..], value));
+                *node = extension(&path[..common], branch(children));
+                return true;
+            }
+        }
+        Node::Branch { children } => insert(&mut children[path[0] as usize], &path[1..], value),
+    };
+    if changed {
+        node.reference = None;
+    }
+    changed
+}
+
+/// Removes the value from the subtrie, returns true if the subtrie was changed.
+fn remove(slot: &mut Option<Box<TrieNode>>, path: &[u8]) -> bool {
+    let Some(node) = slot.take() else {
+        return false;
+    };
+    let (node, changed) = remove_node(node, path);
+    *slot = node;
+    changed
+}
+
+fn remove_node(mut node: Box<TrieNode>, path: &[u8]) -> (Option<Box<TrieNode>>, bool) {
+    match &mut node.node {
+        Node::Leaf {
+            path: leaf_path, ..
+        } => {
+            if leaf_path.as_slice() == path {
+                return (None, true);
+            }
+        }
+        Node::Extension {
+            path: extension_path,
+            child,
+        } => {
+            if let Some(rest) = path.strip_prefix(extension_path.as_slice()) {
+                let placeholder = leaf(&[], Vec::new());
+                let (new_child, changed) = remove_node(mem::replace(child, placeholder), rest);
+                let Some(new_child) = new_child else {
+                    return (None, true);
+                };
+                if changed {
+                    return (Some(join(mem::take(extension_path), new_child)), true);
+                }
+                *child = new_child;
+            }
+        }
+        Node::Branch { children } => {
+            if remove(&mut children[path[0] as usize], &path[1..]) {
+                let mut present = children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, child)| child.is_some())
+                    .map(|(index, _)| index);
+                let node = match (present.next(), present.next()) {
+                    (Some(index), None) => {
+                        let child = children[index].take().expect("child is present");
+                        join(vec![index as u8], child)
+                    }
+                    _ => {
+                        node.reference = None;
+                        node
+                    }
+                };
+                return (Some(node), true);
+            }
+        }
+    }
+    (Some(node), false)
+}
+
+/// Prepends the path to the node, merging it with the path of the leaf or the extension.
+fn join(mut path: Vec<u8>, node: Box<TrieNode>) -> Box<TrieNode> {
+    match node.node {
+        Node::Leaf { path: rest, value } => {
+            path.extend(rest);
+            TrieNode::new(Node::Leaf { path, value })
+        }
+        Node::Extension { path: rest, child } => {
+            path.extend(rest);
+            TrieNode::new(Node::Extension { path, child })
+        }
+        Node::Branch { .. } => extension(&path, node),
+    }
+}
+
+impl TrieNode {
+    fn new(node: Node) -> Box<Self> {
+        Box::new(Self {
+            node,
+            reference: None,
+        })
+    }
+
+    /// Computes the references of the changed nodes and returns the reference of this node.
+    fn rehash(&mut self) -> &[u8] {
+        if self.reference.is_none() {
+            let mut out = Vec::new();
+            match &mut self.node {
+                Node::Leaf { path, value } => {
+                    let path = encode_path(path, true);
+                    let value: &[u8] = value;
+                    encode_list(&[&&*path, &value], &mut out);
+                }
+                Node::Extension { path, child } => {
+                    let path = encode_path(path, false);
+                    encode_list(&[&&*path, &Raw(child.rehash())], &mut out);
+                }
+                Node::Branch { children } => {
+                    let empty = Raw(&[EMPTY_STRING_CODE]);
+                    let references: Vec<_> = children
+                        .iter_mut()
+                        .map(|child| {
+                            child
+                                .as_mut()
+                                .map_or(Raw(&[EMPTY_STRING_CODE]), |child| Raw(child.rehash()))
+                        })
+                        .collect();
+                    let mut fields: Vec<&dyn Encodable> =
+                        references.iter().map(|reference| reference as _).collect();
+                    // Value of the branch.
+                    fields.push(&empty);
+                    encode_list(&fields, &mut out);
+                }
+            }
+            // Nodes shorter than the hash are inlined into their parent.
+            if out.len() >= B256::len_bytes() {
+                let hash = keccak256(&out);
+                out.clear();
+                hash.encode(&mut out);
+            }
+            self.reference = Some(out);
+        }
+        self.reference.as_deref().expect("reference is computed")
+    }
+}
+
+/// Already encoded RLP item.
+struct Raw<'a>(&'a [u8]);
+
+impl Encodable for Raw<'_> {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        out.put_slice(self.0);
+    }
+
+    fn length(&self) -> usize {
+        self.0.len()
+    }
+}
+
+fn encode_list(fields: &[&dyn Encodable], out: &mut Vec<u8>) {
+    Header {
+        list: true,
+        payload_length: fields.iter().map(|field| field.length()).sum(),
+    }
+    .encode(out);
+    for field in fields {
+        field.encode(out);
+    }
+}
+
+/// Hex prefix encoding of the path of the leaf or the extension.
+fn encode_path(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let mut out = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        out.push(flag | 0x10 | path[0]);
+        &path[1..]
+    } else {
+        out.push(flag);
+        path
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::states::trie_root, primitives::HashMap};
+
+    #[test]
+    fn matches_trie_root() {
+        let mut trie = AccountTrie::default();
+        let mut accounts = HashMap::new();
+        let address = |i: u64| Address::left_padding_from(&i.to_be_bytes());
+        // Short values make nodes that are inlined into their parents.
+        let value = |i: u64, round: u64| vec![0x42; (i * 7 + round) as usize % 40 + 1];
+
+        for round in 0..4 {
+            for i in 0..300 {
+                if (i + round) % 3 == 0 {
+                    trie.remove(&address(i));
+                    accounts.remove(&address(i));
+                } else if (i + round) % 5 != 0 {
+                    trie.insert(&address(i), value(i, round));
+                    accounts.insert(address(i), value(i, round));
+                }
+            }
+            trie.rehash();
+            assert_eq!(trie.root(), trie_root(accounts.iter()));
+        }
+
+        for i in 0..300 {
+            trie.remove(&address(i));
+        }
+        assert_eq!(trie, AccountTrie::default());
+        assert_eq!(trie.root(), crate::db::states::EMPTY_ROOT_HASH);
+
+        // Single leaf is the root.
+        trie.insert(&address(1), vec![1]);
+        trie.rehash();
+        assert_eq!(trie.root(), trie_root([(address(1), [1])]));
+    }
+}
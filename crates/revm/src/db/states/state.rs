@@ -1,8 +1,9 @@
 use super::{
-    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
-    CacheAccount, StateBuilder, TransitionAccount, TransitionState,
+    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, AccountStatus,
+    BundleState, CacheAccount, PlainAccount, StateBuilder, StorageSlot, StorageWithOriginalValues,
+    TransitionAccount, TransitionState,
 };
-use crate::db::EmptyDB;
+use crate::db::{DatabaseSnapshot, EmptyDB, SnapshotId};
 use revm_interpreter::primitives::{
     db::{Database, DatabaseCommit},
     hash_map, Account, AccountInfo, Address, Bytecode, HashMap, B256, BLOCK_HASH_HISTORY, U256,
@@ -56,6 +57,118 @@ pub struct State<DB> {
     /// This map can be used to give different values for block hashes if in case
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// Changes committed since each snapshot, see [DatabaseSnapshot].
+    pub(crate) snapshots: Vec<StateSnapshot>,
+    /// Identifier of the next snapshot.
+    pub(crate) next_snapshot_id: SnapshotId,
+}
+
+/// Values of the cached accounts and transitions before the changes committed after a
+/// snapshot.
+#[derive(Clone, Debug)]
+pub(crate) struct StateSnapshot {
+    /// Identifier of the snapshot.
+    id: SnapshotId,
+    /// Accounts changed since the snapshot.
+    accounts: HashMap<Address, AccountSnapshot>,
+}
+
+#[derive(Clone, Debug)]
+struct AccountSnapshot {
+    /// Cached account before its first change, `None` if the account was not cached.
+    cache: Option<CacheAccountSnapshot>,
+    /// Transition before its first change, `None` if there was no transition.
+    transition: Option<TransitionSnapshot>,
+}
+
+#[derive(Clone, Debug)]
+struct CacheAccountSnapshot {
+    status: AccountStatus,
+    info: Option<AccountInfo>,
+    /// Storage slots before their first change, `None` if the slot was not cached.
+    storage: HashMap<U256, Option<U256>>,
+    /// Storage before it was cleared, slot changes after it are not recorded.
+    cleared_storage: Option<PlainStorage>,
+}
+
+#[derive(Clone, Debug)]
+struct TransitionSnapshot {
+    info: Option<AccountInfo>,
+    status: AccountStatus,
+    previous_info: Option<AccountInfo>,
+    previous_status: AccountStatus,
+    storage_was_destroyed: bool,
+    /// Storage slots before their first change, `None` if the slot was not in the transition.
+    storage: HashMap<U256, Option<StorageSlot>>,
+    /// Storage before it was replaced, slot changes after it are not recorded.
+    cleared_storage: Option<StorageWithOriginalValues>,
+}
+
+impl AccountSnapshot {
+    fn new(cache: Option<&CacheAccount>, transition: Option<&TransitionAccount>) -> Self {
+        Self {
+            cache: cache.map(|account| CacheAccountSnapshot {
+                status: account.status,
+                info: account.account.as_ref().map(|account| account.info.clone()),
+                storage: HashMap::new(),
+                cleared_storage: None,
+            }),
+            transition: transition.map(|transition| TransitionSnapshot {
+                info: transition.info.clone(),
+                status: transition.status,
+                previous_info: transition.previous_info.clone(),
+                previous_status: transition.previous_status,
+                storage_was_destroyed: transition.storage_was_destroyed,
+                storage: HashMap::new(),
+                cleared_storage: None,
+            }),
+        }
+    }
+
+    /// Records the storage slots before they are changed, or the whole storage if it is
+    /// cleared or replaced by the change.
+    fn record_storage(
+        &mut self,
+        slots: &[U256],
+        clears_storage: bool,
+        cache: Option<&CacheAccount>,
+        transition: Option<&TransitionAccount>,
+    ) {
+        if let (Some(snapshot), Some(storage)) = (
+            self.cache.as_mut().filter(|s| s.cleared_storage.is_none()),
+            cache
+                .and_then(|account| account.account.as_ref())
+                .map(|a| &a.storage),
+        ) {
+            if clears_storage {
+                snapshot.cleared_storage = Some(storage.clone());
+            } else {
+                for slot in slots {
+                    snapshot
+                        .storage
+                        .entry(*slot)
+                        .or_insert_with(|| storage.get(slot).copied());
+                }
+            }
+        }
+        if let (Some(snapshot), Some(transition)) = (
+            self.transition
+                .as_mut()
+                .filter(|s| s.cleared_storage.is_none()),
+            transition,
+        ) {
+            if clears_storage {
+                snapshot.cleared_storage = Some(transition.storage.clone());
+            } else {
+                for slot in slots {
+                    snapshot
+                        .storage
+                        .entry(*slot)
+                        .or_insert_with(|| transition.storage.get(slot).cloned());
+                }
+            }
+        }
+    }
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -91,6 +204,7 @@ impl<DB: Database> State<DB> {
             if balance == 0 {
                 continue;
             }
+            self.record_snapshot(address, &[], false);
             let original_account = self.load_cache_account(address)?;
             transitions.push((
                 address,
@@ -117,6 +231,7 @@ impl<DB: Database> State<DB> {
         let mut transitions = Vec::new();
         let mut balances = Vec::new();
         for address in addresses {
+            self.record_snapshot(address, &[], false);
             let original_account = self.load_cache_account(address)?;
             let (balance, transition) = original_account.drain_balance();
             balances.push(balance);
@@ -164,13 +279,36 @@ impl<DB: Database> State<DB> {
     /// This action will create final post state and all reverts so that
     /// we at any time revert state of bundle to the state before transition
     /// is applied.
+    ///
+    /// Snapshots can't revert merged transitions and are discarded.
     pub fn merge_transitions(&mut self, retention: BundleRetention) {
+        self.snapshots.clear();
         if let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take) {
             self.bundle_state
                 .apply_transitions_and_create_reverts(transition_state, retention);
         }
     }
 
+    /// Records the fields of the cached account and its transition before their first change
+    /// since the last snapshot, together with the storage slots that are going to change.
+    ///
+    /// Whole storage is recorded only if the change clears it.
+    fn record_snapshot(&mut self, address: Address, slots: &[U256], clears_storage: bool) {
+        let Some(snapshot) = self.snapshots.last_mut() else {
+            return;
+        };
+        let cache = self.cache.accounts.get(&address);
+        let transition = self
+            .transition_state
+            .as_ref()
+            .and_then(|state| state.transitions.get(&address));
+        snapshot
+            .accounts
+            .entry(address)
+            .or_insert_with(|| AccountSnapshot::new(cache, transition))
+            .record_storage(slots, clears_storage, cache, transition);
+    }
+
     pub fn load_cache_account(&mut self, address: Address) -> Result<&mut CacheAccount, DB::Error> {
         match self.cache.accounts.entry(address) {
             hash_map::Entry::Vacant(entry) => {
@@ -294,11 +432,100 @@ impl<DB: Database> Database for State<DB> {
 
 impl<DB: Database> DatabaseCommit for State<DB> {
     fn commit(&mut self, evm_state: HashMap<Address, Account>) {
+        if !self.snapshots.is_empty() {
+            for (address, account) in evm_state.iter().filter(|(_, account)| account.is_touched()) {
+                let slots: Vec<U256> = account
+                    .changed_storage_slots()
+                    .map(|(slot, _)| *slot)
+                    .collect();
+                // Selfdestructed and created accounts have their storage replaced, touched
+                // empty accounts are removed.
+                let clears_storage =
+                    account.is_selfdestructed() || account.is_created() || account.is_empty();
+                self.record_snapshot(*address, &slots, clears_storage);
+            }
+        }
         let transitions = self.cache.apply_evm_state(evm_state);
         self.apply_transition(transitions);
     }
 }
 
+/// Snapshots record the previous fields of the changed accounts and their transitions, and
+/// the previous values of the changed storage slots. Snapshots are discarded when transitions
+/// are merged with [State::merge_transitions].
+impl<DB> DatabaseSnapshot for State<DB> {
+    fn snapshot(&mut self) -> SnapshotId {
+        let id = self.next_snapshot_id;
+        self.next_snapshot_id += 1;
+        self.snapshots.push(StateSnapshot {
+            id,
+            accounts: HashMap::new(),
+        });
+        id
+    }
+
+    fn revert_to_snapshot(&mut self, id: SnapshotId) -> bool {
+        let Some(index) = self.snapshots.iter().position(|snapshot| snapshot.id == id) else {
+            return false;
+        };
+        for snapshot in self.snapshots.drain(index..).rev() {
+            for (address, account) in snapshot.accounts {
+                match account.cache {
+                    Some(cache) => {
+                        let account = self
+                            .cache
+                            .accounts
+                            .entry(address)
+                            .or_insert_with(CacheAccount::new_loaded_not_existing);
+                        let storage = account.account.take().map(|account| account.storage);
+                        account.status = cache.status;
+                        account.account = cache.info.map(|info| {
+                            let mut storage = cache.cleared_storage.or(storage).unwrap_or_default();
+                            for (slot, value) in cache.storage {
+                                match value {
+                                    Some(value) => storage.insert(slot, value),
+                                    None => storage.remove(&slot),
+                                };
+                            }
+                            PlainAccount { info, storage }
+                        });
+                    }
+                    None => {
+                        self.cache.accounts.remove(&address);
+                    }
+                }
+
+                let Some(transition_state) = self.transition_state.as_mut() else {
+                    continue;
+                };
+                let Some(snapshot) = account.transition else {
+                    transition_state.transitions.remove(&address);
+                    continue;
+                };
+                let transition = transition_state.transitions.entry(address).or_default();
+                let mut storage = snapshot
+                    .cleared_storage
+                    .unwrap_or_else(|| core::mem::take(&mut transition.storage));
+                for (slot, value) in snapshot.storage {
+                    match value {
+                        Some(value) => storage.insert(slot, value),
+                        None => storage.remove(&slot),
+                    };
+                }
+                *transition = TransitionAccount {
+                    info: snapshot.info,
+                    status: snapshot.status,
+                    previous_info: snapshot.previous_info,
+                    previous_status: snapshot.previous_status,
+                    storage,
+                    storage_was_destroyed: snapshot.storage_was_destroyed,
+                };
+            }
+        }
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
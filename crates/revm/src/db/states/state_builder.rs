@@ -4,7 +4,7 @@ use revm_interpreter::primitives::{
     db::{Database, DatabaseRef, WrapDatabaseRef},
     B256,
 };
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, vec::Vec};
 
 /// Allows building of State and initializing it with different options.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -166,6 +166,8 @@ impl<DB: Database> StateBuilder<DB> {
             bundle_state: self.with_bundle_prestate.unwrap_or_default(),
             use_preloaded_bundle,
             block_hashes: self.with_block_hashes,
+            snapshots: Vec::new(),
+            next_snapshot_id: 0,
         }
     }
 }
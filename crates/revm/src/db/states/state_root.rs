@@ -0,0 +1,318 @@
+//! Merkle Patricia Trie root computation of the state.
+
+use super::{
+    account_trie::AccountTrie, plain_account::PlainStorage, BundleState, CacheState, PlainAccount,
+};
+use crate::primitives::{b256, keccak256, AccountInfo, Address, HashMap, Log, B256, U256};
+use alloy_rlp::{RlpEncodable, RlpMaxEncodedLen};
+use hash_db::Hasher;
+use plain_hasher::PlainHasher;
+use std::vec::Vec;
+use triehash::{ordered_trie_root as ordered_root, sec_trie_root};
+
+/// Root hash of an empty trie.
+pub const EMPTY_ROOT_HASH: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// Returns the hash of the RLP encoded logs.
+pub fn log_rlp_hash(logs: &[Log]) -> B256 {
+    let mut out = Vec::with_capacity(alloy_rlp::list_length(logs));
+    alloy_rlp::encode_list(logs, &mut out);
+    keccak256(&out)
+}
+
+/// Computes the state root of the given accounts from scratch.
+pub fn state_merkle_trie_root<'a>(
+    accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
+) -> B256 {
+    trie_root(accounts.into_iter().map(|(address, acc)| {
+        (
+            address,
+            alloy_rlp::encode_fixed_size(&TrieAccount::new(
+                &acc.info,
+                storage_root(acc.storage.iter().map(|(k, v)| (*k, *v))),
+            )),
+        )
+    }))
+}
+
+/// Computes the storage root of the given storage slots.
+///
+/// Slots with zero value are skipped.
+pub fn storage_root(storage: impl IntoIterator<Item = (U256, U256)>) -> B256 {
+    trie_root(
+        storage
+            .into_iter()
+            .filter(|(_, v)| *v != U256::ZERO)
+            .map(|(k, v)| (k.to_be_bytes::<32>(), alloy_rlp::encode_fixed_size(&v))),
+    )
+}
+
+/// Computes the root of the secure trie, where keys are hashed with keccak256.
+#[inline]
+pub fn trie_root<I, A, B>(input: I) -> B256
+where
+    I: IntoIterator<Item = (A, B)>,
+    A: AsRef<[u8]>,
+    B: AsRef<[u8]>,
+{
+    sec_trie_root::<KeccakHasher, _, _, _>(input)
+}
+
+/// Computes the root of the trie keyed by the RLP encoded index of the item,
+/// as used for transactions, receipts and withdrawals roots.
+#[inline]
+pub fn ordered_trie_root<I>(input: I) -> B256
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    ordered_root::<KeccakHasher, _>(input)
+}
+
+/// Keccak256 hasher used by the trie.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeccakHasher;
+
+impl Hasher for KeccakHasher {
+    type Out = B256;
+    type StdHasher = PlainHasher;
+    const LENGTH: usize = 32;
+
+    #[inline]
+    fn hash(x: &[u8]) -> Self::Out {
+        keccak256(x)
+    }
+}
+
+/// Account as it is stored in the state trie.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpMaxEncodedLen)]
+struct TrieAccount {
+    nonce: u64,
+    balance: U256,
+    root_hash: B256,
+    code_hash: B256,
+}
+
+impl TrieAccount {
+    fn new(info: &AccountInfo, root_hash: B256) -> Self {
+        Self {
+            nonce: info.nonce,
+            balance: info.balance,
+            root_hash,
+            code_hash: info.code_hash,
+        }
+    }
+}
+
+/// Account of the [`TrieState`] with its storage and cached storage root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TrieStateAccount {
+    account: TrieAccount,
+    storage: PlainStorage,
+}
+
+impl TrieStateAccount {
+    fn new(info: &AccountInfo, storage: PlainStorage) -> Self {
+        let root_hash = storage_root(storage.iter().map(|(k, v)| (*k, *v)));
+        Self {
+            account: TrieAccount::new(info, root_hash),
+            storage,
+        }
+    }
+}
+
+/// State used to compute the state root incrementally.
+///
+/// Holds the plain state together with the storage roots of all accounts and the account
+/// trie with the hashes of its nodes. When a [`BundleState`] is applied, only storage roots of
+/// touched accounts with changed storage are recomputed and only the account trie nodes on
+/// the paths of the changed accounts are rehashed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrieState {
+    accounts: HashMap<Address, TrieStateAccount>,
+    trie: AccountTrie,
+}
+
+impl TrieState {
+    /// Creates new trie state from the given pre-state accounts.
+    pub fn new(accounts: impl IntoIterator<Item = (Address, PlainAccount)>) -> Self {
+        let accounts: HashMap<_, _> = accounts
+            .into_iter()
+            .map(|(address, acc)| (address, TrieStateAccount::new(&acc.info, acc.storage)))
+            .collect();
+        let mut trie = AccountTrie::default();
+        for (address, account) in &accounts {
+            trie.insert(address, alloy_rlp::encode(&account.account));
+        }
+        trie.rehash();
+        Self { accounts, trie }
+    }
+
+    /// Creates new trie state from accounts that exist in the cache state.
+    pub fn from_cache_state(cache: &CacheState) -> Self {
+        Self::new(
+            cache
+                .trie_account()
+                .into_iter()
+                .map(|(address, acc)| (address, acc.clone())),
+        )
+    }
+
+    /// Applies the changes of the bundle on top of the state.
+    ///
+    /// Bundle is expected to be created on top of this state.
+    pub fn apply_bundle(&mut self, bundle: &BundleState) {
+        for (address, bundle_account) in bundle.state() {
+            let Some(info) = &bundle_account.info else {
+                self.accounts.remove(address);
+                self.trie.remove(address);
+                continue;
+            };
+
+            let account = self
+                .accounts
+                .entry(*address)
+                .or_insert_with(|| TrieStateAccount::new(info, PlainStorage::default()));
+
+            let mut storage_changed = false;
+            if bundle_account.was_destroyed() && !account.storage.is_empty() {
+                account.storage.clear();
+                storage_changed = true;
+            }
+            for (slot, value) in &bundle_account.storage {
+                let present_value = value.present_value;
+                let current_value = account.storage.get(slot).copied().unwrap_or_default();
+                if present_value == current_value {
+                    continue;
+                }
+                if present_value == U256::ZERO {
+                    account.storage.remove(slot);
+                } else {
+                    account.storage.insert(*slot, present_value);
+                }
+                storage_changed = true;
+            }
+
+            let root_hash = if storage_changed {
+                storage_root(account.storage.iter().map(|(k, v)| (*k, *v)))
+            } else {
+                account.account.root_hash
+            };
+            account.account = TrieAccount::new(info, root_hash);
+            self.trie
+                .insert(address, alloy_rlp::encode(&account.account));
+        }
+        self.trie.rehash();
+    }
+
+    /// Returns the storage root of the account, `None` if account does not exist.
+    pub fn storage_root(&self, address: &Address) -> Option<B256> {
+        self.accounts
+            .get(address)
+            .map(|account| account.account.root_hash)
+    }
+
+    /// Returns the state root.
+    ///
+    /// Root is kept up to date by [`TrieState::apply_bundle`], nothing is rehashed.
+    pub fn state_root(&self) -> B256 {
+        self.trie.root()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{states::bundle_state::BundleRetention, EmptyDB},
+        primitives::{address, Account, AccountStatus, EvmStorageSlot},
+        DatabaseCommit, State,
+    };
+
+    #[test]
+    fn empty_roots() {
+        assert_eq!(storage_root([]), EMPTY_ROOT_HASH);
+        assert_eq!(ordered_trie_root::<[&[u8]; 0]>([]), EMPTY_ROOT_HASH);
+        assert_eq!(TrieState::default().state_root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn incremental_state_root() {
+        let address1 = address!("1000000000000000000000000000000000000000");
+        let address2 = address!("2000000000000000000000000000000000000000");
+        let pre_state = [
+            (
+                address1,
+                PlainAccount {
+                    info: AccountInfo::from_balance(U256::from(10)),
+                    storage: [(U256::from(1), U256::from(1))].into_iter().collect(),
+                },
+            ),
+            (
+                address2,
+                PlainAccount::from(AccountInfo::from_balance(U256::from(20))),
+            ),
+        ];
+
+        let mut state = State::builder().with_bundle_update().build();
+        for (address, account) in pre_state.clone() {
+            state.insert_account_with_storage(address, account.info, account.storage);
+        }
+        let mut trie_state = TrieState::new(pre_state);
+        let address2_storage_root = trie_state.storage_root(&address2);
+
+        // change balance and storage of the first account.
+        let mut account = Account::from(AccountInfo::from_balance(U256::from(11)));
+        account.storage.insert(
+            U256::from(1),
+            EvmStorageSlot::new_changed(U256::from(1), U256::ZERO),
+        );
+        account.storage.insert(
+            U256::from(2),
+            EvmStorageSlot::new_changed(U256::ZERO, U256::from(2)),
+        );
+        account.status = AccountStatus::Touched;
+        state.commit([(address1, account)].into_iter().collect());
+        state.merge_transitions(BundleRetention::PlainState);
+
+        trie_state.apply_bundle(&state.take_bundle());
+
+        let expected = state_merkle_trie_root(state.cache.trie_account());
+        assert_eq!(trie_state.state_root(), expected);
+        assert_eq!(
+            trie_state.storage_root(&address1),
+            Some(storage_root([(U256::from(2), U256::from(2))]))
+        );
+        assert_eq!(trie_state.storage_root(&address2), address2_storage_root);
+
+        // cache state is the post state.
+        assert_eq!(
+            TrieState::from_cache_state(&state.cache).state_root(),
+            expected
+        );
+    }
+
+    #[test]
+    fn destroyed_account_is_removed() {
+        let address = address!("1000000000000000000000000000000000000000");
+        let mut state = State::builder()
+            .with_database(EmptyDB::default())
+            .with_bundle_update()
+            .build();
+        state.insert_account(address, AccountInfo::from_balance(U256::from(1)));
+        let mut trie_state = TrieState::new([(
+            address,
+            PlainAccount::from(AccountInfo::from_balance(U256::from(1))),
+        )]);
+
+        let mut account = Account::from(AccountInfo::default());
+        account.status = AccountStatus::Touched | AccountStatus::SelfDestructed;
+        state.commit([(address, account)].into_iter().collect());
+        state.merge_transitions(BundleRetention::PlainState);
+
+        trie_state.apply_bundle(&state.take_bundle());
+        assert_eq!(trie_state.storage_root(&address), None);
+        assert_eq!(trie_state.state_root(), EMPTY_ROOT_HASH);
+    }
+}
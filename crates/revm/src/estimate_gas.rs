@@ -0,0 +1,332 @@
+//! Gas estimation, equivalent of the `eth_estimateGas` RPC method.
+
+use crate::{
+    db::Database,
+    interpreter::gas,
+    primitives::{Bytes, EVMError, ExecutionResult, HaltReason},
+    Evm,
+};
+use core::fmt;
+
+/// Result of the gas estimation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasEstimate {
+    /// Minimal gas limit with which the transaction succeeds.
+    pub gas_limit: u64,
+    /// Result of the transaction executed with the estimated gas limit.
+    pub result: ExecutionResult,
+}
+
+/// Gas estimation error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EstimateGasError<DBError> {
+    /// Transaction ran out of gas with the highest gas limit.
+    OutOfGas {
+        /// Highest gas limit of the search.
+        gas_limit: u64,
+    },
+    /// Transaction reverted with the highest gas limit.
+    Revert {
+        /// Highest gas limit of the search.
+        gas_limit: u64,
+        /// Output of the revert.
+        output: Bytes,
+    },
+    /// Transaction halted with the highest gas limit for a reason other than out of gas.
+    Halt {
+        /// Highest gas limit of the search.
+        gas_limit: u64,
+        /// Reason of the halt.
+        reason: HaltReason,
+    },
+    /// Transaction could not be executed.
+    Evm(EVMError<DBError>),
+}
+
+#[cfg(feature = "std")]
+impl<DBError: std::error::Error + 'static> std::error::Error for EstimateGasError<DBError> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Evm(e) => Some(e),
+            Self::OutOfGas { .. } | Self::Revert { .. } | Self::Halt { .. } => None,
+        }
+    }
+}
+
+impl<DBError: fmt::Display> fmt::Display for EstimateGasError<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfGas { gas_limit } => write!(f, "out of gas with gas limit {gas_limit}"),
+            Self::Revert { gas_limit, output } => {
+                write!(f, "reverted with gas limit {gas_limit}: {output}")
+            }
+            Self::Halt { gas_limit, reason } => {
+                write!(f, "halted with gas limit {gas_limit}: {reason:?}")
+            }
+            Self::Evm(e) => write!(f, "evm error: {e}"),
+        }
+    }
+}
+
+impl<DBError> From<EVMError<DBError>> for EstimateGasError<DBError> {
+    fn from(value: EVMError<DBError>) -> Self {
+        Self::Evm(value)
+    }
+}
+
+impl<EXT, DB: Database> Evm<'_, EXT, DB> {
+    /// Estimates the minimal gas limit needed for the transaction to succeed.
+    ///
+    /// Gas limit is binary searched between the intrinsic gas of the transaction and the
+    /// lower of the block and transaction gas limit. If the transaction does not succeed
+    /// with the highest gas limit, the failure is returned as an error without the search.
+    ///
+    /// State changes are not committed and gas limit of the transaction is restored.
+    pub fn estimate_gas(&mut self) -> Result<GasEstimate, EstimateGasError<DB::Error>> {
+        let gas_limit = self.context.evm.env.tx.gas_limit;
+        let output = self.estimate_gas_inner();
+        self.context.evm.env.tx.gas_limit = gas_limit;
+        output
+    }
+
+    fn estimate_gas_inner(&mut self) -> Result<GasEstimate, EstimateGasError<DB::Error>> {
+        let env = &self.context.evm.env;
+        let initial_gas_spend = gas::validate_initial_tx_gas(
+            self.spec_id(),
+            &env.tx.data,
+            env.tx.transact_to.is_create(),
+            &env.tx.access_list,
+            env.tx
+                .authorization_list
+                .as_ref()
+                .map(|l| l.len() as u64)
+                .unwrap_or_default(),
+        );
+        let mut hi = env
+            .tx
+            .gas_limit
+            .min(env.block.gas_limit.saturating_to::<u64>());
+
+        let mut result = self.transact_with_gas_limit(hi)?;
+        let (gas_used, gas_refunded) = match result {
+            ExecutionResult::Success {
+                gas_used,
+                gas_refunded,
+                ..
+            } => (gas_used, gas_refunded),
+            ExecutionResult::Revert { output, .. } => {
+                return Err(EstimateGasError::Revert {
+                    gas_limit: hi,
+                    output,
+                })
+            }
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(_),
+                ..
+            } => return Err(EstimateGasError::OutOfGas { gas_limit: hi }),
+            ExecutionResult::Halt { reason, .. } => {
+                return Err(EstimateGasError::Halt {
+                    gas_limit: hi,
+                    reason,
+                })
+            }
+        };
+
+        // Execution is deterministic, so any limit below the spent gas fails.
+        let mut lo = (gas_used + gas_refunded).max(initial_gas_spend) - 1;
+
+        // Calls forward at most 63/64 of the available gas, so the spent gas with
+        // some headroom is usually enough.
+        let optimistic_gas_limit = (gas_used + gas_refunded + gas::CALL_STIPEND) * 64 / 63;
+        if optimistic_gas_limit < hi {
+            let optimistic_result = self.transact_with_gas_limit(optimistic_gas_limit)?;
+            if optimistic_result.is_success() {
+                hi = optimistic_gas_limit;
+                result = optimistic_result;
+            } else {
+                lo = optimistic_gas_limit;
+            }
+        }
+
+        while lo + 1 < hi {
+            // Most transactions need gas close to the lower bound.
+            let mid = ((lo + hi) / 2).min(lo.saturating_mul(2));
+            let mid_result = self.transact_with_gas_limit(mid)?;
+            match mid_result {
+                ExecutionResult::Success { .. } => {
+                    hi = mid;
+                    result = mid_result;
+                }
+                ExecutionResult::Halt {
+                    reason: HaltReason::OutOfGas(_),
+                    ..
+                } => lo = mid,
+                // Transaction succeeded with more gas, so the revert or halt is caused
+                // by the lack of gas, e.g. a gas check in the contract or a failed subcall.
+                ExecutionResult::Revert { .. } | ExecutionResult::Halt { .. } => lo = mid,
+            }
+        }
+
+        Ok(GasEstimate {
+            gas_limit: hi,
+            result,
+        })
+    }
+
+    /// Executes the transaction with the given gas limit without committing the state.
+    fn transact_with_gas_limit(
+        &mut self,
+        gas_limit: u64,
+    ) -> Result<ExecutionResult, EVMError<DB::Error>> {
+        self.context.evm.env.tx.gas_limit = gas_limit;
+        self.transact()
+            .map(|result_and_state| result_and_state.result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        interpreter::opcode,
+        primitives::{address, AccountInfo, Address, Bytecode, TransactTo, U256},
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+    const CONTRACT: Address = address!("2000000000000000000000000000000000000000");
+    const CALLEE: Address = address!("3000000000000000000000000000000000000000");
+
+    fn evm(db: CacheDB<EmptyDB>) -> Evm<'static, (), CacheDB<EmptyDB>> {
+        Evm::builder()
+            .with_db(db)
+            .modify_block_env(|block| block.gas_limit = U256::from(30_000_000))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(CONTRACT);
+            })
+            .build()
+    }
+
+    fn insert_code(db: &mut CacheDB<EmptyDB>, address: Address, code: &[u8]) {
+        let code = Bytecode::new_raw(code.to_vec().into());
+        db.insert_account_info(
+            address,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+    }
+
+    #[test]
+    fn estimate_sstore() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        // SSTORE(0, 1)
+        insert_code(
+            &mut db,
+            CONTRACT,
+            &[opcode::PUSH1, 1, opcode::PUSH1, 0, opcode::SSTORE],
+        );
+
+        let mut evm = evm(db);
+        let estimate = evm.estimate_gas().unwrap();
+        assert_eq!(estimate.gas_limit, 21_000 + 3 + 3 + 22_100);
+        assert_eq!(evm.tx().gas_limit, u64::MAX);
+        assert!(estimate.result.is_success());
+        assert_eq!(estimate.result.gas_used(), estimate.gas_limit);
+    }
+
+    #[test]
+    fn estimate_nested_call() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        // SSTORE(0, 1)
+        insert_code(
+            &mut db,
+            CALLEE,
+            &[opcode::PUSH1, 1, opcode::PUSH1, 0, opcode::SSTORE],
+        );
+        // REVERT if CALL(GAS, CALLEE, 0, 0, 0, 0, 0) fails
+        let mut code = vec![opcode::PUSH0, opcode::PUSH0, opcode::PUSH0, opcode::PUSH0];
+        code.push(opcode::PUSH0);
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend_from_slice(&[
+            opcode::GAS,
+            opcode::CALL,
+            opcode::PUSH1,
+            34,
+            opcode::JUMPI,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::REVERT,
+        ]);
+        assert_eq!(code.len(), 34);
+        code.push(opcode::JUMPDEST);
+        insert_code(&mut db, CONTRACT, &code);
+
+        let mut evm = evm(db);
+        let estimate = evm.estimate_gas().unwrap();
+        assert!(estimate.result.is_success());
+        // 1/64 of the gas is retained by the caller.
+        assert!(estimate.gas_limit > estimate.result.gas_used());
+
+        // one gas less is not enough.
+        let result = evm.transact_with_gas_limit(estimate.gas_limit - 1).unwrap();
+        assert!(matches!(result, ExecutionResult::Revert { .. }));
+    }
+
+    #[test]
+    fn estimate_revert() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        insert_code(
+            &mut db,
+            CONTRACT,
+            &[opcode::PUSH0, opcode::PUSH0, opcode::REVERT],
+        );
+
+        assert_eq!(
+            evm(db).estimate_gas(),
+            Err(EstimateGasError::Revert {
+                gas_limit: 30_000_000,
+                output: Bytes::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn estimate_out_of_gas() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        // Infinite loop.
+        insert_code(
+            &mut db,
+            CONTRACT,
+            &[opcode::JUMPDEST, opcode::PUSH0, opcode::JUMP],
+        );
+        let mut evm = evm(db);
+        assert_eq!(
+            evm.estimate_gas(),
+            Err(EstimateGasError::OutOfGas {
+                gas_limit: 30_000_000
+            })
+        );
+
+        // Lower transaction gas limit is the cap.
+        evm.tx_mut().gas_limit = 100_000;
+        assert_eq!(
+            evm.estimate_gas(),
+            Err(EstimateGasError::OutOfGas { gas_limit: 100_000 })
+        );
+        assert_eq!(evm.tx().gas_limit, 100_000);
+    }
+
+    #[test]
+    fn estimate_halt() {
+        let mut db = CacheDB::new(EmptyDB::default());
+        insert_code(&mut db, CONTRACT, &[opcode::INVALID]);
+        assert_eq!(
+            evm(db).estimate_gas(),
+            Err(EstimateGasError::Halt {
+                gas_limit: 30_000_000,
+                reason: HaltReason::InvalidFEOpcode,
+            })
+        );
+    }
+}
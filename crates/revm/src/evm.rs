@@ -1,11 +1,12 @@
 use crate::{
     builder::{EvmBuilder, HandlerStage, SetGenericStage},
-    db::{Database, DatabaseCommit, EmptyDB},
+    db::{Database, DatabaseCommit, DatabaseSnapshot, EmptyDB, SnapshotId},
     handler::Handler,
-    interpreter::{Host, InterpreterAction, SharedMemory},
+    interpreter::{Host, InstructionResult, InterpreterAction, SharedMemory},
     primitives::{
-        specification::SpecId, BlockEnv, CfgEnv, EVMError, EVMResult, EnvWithHandlerCfg,
-        ExecutionResult, HandlerCfg, ResultAndState, TransactTo, TxEnv,
+        specification::SpecId, Address, BlockEnv, Bytes, CfgEnv, EVMError, EVMResult,
+        EnvWithHandlerCfg, ExecutionResult, HandlerCfg, ResultAndState, TransactTo, TxEnv,
+        SYSTEM_ADDRESS, SYSTEM_CALL_GAS_LIMIT, U256,
     },
     Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult,
 };
@@ -46,6 +47,56 @@ impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
         self.context.evm.db.commit(state);
         Ok(result)
     }
+
+    /// Execute the system call and commit the changes to the database.
+    ///
+    /// See [`Evm::transact_system_call`].
+    pub fn transact_system_call_commit(
+        &mut self,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> Result<ExecutionResult, EVMError<DB::Error>> {
+        let ResultAndState { result, state } =
+            self.transact_system_call(system_contract_address, data)?;
+        self.context.evm.db.commit(state);
+        Ok(result)
+    }
+
+    /// Executes the pre-block system calls of the handler and commits the changes to the
+    /// database.
+    ///
+    /// Should be called before the first transaction of the block. Returns the results of
+    /// the calls in order of execution, see [`PreExecutionHandler::pre_block_calls`].
+    ///
+    /// [`PreExecutionHandler::pre_block_calls`]: crate::handler::PreExecutionHandler::pre_block_calls
+    pub fn apply_pre_block_calls(&mut self) -> Result<Vec<ExecutionResult>, EVMError<DB::Error>> {
+        let calls = self
+            .handler
+            .pre_execution()
+            .pre_block_calls(&mut self.context)?;
+        calls
+            .into_iter()
+            .map(|(system_contract_address, data)| {
+                self.transact_system_call_commit(system_contract_address, data)
+            })
+            .collect()
+    }
+}
+
+impl<EXT, DB: Database + DatabaseSnapshot> Evm<'_, EXT, DB> {
+    /// Takes a snapshot of the state committed to the database.
+    ///
+    /// See [DatabaseSnapshot::snapshot].
+    pub fn snapshot(&mut self) -> SnapshotId {
+        self.context.evm.db.snapshot()
+    }
+
+    /// Reverts the changes committed to the database since the snapshot.
+    ///
+    /// See [DatabaseSnapshot::revert_to_snapshot].
+    pub fn revert_to_snapshot(&mut self, id: SnapshotId) -> bool {
+        self.context.evm.db.revert_to_snapshot(id)
+    }
 }
 
 impl<'a> Evm<'a, (), EmptyDB> {
@@ -72,11 +123,21 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
     }
 
     /// Runs main call loop.
+    ///
+    /// Execution can't be suspended in this loop, [EVMError::Suspended] is returned if an
+    /// instruction suspends it, see [Evm::transact_resumable].
     #[inline]
     pub fn run_the_loop(&mut self, first_frame: Frame) -> Result<FrameResult, EVMError<DB::Error>> {
         let mut call_stack: Vec<Frame> = Vec::with_capacity(1025);
         call_stack.push(first_frame);
+        let mut shared_memory = self.new_shared_memory();
+
+        self.run_call_stack(&mut call_stack, &mut shared_memory)?
+            .ok_or(EVMError::Suspended)
+    }
 
+    /// Creates the shared memory of the first frame.
+    pub(crate) fn new_shared_memory(&self) -> SharedMemory {
         #[cfg(feature = "memory_limit")]
         let mut shared_memory =
             SharedMemory::new_with_memory_limit(self.context.evm.env.cfg.memory_limit);
@@ -84,7 +145,18 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
         let mut shared_memory = SharedMemory::new();
 
         shared_memory.new_context();
+        shared_memory
+    }
 
+    /// Runs the call stack until the first frame returns or the execution is suspended.
+    ///
+    /// Returns `None` if the execution was suspended, call stack and shared memory are left
+    /// as they were before the suspended instruction.
+    pub(crate) fn run_call_stack(
+        &mut self,
+        call_stack: &mut Vec<Frame>,
+        shared_memory: &mut SharedMemory,
+    ) -> Result<Option<FrameResult>, EVMError<DB::Error>> {
         // Peek the last stack frame.
         let mut stack_frame = call_stack.last_mut().unwrap();
 
@@ -92,7 +164,7 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
             // Execute the frame.
             let next_action =
                 self.handler
-                    .execute_frame(stack_frame, &mut shared_memory, &mut self.context)?;
+                    .execute_frame(stack_frame, shared_memory, &mut self.context)?;
 
             // Take error and break the loop, if any.
             // This error can be set in the Interpreter when it interacts with the context.
@@ -100,6 +172,11 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
 
             let exec = &mut self.handler.execution;
             let frame_or_result = match next_action {
+                InterpreterAction::Return { result }
+                    if result.result == InstructionResult::Suspend =>
+                {
+                    return Ok(None);
+                }
                 InterpreterAction::Call { inputs } => exec.call(&mut self.context, inputs)?,
                 InterpreterAction::Create { inputs } => exec.create(&mut self.context, inputs)?,
                 InterpreterAction::EOFCreate { inputs } => {
@@ -143,7 +220,7 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
                 FrameOrResult::Result(result) => {
                     let Some(top_frame) = call_stack.last_mut() else {
                         // Break the loop if there are no more frames.
-                        return Ok(result);
+                        return Ok(Some(result));
                     };
                     stack_frame = top_frame;
                     let ctx = &mut self.context;
@@ -151,7 +228,7 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
                     match result {
                         FrameResult::Call(outcome) => {
                             // return_call
-                            exec.insert_call_outcome(ctx, stack_frame, &mut shared_memory, outcome)?
+                            exec.insert_call_outcome(ctx, stack_frame, shared_memory, outcome)?
                         }
                         FrameResult::Create(outcome) => {
                             // return_create
@@ -186,7 +263,7 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     }
 
     /// Calls clear handle of post execution to clear the state for next execution.
-    fn clear(&mut self) {
+    pub(crate) fn clear(&mut self) {
         self.handler.post_execution().clear(&mut self.context);
     }
 
@@ -211,7 +288,7 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
     /// Pre verify transaction inner.
     #[inline]
-    fn preverify_transaction_inner(&mut self) -> Result<u64, EVMError<DB::Error>> {
+    pub(crate) fn preverify_transaction_inner(&mut self) -> Result<u64, EVMError<DB::Error>> {
         self.handler.validation().env(&self.context.evm.env)?;
         let initial_gas_spend = self
             .handler
@@ -239,6 +316,44 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         output
     }
 
+    /// Executes a system call to the given contract.
+    ///
+    /// System calls are block level calls made from [`SYSTEM_ADDRESS`] with
+    /// [`SYSTEM_CALL_GAS_LIMIT`] gas. They skip transaction validation, are not charged
+    /// for gas, do not bump the nonce of the caller and do not reward the beneficiary.
+    ///
+    /// The transaction environment is restored after the call and the system address
+    /// is not part of the returned state.
+    #[inline]
+    pub fn transact_system_call(
+        &mut self,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> EVMResult<DB::Error> {
+        let tx = core::mem::replace(
+            &mut self.context.evm.env.tx,
+            TxEnv {
+                caller: SYSTEM_ADDRESS,
+                gas_limit: SYSTEM_CALL_GAS_LIMIT,
+                gas_price: U256::ZERO,
+                transact_to: TransactTo::Call(system_contract_address),
+                value: U256::ZERO,
+                data,
+                ..Default::default()
+            },
+        );
+
+        let output = self.transact_system_call_inner();
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+        self.context.evm.env.tx = tx;
+
+        output.map(|mut result_and_state| {
+            result_and_state.state.remove(&SYSTEM_ADDRESS);
+            result_and_state
+        })
+    }
+
     /// Returns the reference of handler configuration
     #[inline]
     pub fn handler_cfg(&self) -> &HandlerCfg {
@@ -322,8 +437,47 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         ContextWithHandlerCfg::new(self.context, self.handler.cfg)
     }
 
+    /// Transact system call.
+    fn transact_system_call_inner(&mut self) -> EVMResult<DB::Error> {
+        let ctx = &mut self.context;
+
+        // load precompiles
+        let precompiles = self.handler.pre_execution().load_precompiles();
+        ctx.evm.set_precompiles(precompiles);
+
+        let exec = self.handler.execution();
+        let first_frame_or_result = exec.call(
+            ctx,
+            CallInputs::new_boxed(&ctx.evm.env.tx, SYSTEM_CALL_GAS_LIMIT).unwrap(),
+        )?;
+
+        let mut result = match first_frame_or_result {
+            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame)?,
+            FrameOrResult::Result(result) => result,
+        };
+
+        let ctx = &mut self.context;
+        self.handler
+            .execution()
+            .last_frame_return(ctx, &mut result)?;
+        self.handler.post_execution().output(ctx, result)
+    }
+
     /// Transact pre-verified transaction.
     fn transact_preverified_inner(&mut self, initial_gas_spend: u64) -> EVMResult<DB::Error> {
+        // Starts the main running loop.
+        let result = match self.transact_first_frame(initial_gas_spend)? {
+            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame)?,
+            FrameOrResult::Result(result) => result,
+        };
+        self.transact_output(result)
+    }
+
+    /// Runs pre-execution of the pre-verified transaction and creates its first frame.
+    pub(crate) fn transact_first_frame(
+        &mut self,
+        initial_gas_spend: u64,
+    ) -> Result<FrameOrResult, EVMError<DB::Error>> {
         let ctx = &mut self.context;
         let pre_exec = self.handler.pre_execution();
 
@@ -341,23 +495,20 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
         let exec = self.handler.execution();
         // call inner handling of call/create
-        let first_frame_or_result = match ctx.evm.env.tx.transact_to {
+        match ctx.evm.env.tx.transact_to {
             TransactTo::Call(_) => exec.call(
                 ctx,
                 CallInputs::new_boxed(&ctx.evm.env.tx, gas_limit).unwrap(),
-            )?,
+            ),
             TransactTo::Create => exec.create(
                 ctx,
                 CreateInputs::new_boxed(&ctx.evm.env.tx, gas_limit).unwrap(),
-            )?,
-        };
-
-        // Starts the main running loop.
-        let mut result = match first_frame_or_result {
-            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame)?,
-            FrameOrResult::Result(result) => result,
-        };
+            ),
+        }
+    }
 
+    /// Handles the result of the first frame and returns the output of the transaction.
+    pub(crate) fn transact_output(&mut self, mut result: FrameResult) -> EVMResult<DB::Error> {
         let ctx = &mut self.context;
 
         // handle output of call/create calls.
@@ -374,3 +525,431 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         post_exec.output(ctx, result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB, State},
+        interpreter::{
+            gas,
+            opcode::{PUSH1, SSTORE},
+        },
+        primitives::{
+            address, b256, hex, AccountInfo, AnalysisKind, Authorization, AuthorizationList,
+            Bytecode, ExecutionBudget, HaltReason, InterruptReason, InvalidHeader,
+            RecoveredAuthorization, B256, BEACON_ROOTS_ADDRESS, U256,
+        },
+    };
+    use core::{
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+        time::Duration,
+    };
+    use std::sync::Arc;
+
+    /// [EIP-4788] beacon roots contract runtime code.
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    const BEACON_ROOTS_CODE: &[u8] = &hex!("3373fffffffffffffffffffffffffffffffffffffffe14604d57602036146024575f5ffd5b5f35801560495762001fff810690815414603c575f5ffd5b62001fff01545f5260205ff35b5f5ffd5b62001fff42064281555f359062001fff015500");
+
+    #[test]
+    fn beacon_root_system_call() {
+        let root = b256!("0101010101010101010101010101010101010101010101010101010101010101");
+        let timestamp = 12;
+        let code = Bytecode::new_raw(BEACON_ROOTS_CODE.into());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            BEACON_ROOTS_ADDRESS,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(db)
+            .modify_block_env(|block| {
+                block.number = U256::from(1);
+                block.timestamp = U256::from(timestamp);
+                block.parent_beacon_block_root = Some(root);
+            })
+            .modify_tx_env(|tx| tx.gas_limit = 21_000)
+            .build();
+
+        let results = evm.apply_pre_block_calls().unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_success());
+        // transaction environment is restored.
+        assert_eq!(evm.tx().gas_limit, 21_000);
+
+        let account = evm.db().accounts.get(&BEACON_ROOTS_ADDRESS).unwrap();
+        let timestamp_index = U256::from(timestamp % 8191);
+        assert_eq!(
+            account.storage.get(&timestamp_index),
+            Some(&U256::from(timestamp))
+        );
+        assert_eq!(
+            account.storage.get(&(timestamp_index + U256::from(8191))),
+            Some(&root.into())
+        );
+        assert!(!evm.db().accounts.contains_key(&SYSTEM_ADDRESS));
+    }
+
+    /// Commits transactions that increment the storage slot zero of the contract and reverts
+    /// them with nested snapshots.
+    fn snapshot_and_revert<DB>(db: DB, storage: impl Fn(&DB) -> U256)
+    where
+        DB: Database + DatabaseCommit + DatabaseSnapshot,
+        DB::Error: fmt::Debug,
+    {
+        let contract = address!("0000000000000000000000000000000000001000");
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .build();
+
+        let first = evm.snapshot();
+        evm.transact_commit().unwrap();
+        let second = evm.snapshot();
+        evm.transact_commit().unwrap();
+        evm.transact_commit().unwrap();
+        assert_eq!(storage(evm.db()), U256::from(3));
+
+        assert!(evm.revert_to_snapshot(second));
+        assert_eq!(storage(evm.db()), U256::from(1));
+        // reverted snapshot is discarded and its id is not reused.
+        let third = evm.snapshot();
+        assert_ne!(third, second);
+        assert!(!evm.revert_to_snapshot(second));
+
+        evm.transact_commit().unwrap();
+        assert_eq!(storage(evm.db()), U256::from(2));
+        assert!(evm.revert_to_snapshot(first));
+        assert_eq!(storage(evm.db()), U256::ZERO);
+        assert!(!evm.revert_to_snapshot(first));
+    }
+
+    /// PUSH1 0, SLOAD, PUSH1 1, ADD, PUSH1 0, SSTORE, STOP
+    const COUNTER_CODE: &[u8] = &hex!("600054600101600055 00");
+
+    #[test]
+    fn cache_db_snapshot() {
+        let code = Bytecode::new_raw(COUNTER_CODE.into());
+        let contract = address!("0000000000000000000000000000000000001000");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        snapshot_and_revert(db, |db| db.accounts[&contract].storage[&U256::ZERO]);
+    }
+
+    #[test]
+    fn state_snapshot() {
+        let code = Bytecode::new_raw(COUNTER_CODE.into());
+        let contract = address!("0000000000000000000000000000000000001000");
+        let mut state = State::builder().with_bundle_update().build();
+        state.insert_account(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        snapshot_and_revert(state, |state| {
+            state.cache.accounts[&contract]
+                .storage_slot(U256::ZERO)
+                .unwrap_or_default()
+        });
+    }
+
+    #[test]
+    fn beacon_root_system_call_skipped() {
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_block_env(|block| block.parent_beacon_block_root = Some(B256::ZERO))
+            .build();
+        // genesis block
+        assert_eq!(evm.apply_pre_block_calls(), Ok(Vec::new()));
+
+        evm.block_mut().parent_beacon_block_root = None;
+        assert_eq!(
+            evm.apply_pre_block_calls(),
+            Err(InvalidHeader::ParentBeaconBlockRootNotSet.into())
+        );
+
+        evm.modify_spec_id(SpecId::SHANGHAI);
+        assert_eq!(evm.apply_pre_block_calls(), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn custom_pre_block_calls() {
+        let contract = address!("0000000000000000000000000000000000000100");
+        let code = Bytecode::new_raw([PUSH1, 0x01, PUSH1, 0x00, SSTORE].into());
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        // Beacon root is not set, the mainnet call would fail.
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(db)
+            .modify_block_env(|block| block.number = U256::from(1))
+            .append_handler_register_box(Box::new(move |handler| {
+                handler.pre_execution.pre_block_calls =
+                    Arc::new(move |_| Ok(vec![(contract, Bytes::new())]));
+            }))
+            .build();
+
+        let results = evm.apply_pre_block_calls().unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_success());
+        assert_eq!(
+            evm.db().accounts[&contract].storage.get(&U256::ZERO),
+            Some(&U256::from(1))
+        );
+    }
+
+    #[test]
+    fn sanity_eip7702_tx() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let delegate = address!("0000000000000000000000000000000000000002");
+        let auth = address!("0000000000000000000000000000000000000100");
+
+        let bytecode = Bytecode::new_raw([PUSH1, 0x01, PUSH1, 0x01, SSTORE].into());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            delegate,
+            AccountInfo::new(U256::ZERO, 0, bytecode.hash_slow(), bytecode),
+        );
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::PRAGUE)
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.authorization_list = Some(AuthorizationList::Recovered(vec![
+                    RecoveredAuthorization::new_unchecked(
+                        Authorization {
+                            chain_id: U256::from(1),
+                            address: delegate,
+                            nonce: 0,
+                        },
+                        Some(auth),
+                    ),
+                ]));
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(auth);
+            })
+            .build();
+
+        let ok = evm.transact().unwrap();
+        assert!(ok.result.is_success());
+
+        let auth_acc = ok.state.get(&auth).unwrap();
+        assert_eq!(auth_acc.info.code, Some(Bytecode::new_eip7702(delegate)));
+        assert_eq!(auth_acc.info.nonce, 1);
+        assert_eq!(
+            auth_acc.storage.get(&U256::from(1)).unwrap().present_value,
+            U256::from(1)
+        );
+    }
+
+    #[test]
+    fn eip7702_extcode_follows_delegation() {
+        let caller = address!("0000000000000000000000000000000000000001");
+        let delegate = address!("0000000000000000000000000000000000000300");
+        let auth = address!("0000000000000000000000000000000000000100");
+        let reader = address!("0000000000000000000000000000000000000200");
+
+        let bytecode = Bytecode::new_raw([PUSH1, 0x01, PUSH1, 0x01, SSTORE].into());
+        // sstore(0, extcodesize(auth)), sstore(1, extcodehash(auth)),
+        // extcodecopy(auth, 0, 0, 32), sstore(2, mload(0))
+        let reader_code = [
+            &[0x73][..],
+            auth.as_slice(),
+            &[0x3b, 0x60, 0x00, 0x55],
+            &[0x73],
+            auth.as_slice(),
+            &[0x3f, 0x60, 0x01, 0x55, 0x60, 0x20, 0x60, 0x00, 0x60, 0x00],
+            &[0x73],
+            auth.as_slice(),
+            &[0x3c, 0x60, 0x00, 0x51, 0x60, 0x02, 0x55, 0x00],
+        ]
+        .concat();
+
+        let transact = |auth_code: Bytecode| {
+            let mut db = CacheDB::new(EmptyDB::default());
+            for (address, code) in [
+                (delegate, bytecode.clone()),
+                (auth, auth_code),
+                (reader, Bytecode::new_raw(reader_code.clone().into())),
+            ] {
+                db.insert_account_info(
+                    address,
+                    AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+                );
+            }
+
+            let mut evm = Evm::builder()
+                .with_spec_id(SpecId::PRAGUE)
+                .with_db(db)
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TransactTo::Call(reader);
+                })
+                .build();
+            evm.transact().unwrap()
+        };
+        let ok = transact(Bytecode::new_eip7702(delegate));
+        assert!(ok.result.is_success());
+
+        // The delegated account is charged as a cold access on EXTCODESIZE and as a warm access
+        // on EXTCODEHASH and EXTCODECOPY.
+        let direct = transact(bytecode.clone());
+        assert_eq!(
+            ok.result.gas_used() - direct.result.gas_used(),
+            gas::COLD_ACCOUNT_ACCESS_COST + 2 * gas::WARM_STORAGE_READ_COST
+        );
+
+        let mut copied = [0u8; 32];
+        copied[..bytecode.len()].copy_from_slice(bytecode.original_byte_slice());
+        let storage = &ok.state[&reader].storage;
+        assert_eq!(
+            storage[&U256::from(0)].present_value,
+            U256::from(bytecode.len())
+        );
+        assert_eq!(
+            storage[&U256::from(1)].present_value,
+            U256::from_be_bytes(bytecode.hash_slow().0)
+        );
+        assert_eq!(
+            storage[&U256::from(2)].present_value,
+            U256::from_be_bytes(copied)
+        );
+    }
+
+    fn interrupted_execution(budget: ExecutionBudget) -> ExecutionResult {
+        let contract = address!("0000000000000000000000000000000000000100");
+        let looping = address!("0000000000000000000000000000000000000200");
+        // call(gas, looping, 0, 0, 0, 0, 0), sstore(0, 1), stop
+        let contract_code = [
+            &hex!("60006000600060006000")[..],
+            &[0x73],
+            looping.as_slice(),
+            &hex!("5af150600160005500"),
+        ]
+        .concat();
+        // jumpdest, jump(0)
+        let looping_code = hex!("5b600056");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [(contract, contract_code), (looping, looping_code.to_vec())] {
+            let code = Bytecode::new_raw(code.into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+            );
+        }
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_cfg_env(|cfg| cfg.execution_budget = budget)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = u64::MAX / 2;
+            })
+            .modify_block_env(|block| block.gas_limit = U256::MAX)
+            .build();
+        let result = evm.transact().unwrap().result;
+        assert!(evm.context.evm.budget.is_none());
+        result
+    }
+
+    #[test]
+    fn execution_budget() {
+        let halt = |reason| ExecutionResult::Halt {
+            reason: HaltReason::ExecutionInterrupted(reason),
+            gas_used: u64::MAX / 2,
+        };
+
+        assert_eq!(
+            interrupted_execution(ExecutionBudget::default().with_max_instructions(10_000)),
+            halt(InterruptReason::InstructionLimit)
+        );
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        assert_eq!(
+            interrupted_execution(ExecutionBudget::default().with_cancel_flag(cancel)),
+            halt(InterruptReason::Cancelled)
+        );
+
+        // Every call of the clock advances it by a second.
+        let now = Arc::new(AtomicU64::new(0));
+        let clock = Arc::new(move || Duration::from_secs(now.fetch_add(1, Ordering::Relaxed)));
+        assert_eq!(
+            interrupted_execution(
+                ExecutionBudget::default().with_time_limit(clock, Duration::from_secs(10))
+            ),
+            halt(InterruptReason::TimeLimit)
+        );
+    }
+
+    #[test]
+    fn decoded_bytecode_execution() {
+        let contract = address!("0000000000000000000000000000000000000100");
+        let callee = address!("0000000000000000000000000000000000000200");
+        // Count down from 10 in a loop, call(gas, callee, 0, 0, 0, 0, 32), pop,
+        // sstore(0, mload(0)), sstore(1, gas), create contracts with init codes
+        // sstore(0, 1) and sstore(0, 2), stop
+        let contract_code = [
+            &hex!("600a5b60019003806002575060206000600060006000")[..],
+            &[0x73],
+            callee.as_slice(),
+            &hex!("5af1506000516000555a600155"),
+            &hex!("656001600055006000526006601a6000f050"),
+            &hex!("656002600055006000526006601a6000f050"),
+            &[0x00],
+        ]
+        .concat();
+        // sstore(1, 42), mstore(0, 42), return(0, 32)
+        let callee_code = hex!("602a600155602a60005260206000f3");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [(contract, contract_code), (callee, callee_code.to_vec())] {
+            let code = Bytecode::new_raw(code.into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+            );
+        }
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+        let expected = evm.transact().unwrap();
+        assert!(expected.result.is_success());
+        let created = [0, 1].map(|nonce| contract.create(nonce));
+        for (address, value) in created.into_iter().zip([1, 2]) {
+            assert_eq!(
+                expected.state[&address].storage[&U256::ZERO].present_value,
+                U256::from(value)
+            );
+        }
+        assert!(evm.context.evm.decoded_bytecodes.is_empty());
+
+        evm.cfg_mut().perf_analyse_created_bytecodes = AnalysisKind::Decode;
+        assert_eq!(evm.transact().unwrap(), expected);
+        assert_eq!(evm.context.evm.decoded_bytecodes.len(), 2);
+        // Decoded bytecodes are reused by the next transaction.
+        assert_eq!(evm.transact().unwrap(), expected);
+        assert_eq!(evm.context.evm.decoded_bytecodes.len(), 2);
+    }
+}
@@ -17,7 +17,8 @@ pub use execution::{
 };
 
 pub use pre_execution::{
-    DeductCallerHandle, LoadAccountsHandle, LoadPrecompilesHandle, PreExecutionHandler,
+    DeductCallerHandle, LoadAccountsHandle, LoadPrecompilesHandle, PreBlockCallsHandle,
+    PreExecutionHandler,
 };
 
 pub use post_execution::{
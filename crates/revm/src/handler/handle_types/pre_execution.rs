@@ -1,10 +1,10 @@
 // Includes.
 use crate::{
     handler::mainnet,
-    primitives::{db::Database, EVMError, EVMResultGeneric, Spec},
+    primitives::{db::Database, Address, Bytes, EVMError, EVMResultGeneric, Spec},
     Context, ContextPrecompiles,
 };
-use std::sync::Arc;
+use std::{sync::Arc, vec::Vec};
 
 /// Loads precompiles into Evm
 pub type LoadPrecompilesHandle<'a, DB> = Arc<dyn Fn() -> ContextPrecompiles<DB> + 'a>;
@@ -19,6 +19,15 @@ pub type LoadAccountsHandle<'a, EXT, DB> =
 pub type DeductCallerHandle<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>) -> EVMResultGeneric<(), <DB as Database>::Error> + 'a>;
 
+/// Returns the system calls, as the contract address and the input, that are made
+/// before the first transaction of the block.
+pub type PreBlockCallsHandle<'a, EXT, DB> = Arc<
+    dyn Fn(
+            &mut Context<EXT, DB>,
+        ) -> EVMResultGeneric<Vec<(Address, Bytes)>, <DB as Database>::Error>
+        + 'a,
+>;
+
 /// Handles related to pre execution before the stack loop is started.
 pub struct PreExecutionHandler<'a, EXT, DB: Database> {
     /// Load precompiles
@@ -27,6 +36,8 @@ pub struct PreExecutionHandler<'a, EXT, DB: Database> {
     pub load_accounts: LoadAccountsHandle<'a, EXT, DB>,
     /// Deduct max value from the caller.
     pub deduct_caller: DeductCallerHandle<'a, EXT, DB>,
+    /// System calls made before the first transaction of the block.
+    pub pre_block_calls: PreBlockCallsHandle<'a, EXT, DB>,
 }
 
 impl<'a, EXT: 'a, DB: Database + 'a> PreExecutionHandler<'a, EXT, DB> {
@@ -36,6 +47,7 @@ impl<'a, EXT: 'a, DB: Database + 'a> PreExecutionHandler<'a, EXT, DB> {
             load_precompiles: Arc::new(mainnet::load_precompiles::<SPEC, DB>),
             load_accounts: Arc::new(mainnet::load_accounts::<SPEC, EXT, DB>),
             deduct_caller: Arc::new(mainnet::deduct_caller::<SPEC, EXT, DB>),
+            pre_block_calls: Arc::new(mainnet::pre_block_calls::<SPEC, EXT, DB>),
         }
     }
 }
@@ -55,4 +67,12 @@ impl<'a, EXT, DB: Database> PreExecutionHandler<'a, EXT, DB> {
     pub fn load_precompiles(&self) -> ContextPrecompiles<DB> {
         (self.load_precompiles)()
     }
+
+    /// Pre-block system calls
+    pub fn pre_block_calls(
+        &self,
+        context: &mut Context<EXT, DB>,
+    ) -> Result<Vec<(Address, Bytes)>, EVMError<DB::Error>> {
+        (self.pre_block_calls)(context)
+    }
 }
@@ -10,8 +10,8 @@ use crate::{
 };
 use core::mem;
 use revm_interpreter::{
-    opcode::InstructionTables, CallOutcome, EOFCreateInput, EOFCreateOutcome, InterpreterAction,
-    InterpreterResult, EMPTY_SHARED_MEMORY,
+    opcode::InstructionTables, BudgetTracker, CallOutcome, EOFCreateInput, EOFCreateOutcome,
+    InterpreterAction, InterpreterResult, EMPTY_SHARED_MEMORY,
 };
 use std::boxed::Box;
 
@@ -25,9 +25,33 @@ pub fn execute_frame<SPEC: Spec, EXT, DB: Database>(
 ) -> Result<InterpreterAction, EVMError<DB::Error>> {
     let interpreter = frame.interpreter_mut();
     let memory = mem::replace(shared_memory, EMPTY_SHARED_MEMORY);
-    let next_action = match instruction_tables {
-        InstructionTables::Plain(table) => interpreter.run(memory, table, context),
-        InstructionTables::Boxed(table) => interpreter.run(memory, table, context),
+    let next_action = if context.evm.env.cfg.execution_budget.is_unlimited() {
+        match instruction_tables {
+            InstructionTables::Plain(table) => {
+                // Inspector uses boxed instructions, so it observes every step.
+                match context.evm.decoded_bytecode(&interpreter.contract) {
+                    Some(decoded) => interpreter.run_decoded(memory, &decoded, table, context),
+                    None => interpreter.run(memory, table, context),
+                }
+            }
+            InstructionTables::Boxed(table) => interpreter.run(memory, table, context),
+        }
+    } else {
+        // Budget is shared by all frames of the transaction.
+        let mut budget =
+            context.evm.budget.take().unwrap_or_else(|| {
+                BudgetTracker::new(context.evm.env.cfg.execution_budget.clone())
+            });
+        let next_action = match instruction_tables {
+            InstructionTables::Plain(table) => {
+                interpreter.run_with_budget(memory, table, context, &mut budget)
+            }
+            InstructionTables::Boxed(table) => {
+                interpreter.run_with_budget(memory, table, context, &mut budget)
+            }
+        };
+        context.evm.budget = Some(budget);
+        next_action
     };
     // Take the shared memory back.
     *shared_memory = interpreter.take_memory();
@@ -36,10 +60,14 @@ pub fn execute_frame<SPEC: Spec, EXT, DB: Database>(
 }
 
 /// Helper function called inside [`last_frame_return`]
+///
+/// `eip7702_refund` is the refund for applied EIP-7702 authorizations, it is
+/// granted regardless of the outcome of the execution.
 #[inline]
 pub fn frame_return_with_refund_flag<SPEC: Spec>(
     env: &Env,
     frame_result: &mut FrameResult,
+    eip7702_refund: i64,
     refund_enabled: bool,
 ) {
     let instruction_result = frame_result.interpreter_result().result;
@@ -61,6 +89,9 @@ pub fn frame_return_with_refund_flag<SPEC: Spec>(
         _ => {}
     }
 
+    // EIP-7702: Authorizations are applied before execution and are not reverted.
+    gas.record_refund(eip7702_refund);
+
     // Calculate gas refund for transaction.
     // If config is set to disable gas refund, it will return 0.
     // If spec is set to london, it will decrease the maximum refund amount to 5th part of
@@ -77,7 +108,8 @@ pub fn last_frame_return<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
     frame_result: &mut FrameResult,
 ) -> Result<(), EVMError<DB::Error>> {
-    frame_return_with_refund_flag::<SPEC>(&context.evm.env, frame_result, true);
+    let eip7702_refund = context.evm.inner.eip7702_refund;
+    frame_return_with_refund_flag::<SPEC>(&context.evm.env, frame_result, eip7702_refund, true);
     Ok(())
 }
 
@@ -220,7 +252,7 @@ mod tests {
             },
             0..0,
         ));
-        frame_return_with_refund_flag::<CancunSpec>(&env, &mut first_frame, true);
+        frame_return_with_refund_flag::<CancunSpec>(&env, &mut first_frame, 0, true);
         *first_frame.gas()
     }
 
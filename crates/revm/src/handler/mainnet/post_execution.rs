@@ -15,11 +15,13 @@ pub fn end<EXT, DB: Database>(
     evm_output
 }
 
-/// Clear handle clears error and journal state.
+/// Clear handle clears error, EIP-7702 refund, execution budget and journal state.
 #[inline]
 pub fn clear<EXT, DB: Database>(context: &mut Context<EXT, DB>) {
     // clear error and journaled state.
     let _ = context.evm.take_error();
+    context.evm.inner.eip7702_refund = 0;
+    context.evm.inner.budget = None;
     context.evm.inner.journaled_state.clear();
 }
 
@@ -110,10 +112,11 @@ pub fn output<EXT, DB: Database>(
             reason,
             gas_used: final_gas_used,
         },
-        // Only two internal return flags.
+        // Internal return flags.
         flag @ (SuccessOrHalt::FatalExternalError
         | SuccessOrHalt::InternalContinue
-        | SuccessOrHalt::InternalCallOrCreate) => {
+        | SuccessOrHalt::InternalCallOrCreate
+        | SuccessOrHalt::InternalSuspend) => {
             panic!(
                 "Encountered unexpected internal return flag: {:?} with instruction result: {:?}",
                 flag, instruction_result
@@ -3,15 +3,19 @@
 //! They handle initial setup of the EVM, call loop and the final return of the EVM
 
 use crate::{
-    precompile::{PrecompileSpecId, Precompiles},
+    precompile::{secp256k1::ecrecover, PrecompileSpecId, Precompiles},
     primitives::{
+        alloy_primitives::B512,
         db::Database,
-        Account, EVMError, Env, Spec,
+        eip7702::{PER_AUTH_BASE_COST, PER_EMPTY_ACCOUNT_COST},
+        Account, Address, Authorization, Bytecode, Bytes, EVMError, Env, InvalidHeader,
+        SignedAuthorization, Spec,
         SpecId::{CANCUN, PRAGUE, SHANGHAI},
-        TransactTo, BLOCKHASH_STORAGE_ADDRESS, U256,
+        TransactTo, BEACON_ROOTS_ADDRESS, BLOCKHASH_STORAGE_ADDRESS, KECCAK_EMPTY, U256,
     },
     Context, ContextPrecompiles,
 };
+use std::vec::Vec;
 
 /// Main precompile load
 #[inline]
@@ -50,9 +54,118 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
     }
 
     context.evm.load_access_list()?;
+
+    // EIP-7702: Set EOA account code
+    context.evm.inner.eip7702_refund = if SPEC::enabled(PRAGUE) {
+        apply_eip7702_auth_list::<SPEC, EXT, DB>(context)? as i64
+    } else {
+        0
+    };
     Ok(())
 }
 
+/// Applies the EIP-7702 authorization list of the transaction.
+///
+/// Invalid authorizations are skipped. Returns the gas refund for authorities
+/// whose accounts were not empty, as they were charged as empty in the initial gas.
+#[inline]
+pub fn apply_eip7702_auth_list<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<u64, EVMError<DB::Error>> {
+    let Some(authorization_list) = context.evm.inner.env.tx.authorization_list.as_ref() else {
+        return Ok(0);
+    };
+    let authorization_list = authorization_list.recovered_with(recover_authority);
+    let chain_id = U256::from(context.evm.inner.env.cfg.chain_id);
+    let caller = context.evm.inner.env.tx.caller;
+
+    let mut refunded_accounts = 0;
+    for authorization in authorization_list {
+        let Authorization {
+            chain_id: auth_chain_id,
+            address,
+            nonce,
+        } = authorization.inner;
+
+        // 1. Verify the chain id is either 0 or the chain's current ID.
+        if auth_chain_id != U256::ZERO && auth_chain_id != chain_id {
+            continue;
+        }
+
+        // 2. Verify the nonce is less than 2**64 - 1.
+        if nonce == u64::MAX {
+            continue;
+        }
+
+        // 3. Authority is recovered from the signature, skip if the signature is invalid.
+        let Some(authority) = authorization.authority else {
+            continue;
+        };
+
+        // 4. Add authority to accessed addresses.
+        let (authority_acc, _) = context
+            .evm
+            .inner
+            .journaled_state
+            .load_code(authority, &mut context.evm.inner.db)?;
+
+        // 5. Verify the code of authority is either empty or already delegated.
+        if authority_acc
+            .info
+            .code
+            .as_ref()
+            .is_some_and(|code| !code.is_empty() && !code.is_eip7702())
+        {
+            continue;
+        }
+
+        // 6. Verify the nonce of authority is equal to nonce.
+        // Nonce of the caller is bumped before the list is applied, in revm this
+        // happens later inside `deduct_caller` so it needs to be accounted for here.
+        let mut authority_nonce = authority_acc.info.nonce;
+        if authority == caller {
+            authority_nonce = authority_nonce.saturating_add(1);
+        }
+        if authority_nonce != nonce {
+            continue;
+        }
+
+        // 7. Refund the difference if the authority account is not empty.
+        if !authority_acc.is_empty() {
+            refunded_accounts += 1;
+        }
+
+        // 8. Set the code of authority to the delegation designator,
+        // delegating to the zero address clears the code.
+        let (bytecode, code_hash) = if address == Address::ZERO {
+            (Bytecode::default(), KECCAK_EMPTY)
+        } else {
+            let bytecode = Bytecode::new_eip7702(address);
+            let code_hash = bytecode.hash_slow();
+            (bytecode, code_hash)
+        };
+        authority_acc.info.code_hash = code_hash;
+        authority_acc.info.code = Some(bytecode);
+
+        // 9. Increase the nonce of authority.
+        authority_acc.info.nonce = authority_acc.info.nonce.saturating_add(1);
+        authority_acc.mark_touch();
+    }
+
+    Ok(refunded_accounts * (PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST))
+}
+
+/// Recovers the authority of the signed EIP-7702 authorization.
+///
+/// Returns `None` if the signature is invalid.
+#[inline]
+pub fn recover_authority(authorization: &SignedAuthorization) -> Option<Address> {
+    let (sig, recid) = authorization.compact_signature()?;
+    ecrecover(&B512::from(sig), recid, &authorization.signature_hash())
+        .ok()
+        .map(Address::from_word)
+}
+
 /// Helper function that deducts the caller balance.
 #[inline]
 pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env) {
@@ -96,3 +209,27 @@ pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
 
     Ok(())
 }
+
+/// Returns the system calls made before the first transaction of the block.
+#[inline]
+pub fn pre_block_calls<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<Vec<(Address, Bytes)>, EVMError<DB::Error>> {
+    let mut calls = Vec::new();
+    let block = &context.evm.inner.env.block;
+
+    // EIP-4788: Beacon block root in the EVM
+    if SPEC::enabled(CANCUN) {
+        let Some(parent_beacon_block_root) = block.parent_beacon_block_root else {
+            return Err(InvalidHeader::ParentBeaconBlockRootNotSet.into());
+        };
+        // Genesis block has no parent.
+        if block.number != U256::ZERO {
+            calls.push((
+                BEACON_ROOTS_ADDRESS,
+                parent_beacon_block_root.0.to_vec().into(),
+            ));
+        }
+    }
+    Ok(calls)
+}
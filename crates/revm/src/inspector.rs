@@ -5,6 +5,9 @@ use crate::{
 };
 use auto_impl::auto_impl;
 
+mod access_list;
+mod call_tracer;
+mod coverage;
 #[cfg(feature = "std")]
 mod customprinter;
 #[cfg(all(feature = "std", feature = "serde-json"))]
@@ -12,6 +15,9 @@ mod eip3155;
 mod gas;
 mod handler_register;
 mod noop;
+mod prestate_tracer;
+#[cfg(feature = "std")]
+mod profiler;
 
 // Exports.
 
@@ -20,12 +26,25 @@ use revm_interpreter::{CallOutcome, CreateOutcome};
 
 /// [Inspector] implementations.
 pub mod inspectors {
+    pub use super::access_list::AccessListInspector;
+    pub use super::call_tracer::{
+        CallKind, CallTraceFrame, CallTraceLog, CallTracer, CallTracerConfig,
+    };
+    pub use super::coverage::{
+        BranchCoverage, CodeCoverage, CoverageInspector, SourceFile, SourceMap, SourceMapEntry,
+        SourceMappedContract,
+    };
     #[cfg(feature = "std")]
     pub use super::customprinter::CustomPrintTracer;
     #[cfg(all(feature = "std", feature = "serde-json"))]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
     pub use super::noop::NoOpInspector;
+    pub use super::prestate_tracer::{
+        PrestateAccount, PrestateAccounts, PrestateFrame, PrestateTracer, PrestateTracerConfig,
+    };
+    #[cfg(feature = "std")]
+    pub use super::profiler::{FlameGraphKey, FlameGraphWeight, ProfileEntry, Profiler};
 }
 
 /// EVM [Interpreter] callbacks.
@@ -0,0 +1,137 @@
+//! [Inspector] that collects the access list of the transaction.
+
+use crate::{
+    interpreter::{opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    primitives::{db::Database, Address, HashSet, SpecId, U256},
+    EvmContext, Inspector,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Inspector that collects all addresses and storage keys touched by the transaction.
+///
+/// Follows the geth `AccessListTracer`: storage keys are recorded for every `SLOAD` and
+/// `SSTORE`, addresses for account and call opcodes. Caller, target of the transaction,
+/// precompiles and, since Shanghai (EIP-3651), coinbase are warm by default and are
+/// excluded from the list unless storage of them was accessed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessListInspector {
+    /// Touched addresses and storage keys.
+    access_list: BTreeMap<Address, BTreeSet<U256>>,
+    /// Addresses that are excluded from the access list.
+    excluded: HashSet<Address>,
+}
+
+impl AccessListInspector {
+    /// Creates new inspector seeded with the given access list.
+    pub fn new(access_list: &[(Address, Vec<U256>)]) -> Self {
+        Self {
+            access_list: access_list
+                .iter()
+                .map(|(address, slots)| (*address, slots.iter().copied().collect()))
+                .collect(),
+            excluded: HashSet::default(),
+        }
+    }
+
+    /// Returns the collected access list.
+    pub fn access_list(&self) -> Vec<(Address, Vec<U256>)> {
+        self.access_list
+            .iter()
+            .filter(|(address, slots)| !slots.is_empty() || !self.excluded.contains(*address))
+            .map(|(address, slots)| (*address, slots.iter().copied().collect()))
+            .collect()
+    }
+
+    /// Returns the addresses excluded from the access list.
+    pub fn excluded(&self) -> &HashSet<Address> {
+        &self.excluded
+    }
+
+    /// Excludes the addresses that are warm at the start of the transaction.
+    fn exclude_warm_addresses<DB: Database>(&mut self, context: &EvmContext<DB>) {
+        self.excluded.insert(context.env.tx.caller);
+        if context.spec_id().is_enabled_in(SpecId::SHANGHAI) {
+            self.excluded.insert(context.env.block.coinbase);
+        }
+        self.excluded
+            .extend(context.precompiles.addresses().copied());
+    }
+
+    fn touch_address(&mut self, address: Address) {
+        self.access_list.entry(address).or_default();
+    }
+
+    fn touch_slot(&mut self, address: Address, slot: U256) {
+        self.access_list.entry(address).or_default().insert(slot);
+    }
+}
+
+impl<DB: Database> Inspector<DB> for AccessListInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.touch_slot(interp.contract.target_address, slot);
+                }
+            }
+            opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::EXTCODESIZE
+            | opcode::BALANCE
+            | opcode::SELFDESTRUCT
+            | opcode::EXTCALL
+            | opcode::EXTDELEGATECALL
+            | opcode::EXTSTATICCALL => {
+                if let Ok(address) = interp.stack.peek(0) {
+                    self.touch_address(Address::from_word(address.into()));
+                }
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+                if let Ok(address) = interp.stack.peek(1) {
+                    self.touch_address(Address::from_word(address.into()));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if context.journaled_state.depth() == 0 {
+            self.exclude_warm_addresses(context);
+            self.excluded.insert(inputs.target_address);
+        }
+        None
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if context.journaled_state.depth() == 0 {
+            self.exclude_warm_addresses(context);
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if context.journaled_state.depth() == 0 {
+            if let Some(address) = outcome.address {
+                self.excluded.insert(address);
+            }
+        }
+        outcome
+    }
+}
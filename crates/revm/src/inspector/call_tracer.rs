@@ -0,0 +1,649 @@
+//! Geth `callTracer` compatible [Inspector].
+
+use crate::{
+    interpreter::{
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, Gas, InstructionResult,
+    },
+    primitives::{
+        db::Database, Address, Bytes, CreateScheme, Log, RevertDecoder, RevertReason, SpecId, B256,
+        U256,
+    },
+    EvmContext, Inspector,
+};
+use std::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Configuration of the [`CallTracer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct CallTracerConfig {
+    /// Only trace the top level call, nested calls are not recorded.
+    pub only_top_call: bool,
+    /// Record the logs emitted by the calls.
+    pub with_log: bool,
+}
+
+/// Type of the traced call frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum CallKind {
+    /// `CALL` or transaction call.
+    #[default]
+    Call,
+    /// `STATICCALL`.
+    StaticCall,
+    /// `CALLCODE`.
+    CallCode,
+    /// `DELEGATECALL`.
+    DelegateCall,
+    /// `CREATE` or transaction create.
+    Create,
+    /// `CREATE2`.
+    Create2,
+    /// `SELFDESTRUCT`.
+    SelfDestruct,
+}
+
+impl From<CallScheme> for CallKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => Self::Call,
+            CallScheme::StaticCall => Self::StaticCall,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall => Self::DelegateCall,
+        }
+    }
+}
+
+impl From<CreateScheme> for CallKind {
+    fn from(scheme: CreateScheme) -> Self {
+        match scheme {
+            CreateScheme::Create => Self::Create,
+            CreateScheme::Create2 { .. } => Self::Create2,
+        }
+    }
+}
+
+/// Log emitted inside of the traced call frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallTraceLog {
+    /// Address of the contract that emitted the log.
+    pub address: Address,
+    /// Topics of the log.
+    pub topics: Vec<B256>,
+    /// Data of the log.
+    pub data: Bytes,
+    /// Number of nested calls made by the frame before the log was emitted.
+    #[cfg_attr(feature = "serde", serde(with = "hex_u64"))]
+    pub position: u64,
+}
+
+/// Traced call frame, in the shape of the geth `callTracer` output.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallTraceFrame {
+    /// Type of the call.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: CallKind,
+    /// Caller of the frame.
+    pub from: Address,
+    /// Gas limit of the frame, the transaction gas limit for the top level frame.
+    #[cfg_attr(feature = "serde", serde(with = "hex_u64"))]
+    pub gas: u64,
+    /// Gas used by the frame, gas used by the transaction for the top level frame.
+    #[cfg_attr(feature = "serde", serde(with = "hex_u64"))]
+    pub gas_used: u64,
+    /// Callee of the frame or address of the created contract.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub to: Option<Address>,
+    /// Input of the call or init code of the create.
+    pub input: Bytes,
+    /// Output of the frame.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub output: Option<Bytes>,
+    /// Error of the frame, if it failed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// Decoded revert reason, if the frame reverted with one.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub revert_reason: Option<String>,
+    /// Nested call frames.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub calls: Vec<CallTraceFrame>,
+    /// Logs emitted by the frame.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub logs: Vec<CallTraceLog>,
+    /// Value transferred, not set for `DELEGATECALL` and `STATICCALL`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub value: Option<U256>,
+}
+
+impl CallTraceFrame {
+    /// Sets the result of the frame.
+    fn set_result(
+        &mut self,
+        result: InstructionResult,
+        output: &Bytes,
+        gas_used: u64,
+        revert_decoder: &RevertDecoder,
+    ) {
+        self.gas_used = gas_used;
+        if result.is_ok() {
+            if !output.is_empty() {
+                self.output = Some(output.clone());
+            }
+            return;
+        }
+        self.error = Some(error_message(result).into());
+        if result.is_revert() {
+            if !output.is_empty() {
+                self.output = Some(output.clone());
+            }
+            // Like geth, custom errors without a known signature are not reported.
+            self.revert_reason = revert_decoder
+                .decode(output)
+                .filter(|reason| {
+                    !matches!(
+                        reason,
+                        RevertReason::CustomError {
+                            signature: None,
+                            ..
+                        }
+                    )
+                })
+                .map(|reason| reason.to_string());
+        }
+        // Failed create does not have an address.
+        if matches!(self.kind, CallKind::Create | CallKind::Create2) {
+            self.to = None;
+        }
+    }
+
+    /// Removes logs of the failed frames and of the frames nested in them.
+    fn clear_failed_logs(&mut self, parent_failed: bool) {
+        let failed = parent_failed || self.error.is_some();
+        if failed {
+            self.logs.clear();
+        }
+        for call in &mut self.calls {
+            call.clear_failed_logs(failed);
+        }
+    }
+}
+
+/// [Inspector] that builds the call frame tree of the transaction,
+/// compatible with the geth `callTracer`.
+///
+/// With the `serde` feature the frame serializes to the geth JSON shape.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    config: CallTracerConfig,
+    /// Frames that are being executed.
+    stack: Vec<CallTraceFrame>,
+    /// Depth of the execution, including the frames that are not recorded.
+    depth: usize,
+    /// Top level frame of the last traced transaction.
+    frame: Option<CallTraceFrame>,
+    /// Decoder of the revert reasons.
+    revert_decoder: RevertDecoder,
+}
+
+impl CallTracer {
+    /// Creates new call tracer with the given configuration.
+    pub fn new(config: CallTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the decoder of the revert reasons, used to resolve the custom errors.
+    pub fn with_revert_decoder(mut self, revert_decoder: RevertDecoder) -> Self {
+        self.revert_decoder = revert_decoder;
+        self
+    }
+
+    /// Returns the configuration of the tracer.
+    pub fn config(&self) -> &CallTracerConfig {
+        &self.config
+    }
+
+    /// Returns the top level frame of the last traced transaction.
+    pub fn frame(&self) -> Option<&CallTraceFrame> {
+        self.frame.as_ref()
+    }
+
+    /// Takes the top level frame of the last traced transaction.
+    pub fn take_frame(&mut self) -> Option<CallTraceFrame> {
+        self.frame.take()
+    }
+
+    /// Returns true if the frame at the current depth is recorded.
+    fn is_recorded(&self) -> bool {
+        !self.config.only_top_call || self.depth == 0
+    }
+
+    fn start_frame(&mut self, frame: CallTraceFrame) {
+        if self.depth == 0 {
+            self.stack.clear();
+            self.frame = None;
+        }
+        if self.is_recorded() {
+            self.stack.push(frame);
+        }
+        self.depth += 1;
+    }
+
+    fn end_frame<DB: Database>(
+        &mut self,
+        context: &EvmContext<DB>,
+        result: InstructionResult,
+        output: &Bytes,
+        gas: &Gas,
+        created_address: Option<Address>,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        if !self.is_recorded() {
+            return;
+        }
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        if created_address.is_some() {
+            frame.to = created_address;
+        }
+
+        if let Some(parent) = self.stack.last_mut() {
+            frame.set_result(result, output, gas.spent(), &self.revert_decoder);
+            parent.calls.push(frame);
+            return;
+        }
+
+        // Top level frame is reported with the gas of the transaction.
+        let gas_limit = context.env.tx.gas_limit;
+        frame.gas = gas_limit;
+        frame.set_result(
+            result,
+            output,
+            tx_gas_used(context, result, gas),
+            &self.revert_decoder,
+        );
+        if self.config.with_log {
+            frame.clear_failed_logs(false);
+        }
+        self.frame = Some(frame);
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn log(&mut self, _context: &mut EvmContext<DB>, log: &Log) {
+        if !self.config.with_log || (self.config.only_top_call && self.depth > 1) {
+            return;
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.logs.push(CallTraceLog {
+                address: log.address,
+                topics: log.topics().to_vec(),
+                data: log.data.data.clone(),
+                position: frame.calls.len() as u64,
+            });
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let (from, value) = match inputs.scheme {
+            CallScheme::Call | CallScheme::CallCode => (inputs.caller, Some(inputs.value.get())),
+            CallScheme::StaticCall => (inputs.caller, None),
+            // Caller of the delegate call is the contract that is executing it.
+            CallScheme::DelegateCall => (inputs.target_address, None),
+        };
+        self.start_frame(CallTraceFrame {
+            kind: inputs.scheme.into(),
+            from,
+            gas: inputs.gas_limit,
+            to: Some(inputs.bytecode_address),
+            input: inputs.input.clone(),
+            value,
+            ..Default::default()
+        });
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        let result = &outcome.result;
+        self.end_frame(context, result.result, &result.output, &result.gas, None);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start_frame(CallTraceFrame {
+            kind: inputs.scheme.into(),
+            from: inputs.caller,
+            gas: inputs.gas_limit,
+            input: inputs.init_code.clone(),
+            value: Some(inputs.value),
+            ..Default::default()
+        });
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        let result = &outcome.result;
+        self.end_frame(
+            context,
+            result.result,
+            &result.output,
+            &result.gas,
+            outcome.address,
+        );
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.config.only_top_call {
+            return;
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.calls.push(CallTraceFrame {
+                kind: CallKind::SelfDestruct,
+                from: contract,
+                to: Some(target),
+                value: Some(value),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// Returns the gas used by the transaction, with the refund applied.
+fn tx_gas_used<DB: Database>(
+    context: &EvmContext<DB>,
+    result: InstructionResult,
+    gas: &Gas,
+) -> u64 {
+    let spent = context.env.tx.gas_limit.saturating_sub(gas.remaining());
+    let mut refunded = context.inner.eip7702_refund;
+    if result.is_ok() {
+        refunded += gas.refunded();
+    }
+    let max_refund_quotient = if context.spec_id().is_enabled_in(SpecId::LONDON) {
+        5
+    } else {
+        2
+    };
+    spent - (refunded.max(0) as u64).min(spent / max_refund_quotient)
+}
+
+/// Returns the geth error message of the failed frame.
+fn error_message(result: InstructionResult) -> &'static str {
+    match result {
+        InstructionResult::Revert => "execution reverted",
+        InstructionResult::CallTooDeep => "max call depth exceeded",
+        InstructionResult::OutOfFunds => "insufficient balance for transfer",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG => "out of gas",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "invalid opcode",
+        InstructionResult::CallNotAllowedInsideStatic
+        | InstructionResult::StateChangeDuringStaticCall => "write protection",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached 1024",
+        InstructionResult::OutOfOffset => "return data out of bounds",
+        InstructionResult::CreateCollision => "contract address collision",
+        InstructionResult::OverflowPayment => "gas uint64 overflow",
+        InstructionResult::PrecompileError => "precompiled contract failed",
+        InstructionResult::NonceOverflow => "nonce uint64 overflow",
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded",
+        InstructionResult::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        InstructionResult::CreateInitCodeSizeLimit => "max initcode size exceeded",
+        _ => "execution failed",
+    }
+}
+
+#[cfg(feature = "serde")]
+mod hex_u64 {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::{format, string::String};
+
+    pub(super) fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:#x}"))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let hex = value
+            .strip_prefix("0x")
+            .ok_or_else(|| D::Error::custom("missing 0x prefix"))?;
+        u64::from_str_radix(hex, 16).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, bytes, AccountInfo, Bytecode, TransactTo},
+        Evm,
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+    const OUTER: Address = address!("2000000000000000000000000000000000000000");
+    const INNER: Address = address!("3000000000000000000000000000000000000000");
+
+    /// `Error("x")` revert data.
+    const REVERT_DATA: Bytes = bytes!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000017800000000000000000000000000000000000000000000000000000000000000");
+
+    fn trace(config: CallTracerConfig) -> CallTraceFrame {
+        let mut db = CacheDB::new(EmptyDB::default());
+        // LOG0, CALL(gas, INNER, 0, 0, 0, 0, 0), STOP
+        let mut outer = vec![
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::LOG0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH20,
+        ];
+        outer.extend_from_slice(INNER.as_slice());
+        outer.extend_from_slice(&[opcode::GAS, opcode::CALL, opcode::STOP]);
+        // LOG0, revert with `Error("x")`
+        let mut inner = vec![opcode::PUSH1, 0, opcode::PUSH1, 0, opcode::LOG0];
+        for (i, chunk) in REVERT_DATA.chunks(32).enumerate() {
+            let mut word = [0u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            inner.push(opcode::PUSH32);
+            inner.extend_from_slice(&word);
+            inner.extend_from_slice(&[opcode::PUSH1, (i * 32) as u8, opcode::MSTORE]);
+        }
+        inner.extend_from_slice(&[
+            opcode::PUSH1,
+            REVERT_DATA.len() as u8,
+            opcode::PUSH1,
+            0,
+            opcode::REVERT,
+        ]);
+        for (address, code) in [(OUTER, outer), (INNER, inner)] {
+            let code = Bytecode::new_raw(code.into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(CallTracer::new(config))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(OUTER);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap().result;
+        let frame = evm.context.external.take_frame().unwrap();
+        assert_eq!(frame.gas_used, result.gas_used());
+        frame
+    }
+
+    #[test]
+    fn call_tree() {
+        let frame = trace(CallTracerConfig {
+            only_top_call: false,
+            with_log: true,
+        });
+        assert_eq!(frame.kind, CallKind::Call);
+        assert_eq!(frame.from, CALLER);
+        assert_eq!(frame.to, Some(OUTER));
+        assert_eq!(frame.gas, 100_000);
+        assert_eq!(frame.value, Some(U256::ZERO));
+        assert_eq!(frame.error, None);
+        assert_eq!(frame.logs.len(), 1);
+        assert_eq!(frame.logs[0].position, 0);
+
+        let [inner] = &frame.calls[..] else {
+            panic!("expected one nested call");
+        };
+        assert_eq!(inner.kind, CallKind::Call);
+        assert_eq!(inner.from, OUTER);
+        assert_eq!(inner.to, Some(INNER));
+        assert_eq!(inner.error.as_deref(), Some("execution reverted"));
+        assert_eq!(inner.revert_reason.as_deref(), Some("x"));
+        assert_eq!(inner.output, Some(REVERT_DATA));
+        // logs of the reverted frame are removed.
+        assert!(inner.logs.is_empty());
+    }
+
+    #[test]
+    fn only_top_call() {
+        let frame = trace(CallTracerConfig {
+            only_top_call: true,
+            with_log: false,
+        });
+        assert!(frame.calls.is_empty());
+        assert!(frame.logs.is_empty());
+    }
+
+    #[test]
+    fn revert_reason() {
+        let mut decoder = RevertDecoder::new();
+        let selector = decoder.register("Unauthorized()");
+        let revert_reason = |output: &[u8], decoder: &RevertDecoder| {
+            let mut frame = CallTraceFrame::default();
+            frame.set_result(
+                InstructionResult::Revert,
+                &Bytes::copy_from_slice(output),
+                0,
+                decoder,
+            );
+            frame.revert_reason
+        };
+
+        assert_eq!(revert_reason(&REVERT_DATA, &decoder), Some("x".into()));
+        assert_eq!(revert_reason(&REVERT_DATA[..40], &decoder), None);
+        assert_eq!(revert_reason(&[], &decoder), None);
+        assert_eq!(
+            revert_reason(
+                &bytes!("4e487b710000000000000000000000000000000000000000000000000000000000000001"),
+                &decoder
+            ),
+            Some("assert(false)".into())
+        );
+        assert_eq!(
+            revert_reason(selector.as_slice(), &decoder),
+            Some("Unauthorized()".into())
+        );
+        assert_eq!(
+            revert_reason(selector.as_slice(), &RevertDecoder::default()),
+            None
+        );
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn geth_json() {
+        let frame = trace(CallTracerConfig {
+            only_top_call: false,
+            with_log: true,
+        });
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(json["type"], "CALL");
+        assert_eq!(json["gas"], "0x186a0");
+        assert_eq!(json["gasUsed"], format!("{:#x}", frame.gas_used));
+        assert_eq!(json["value"], "0x0");
+        assert!(json.get("output").is_none());
+        assert_eq!(json["logs"][0]["position"], "0x0");
+        assert_eq!(json["calls"][0]["revertReason"], "x");
+        assert_eq!(json["calls"][0]["error"], "execution reverted");
+        assert!(json["calls"][0].get("logs").is_none());
+
+        let config: CallTracerConfig =
+            serde_json::from_str(r#"{"onlyTopCall":true,"withLog":true}"#).unwrap();
+        assert!(config.only_top_call && config.with_log);
+        assert_eq!(
+            serde_json::from_value::<CallTraceFrame>(json).unwrap(),
+            frame
+        );
+    }
+}
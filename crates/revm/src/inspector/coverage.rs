@@ -0,0 +1,435 @@
+//! [Inspector] that collects code coverage of the executed bytecodes.
+
+use crate::{
+    interpreter::{
+        opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInput,
+        EOFCreateOutcome, Interpreter,
+    },
+    primitives::{db::Database, keccak256, HashMap, B256},
+    EvmContext, Inspector,
+};
+use core::{fmt::Write, num::ParseIntError};
+use std::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Coverage of one bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeCoverage {
+    /// Number of executions per program counter.
+    pub hits: BTreeMap<usize, u64>,
+    /// Taken and not taken `JUMPI` branches per program counter.
+    pub branches: BTreeMap<usize, BranchCoverage>,
+}
+
+/// Number of times the `JUMPI` jumped or continued to the next instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BranchCoverage {
+    /// Number of times the `JUMPI` jumped to the destination.
+    pub taken: u64,
+    /// Number of times the `JUMPI` continued to the next instruction.
+    pub not_taken: u64,
+}
+
+/// Inspector that records the executed program counters and `JUMPI` branches per code hash.
+///
+/// Coverage is keyed by the hash of the executed bytecode, so the same contract deployed at
+/// multiple addresses, with `CREATE2` or behind a proxy, is merged. Init code is keyed by
+/// the hash of the init code.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageInspector {
+    coverage: HashMap<B256, CodeCoverage>,
+    /// Hash of the bytecode that was not hashed by the interpreter, per call depth of the
+    /// executing frames. Hash is computed on the first step of the frame.
+    frame_hashes: Vec<Option<B256>>,
+}
+
+impl CoverageInspector {
+    /// Creates new inspector with empty coverage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the coverage per code hash.
+    pub fn coverage(&self) -> &HashMap<B256, CodeCoverage> {
+        &self.coverage
+    }
+
+    /// Returns the coverage of the bytecode with the given hash.
+    pub fn code_coverage(&self, code_hash: &B256) -> Option<&CodeCoverage> {
+        self.coverage.get(code_hash)
+    }
+
+    /// Returns the coverage in the lcov format, mapped to the sources with the source maps
+    /// of the contracts.
+    ///
+    /// Lines are taken from the start of the source ranges of the instructions, a line is hit
+    /// by its most executed instruction. Each `JUMPI` is a branch block with the taken and not
+    /// taken branches.
+    pub fn lcov(&self, contracts: &[SourceMappedContract<'_>], files: &[SourceFile<'_>]) -> String {
+        let line_starts: Vec<Vec<usize>> = files
+            .iter()
+            .map(|file| {
+                core::iter::once(0)
+                    .chain(file.content.match_indices('\n').map(|(i, _)| i + 1))
+                    .collect()
+            })
+            .collect();
+        let line = |file: usize, offset: usize| match line_starts[file].binary_search(&offset) {
+            Ok(index) => index as u32 + 1,
+            Err(index) => index as u32,
+        };
+
+        // line hits and branches per file.
+        let mut lines: Vec<BTreeMap<u32, u64>> = vec![BTreeMap::new(); files.len()];
+        let mut branches: Vec<BTreeMap<(u32, usize), Option<BranchCoverage>>> =
+            vec![BTreeMap::new(); files.len()];
+        let empty = CodeCoverage::default();
+        for contract in contracts {
+            let coverage = self.coverage.get(&contract.code_hash).unwrap_or(&empty);
+            for (pc, entry) in
+                instruction_offsets(contract.bytecode).zip(&contract.source_map.entries)
+            {
+                let Some(file) = entry.file.filter(|file| *file < files.len()) else {
+                    continue;
+                };
+                let line = line(file, entry.offset);
+                let hits = coverage.hits.get(&pc).copied().unwrap_or_default();
+                let line_hits = lines[file].entry(line).or_default();
+                *line_hits = (*line_hits).max(hits);
+                if contract.bytecode[pc] == opcode::JUMPI {
+                    branches[file].insert((line, pc), coverage.branches.get(&pc).copied());
+                }
+            }
+        }
+
+        let mut out = String::new();
+        for (file, source) in files.iter().enumerate() {
+            if lines[file].is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "TN:\nSF:{}", source.path);
+            for ((line, block), branch) in &branches[file] {
+                for (index, taken) in [branch.map(|b| b.taken), branch.map(|b| b.not_taken)]
+                    .into_iter()
+                    .enumerate()
+                {
+                    let taken = taken.map_or_else(|| String::from("-"), |t| t.to_string());
+                    let _ = writeln!(out, "BRDA:{line},{block},{index},{taken}");
+                }
+            }
+            let branches_hit = branches[file]
+                .values()
+                .flatten()
+                .map(|b| (b.taken != 0) as usize + (b.not_taken != 0) as usize)
+                .sum::<usize>();
+            let _ = writeln!(out, "BRF:{}\nBRH:{branches_hit}", branches[file].len() * 2);
+            for (line, hits) in &lines[file] {
+                let _ = writeln!(out, "DA:{line},{hits}");
+            }
+            let lines_hit = lines[file].values().filter(|hits| **hits != 0).count();
+            let _ = writeln!(
+                out,
+                "LF:{}\nLH:{lines_hit}\nend_of_record",
+                lines[file].len()
+            );
+        }
+        out
+    }
+
+    fn code_hash(&mut self, interp: &Interpreter, depth: u64) -> B256 {
+        // Initcode of CREATE does not have a hash, it is zero.
+        if let Some(hash) = interp.contract.hash.filter(|hash| *hash != B256::ZERO) {
+            return hash;
+        }
+        let depth = depth as usize;
+        if self.frame_hashes.len() <= depth {
+            self.frame_hashes.resize(depth + 1, None);
+        }
+        *self.frame_hashes[depth]
+            .get_or_insert_with(|| keccak256(interp.contract.bytecode.original_byte_slice()))
+    }
+
+    /// Forgets the hashes of the frames that ended, the depth is the depth of the parent frame.
+    fn frame_end(&mut self, depth: u64) {
+        self.frame_hashes.truncate(depth as usize + 1);
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CoverageInspector {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let hash = self.code_hash(interp, context.journaled_state.depth());
+        let pc = interp.program_counter();
+        let coverage = self.coverage.entry(hash).or_default();
+        *coverage.hits.entry(pc).or_default() += 1;
+        if interp.current_opcode() == opcode::JUMPI {
+            if let Ok(condition) = interp.stack.peek(1) {
+                let branch = coverage.branches.entry(pc).or_default();
+                if condition.is_zero() {
+                    branch.not_taken += 1;
+                } else {
+                    branch.taken += 1;
+                }
+            }
+        }
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.frame_end(context.journaled_state.depth());
+        outcome
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.frame_end(context.journaled_state.depth());
+        outcome
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &EOFCreateInput,
+        outcome: EOFCreateOutcome,
+    ) -> EOFCreateOutcome {
+        self.frame_end(context.journaled_state.depth());
+        outcome
+    }
+}
+
+/// Returns the program counters of the instructions of the legacy bytecode.
+fn instruction_offsets(bytecode: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    let mut pc = 0;
+    core::iter::from_fn(move || {
+        let opcode = *bytecode.get(pc)?;
+        let current = pc;
+        pc += 1;
+        if (opcode::PUSH1..=opcode::PUSH32).contains(&opcode) {
+            pc += (opcode - opcode::PUSH1 + 1) as usize;
+        }
+        Some(current)
+    })
+}
+
+/// Entry of the Solidity source map, one per instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    /// Byte offset of the source range.
+    pub offset: usize,
+    /// Byte length of the source range.
+    pub length: usize,
+    /// Index of the source file, `None` if the instruction is not mapped to a file.
+    pub file: Option<usize>,
+    /// Jump type, `i` into a function, `o` out of a function or `-` for a regular jump.
+    pub jump: char,
+    /// Depth of the modifiers the instruction is in.
+    pub modifier_depth: usize,
+}
+
+/// Solidity source map of a bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Entries of the instructions, in order of the instructions in the bytecode.
+    pub entries: Vec<SourceMapEntry>,
+}
+
+impl SourceMap {
+    /// Parses the compressed `s:l:f:j:m;...` source map, where empty fields repeat the
+    /// previous entry.
+    pub fn parse(source_map: &str) -> Result<Self, ParseIntError> {
+        let mut entries = Vec::new();
+        let mut last = SourceMapEntry {
+            jump: '-',
+            ..Default::default()
+        };
+        for entry in source_map.split(';') {
+            for (index, field) in entry.split(':').enumerate() {
+                if field.is_empty() {
+                    continue;
+                }
+                match index {
+                    0 => last.offset = field.parse()?,
+                    1 => last.length = field.parse()?,
+                    2 => last.file = usize::try_from(field.parse::<i64>()?).ok(),
+                    3 => last.jump = field.chars().next().unwrap_or('-'),
+                    4 => last.modifier_depth = field.parse()?,
+                    _ => {}
+                }
+            }
+            entries.push(last);
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Bytecode with the source map used to map coverage to sources.
+#[derive(Clone, Copy, Debug)]
+pub struct SourceMappedContract<'a> {
+    /// Hash of the executed bytecode, it differs from the hash of the compiled bytecode if
+    /// the contract has immutables.
+    pub code_hash: B256,
+    /// Compiled bytecode.
+    pub bytecode: &'a [u8],
+    /// Source map of the bytecode.
+    pub source_map: &'a SourceMap,
+}
+
+impl<'a> SourceMappedContract<'a> {
+    /// Creates new contract keyed by the hash of the given bytecode.
+    pub fn new(bytecode: &'a [u8], source_map: &'a SourceMap) -> Self {
+        Self {
+            code_hash: keccak256(bytecode),
+            bytecode,
+            source_map,
+        }
+    }
+}
+
+/// Source file, the index in the list of files is the file index of the source maps.
+#[derive(Clone, Copy, Debug)]
+pub struct SourceFile<'a> {
+    /// Path of the file, written to the lcov report.
+    pub path: &'a str,
+    /// Content of the file, used to map the byte offsets to lines.
+    pub content: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        primitives::{address, bytes, Bytecode, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn parse_source_map() {
+        let source_map = SourceMap::parse("1:2:0:-;:3;;4::-1:i:1").unwrap();
+        assert_eq!(
+            source_map.entries,
+            vec![
+                SourceMapEntry {
+                    offset: 1,
+                    length: 2,
+                    file: Some(0),
+                    jump: '-',
+                    modifier_depth: 0
+                },
+                SourceMapEntry {
+                    offset: 1,
+                    length: 3,
+                    file: Some(0),
+                    jump: '-',
+                    modifier_depth: 0
+                },
+                SourceMapEntry {
+                    offset: 1,
+                    length: 3,
+                    file: Some(0),
+                    jump: '-',
+                    modifier_depth: 0
+                },
+                SourceMapEntry {
+                    offset: 4,
+                    length: 3,
+                    file: None,
+                    jump: 'i',
+                    modifier_depth: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn coverage_to_lcov() {
+        // PUSH1 0, PUSH1 7, JUMPI, STOP, INVALID, JUMPDEST, STOP
+        let code = bytes!("6000600757 00 fe 5b00");
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.clone())))
+            .with_external_context(CoverageInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.transact_to = TransactTo::Call(Default::default());
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap();
+        let inspector = &evm.context.external;
+
+        let coverage = inspector.code_coverage(&keccak256(&code)).unwrap();
+        assert_eq!(
+            coverage.hits.keys().copied().collect::<Vec<_>>(),
+            vec![0, 2, 4, 5]
+        );
+        assert_eq!(
+            coverage.branches[&4],
+            BranchCoverage {
+                taken: 0,
+                not_taken: 1
+            }
+        );
+
+        // one line per instruction.
+        let content = "push\npush\njumpi\nstop\ninvalid\njumpdest\nstop\n";
+        let source_map = SourceMap::parse("0:4:0;5:4;10:5;16:4;21:7;29:8;38:4").unwrap();
+        let lcov = inspector.lcov(
+            &[SourceMappedContract::new(&code, &source_map)],
+            &[SourceFile {
+                path: "Test.sol",
+                content,
+            }],
+        );
+        assert_eq!(
+            lcov,
+            "TN:\nSF:Test.sol\nBRDA:3,4,0,0\nBRDA:3,4,1,1\nBRF:2\nBRH:1\n\
+             DA:1,1\nDA:2,1\nDA:3,1\nDA:4,1\nDA:5,0\nDA:6,0\nDA:7,0\nLF:7\nLH:4\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn init_code_coverage() {
+        // Init code creates the contract with the init code `STOP` and then with
+        // `JUMPDEST, STOP` and continues after each CREATE.
+        let code = bytes!("6000600053 6001600060 00f0 50 605b600053 6002600060 00f0 50 00");
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .with_external_context(CoverageInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.transact_to = TransactTo::Create;
+                tx.data = code.clone();
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        let inspector = &evm.context.external;
+
+        let hits = |code: &[u8]| {
+            inspector
+                .code_coverage(&keccak256(code))
+                .map(|coverage| coverage.hits.keys().copied().collect::<Vec<_>>())
+        };
+        assert_eq!(inspector.coverage().len(), 3);
+        assert_eq!(
+            hits(&code),
+            Some(vec![
+                0, 2, 4, 5, 7, 9, 11, 12, 13, 15, 17, 18, 20, 22, 24, 25, 26
+            ])
+        );
+        assert_eq!(hits(&[0x00]), Some(vec![0]));
+        assert_eq!(hits(&[0x5b, 0x00]), Some(vec![0, 1]));
+    }
+}
@@ -168,11 +168,13 @@ pub fn inspector_handle_register<DB: Database, EXT: GetInspector<DB>>(
     let old_handle = handler.execution.insert_call_outcome.clone();
     handler.execution.insert_call_outcome =
         Arc::new(move |ctx, frame, shared_memory, mut outcome| {
-            let call_inputs = call_input_stack_inner.borrow_mut().pop().unwrap();
-            outcome = ctx
-                .external
-                .get_inspector()
-                .call_end(&mut ctx.evm, &call_inputs, outcome);
+            // Inputs are missing if the call was started before the execution was suspended.
+            if let Some(call_inputs) = call_input_stack_inner.borrow_mut().pop() {
+                outcome =
+                    ctx.external
+                        .get_inspector()
+                        .call_end(&mut ctx.evm, &call_inputs, outcome);
+            }
             old_handle(ctx, frame, shared_memory, outcome)
         });
 
@@ -180,37 +182,54 @@ pub fn inspector_handle_register<DB: Database, EXT: GetInspector<DB>>(
     let create_input_stack_inner = create_input_stack.clone();
     let old_handle = handler.execution.insert_create_outcome.clone();
     handler.execution.insert_create_outcome = Arc::new(move |ctx, frame, mut outcome| {
-        let create_inputs = create_input_stack_inner.borrow_mut().pop().unwrap();
-        outcome = ctx
-            .external
-            .get_inspector()
-            .create_end(&mut ctx.evm, &create_inputs, outcome);
+        if let Some(create_inputs) = create_input_stack_inner.borrow_mut().pop() {
+            outcome =
+                ctx.external
+                    .get_inspector()
+                    .create_end(&mut ctx.evm, &create_inputs, outcome);
+        }
         old_handle(ctx, frame, outcome)
     });
 
     // TODO(EOF) EOF create handle.
 
     // last frame outcome
+    let call_input_stack_inner = call_input_stack.clone();
+    let create_input_stack_inner = create_input_stack.clone();
+    let eofcreate_input_stack_inner = eofcreate_input_stack.clone();
     let old_handle = handler.execution.last_frame_return.clone();
     handler.execution.last_frame_return = Arc::new(move |ctx, frame_result| {
         let inspector = ctx.external.get_inspector();
         match frame_result {
             FrameResult::Call(outcome) => {
-                let call_inputs = call_input_stack.borrow_mut().pop().unwrap();
-                *outcome = inspector.call_end(&mut ctx.evm, &call_inputs, outcome.clone());
+                if let Some(call_inputs) = call_input_stack_inner.borrow_mut().pop() {
+                    *outcome = inspector.call_end(&mut ctx.evm, &call_inputs, outcome.clone());
+                }
             }
             FrameResult::Create(outcome) => {
-                let create_inputs = create_input_stack.borrow_mut().pop().unwrap();
-                *outcome = inspector.create_end(&mut ctx.evm, &create_inputs, outcome.clone());
+                if let Some(create_inputs) = create_input_stack_inner.borrow_mut().pop() {
+                    *outcome = inspector.create_end(&mut ctx.evm, &create_inputs, outcome.clone());
+                }
             }
             FrameResult::EOFCreate(outcome) => {
-                let eofcreate_inputs = eofcreate_input_stack.borrow_mut().pop().unwrap();
-                *outcome =
-                    inspector.eofcreate_end(&mut ctx.evm, &eofcreate_inputs, outcome.clone());
+                if let Some(eofcreate_inputs) = eofcreate_input_stack_inner.borrow_mut().pop() {
+                    *outcome =
+                        inspector.eofcreate_end(&mut ctx.evm, &eofcreate_inputs, outcome.clone());
+                }
             }
         }
         old_handle(ctx, frame_result)
     });
+
+    // Clear is called at the end of the execution and when it is suspended, so inputs of the
+    // frames that did not end are not paired with the frames of the next execution.
+    let old_handle = handler.post_execution.clear.clone();
+    handler.post_execution.clear = Arc::new(move |ctx| {
+        call_input_stack.borrow_mut().clear();
+        create_input_stack.borrow_mut().clear();
+        eofcreate_input_stack.borrow_mut().clear();
+        old_handle(ctx)
+    });
 }
 
 /// Outer closure that calls Inspector for every instruction.
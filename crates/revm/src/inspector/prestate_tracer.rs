@@ -0,0 +1,436 @@
+//! Geth `prestateTracer` compatible [Inspector].
+
+use crate::{
+    interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome},
+    primitives::{db::Database, Account, Address, Bytes, EVMError, EvmState, B256, U256},
+    EvmContext, Inspector,
+};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Configuration of the [`PrestateTracer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct PrestateTracerConfig {
+    /// Emit the pre and post state of the changed accounts instead of the prestate.
+    pub diff_mode: bool,
+}
+
+/// State of the account as reported by the [`PrestateTracer`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrestateAccount {
+    /// Balance of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub balance: Option<U256>,
+    /// Code of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code: Option<Bytes>,
+    /// Nonce of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub nonce: Option<u64>,
+    /// Storage slots of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+impl PrestateAccount {
+    fn is_empty(&self) -> bool {
+        self.balance.unwrap_or_default().is_zero()
+            && self.code.is_none()
+            && self.nonce.is_none()
+            && self.storage.is_empty()
+    }
+}
+
+/// Accounts by address, as reported by the [`PrestateTracer`].
+pub type PrestateAccounts = BTreeMap<Address, PrestateAccount>;
+
+/// Output of the [`PrestateTracer`], in the shape of the geth `prestateTracer` output.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum PrestateFrame {
+    /// State of all accessed accounts before the execution.
+    Prestate(PrestateAccounts),
+    /// State of the changed accounts before and after the execution.
+    Diff {
+        /// State before the execution.
+        pre: PrestateAccounts,
+        /// Changed fields after the execution.
+        post: PrestateAccounts,
+    },
+}
+
+/// [Inspector] that records the state accessed by the transaction before it was executed.
+///
+/// Accounts and storage slots are read from the database when the top level frame ends,
+/// as the database is not changed during the execution of the transaction.
+/// The post state of diff mode is taken from the state returned by the EVM,
+/// see [`PrestateTracer::frame`].
+#[derive(Clone, Debug, Default)]
+pub struct PrestateTracer {
+    config: PrestateTracerConfig,
+    /// Depth of the execution.
+    depth: usize,
+    /// State of the accessed accounts before the execution.
+    pre: PrestateAccounts,
+}
+
+impl PrestateTracer {
+    /// Creates new prestate tracer with the given configuration.
+    pub fn new(config: PrestateTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the configuration of the tracer.
+    pub fn config(&self) -> &PrestateTracerConfig {
+        &self.config
+    }
+
+    /// Returns the state of the accounts accessed by the last traced transaction.
+    pub fn prestate(&self) -> &PrestateAccounts {
+        &self.pre
+    }
+
+    /// Returns the output of the last traced transaction.
+    ///
+    /// `state` is the state returned by the execution of the traced transaction,
+    /// it is only used in diff mode.
+    pub fn frame(&self, state: &EvmState) -> PrestateFrame {
+        if !self.config.diff_mode {
+            return PrestateFrame::Prestate(self.pre.clone());
+        }
+
+        let mut pre = self.pre.clone();
+        let mut post = PrestateAccounts::new();
+        pre.retain(|address, pre_account| {
+            let Some(account) = state.get(address) else {
+                return false;
+            };
+            // Selfdestructed accounts are kept in pre and pruned from post.
+            if account.is_selfdestructed() {
+                return true;
+            }
+            let post_account = diff_account(pre_account, account);
+            // The prestate of the created accounts was empty.
+            if account.is_created() && pre_account.is_empty() {
+                if let Some(post_account) = post_account {
+                    post.insert(*address, post_account);
+                }
+                return false;
+            }
+            match post_account {
+                Some(post_account) => {
+                    post.insert(*address, post_account);
+                    true
+                }
+                // Accounts that are not modified are not included.
+                None => false,
+            }
+        });
+        PrestateFrame::Diff { pre, post }
+    }
+
+    /// Records the prestate of all accounts and slots loaded by the transaction.
+    fn record_prestate<DB: Database>(
+        &mut self,
+        context: &mut EvmContext<DB>,
+    ) -> Result<(), EVMError<DB::Error>> {
+        let mut accounts = Vec::new();
+        for (address, account) in &context.journaled_state.state {
+            let storage = account
+                .storage
+                .iter()
+                .map(|(slot, value)| (B256::from(*slot), B256::from(value.original_value)))
+                .collect::<BTreeMap<_, _>>();
+            accounts.push((*address, account.is_loaded_as_not_existing(), storage));
+        }
+        // Beneficiary is rewarded after the execution, it may not be loaded yet.
+        let coinbase = context.env.block.coinbase;
+        if !context.journaled_state.state.contains_key(&coinbase) {
+            accounts.push((coinbase, false, BTreeMap::new()));
+        }
+
+        for (address, not_existing, storage) in accounts {
+            let mut pre_account = PrestateAccount {
+                balance: Some(U256::ZERO),
+                storage,
+                ..Default::default()
+            };
+            if !not_existing {
+                if let Some(info) = context.db.basic(address).map_err(EVMError::Database)? {
+                    let code = match info.code {
+                        Some(code) => code.original_bytes(),
+                        None => context
+                            .db
+                            .code_by_hash(info.code_hash)
+                            .map_err(EVMError::Database)?
+                            .original_bytes(),
+                    };
+                    pre_account.balance = Some(info.balance);
+                    pre_account.nonce = (info.nonce != 0).then_some(info.nonce);
+                    pre_account.code = (!code.is_empty()).then_some(code);
+                }
+            }
+            self.pre.insert(address, pre_account);
+        }
+        Ok(())
+    }
+
+    fn start_frame(&mut self) {
+        if self.depth == 0 {
+            self.pre.clear();
+        }
+        self.depth += 1;
+    }
+
+    fn end_frame<DB: Database>(&mut self, context: &mut EvmContext<DB>) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            if let Err(e) = self.record_prestate(context) {
+                context.error = Err(e);
+            }
+        }
+    }
+}
+
+/// Returns the changed fields of the account, `None` if account is not modified.
+///
+/// Unchanged and empty slots are removed from the pre state.
+fn diff_account(pre: &mut PrestateAccount, account: &Account) -> Option<PrestateAccount> {
+    let mut modified = false;
+    let mut post = PrestateAccount::default();
+
+    let info = &account.info;
+    if pre.balance != Some(info.balance) {
+        modified = true;
+        post.balance = Some(info.balance);
+    }
+    if pre.nonce.unwrap_or_default() != info.nonce {
+        modified = true;
+        post.nonce = Some(info.nonce);
+    }
+    let code = info
+        .code
+        .as_ref()
+        .map(|code| code.original_bytes())
+        .unwrap_or_default();
+    if pre.code.as_ref().unwrap_or(&Bytes::new()) != &code {
+        modified = true;
+        post.code = (!code.is_empty()).then_some(code);
+    }
+
+    pre.storage.retain(|slot, pre_value| {
+        let present_value = account
+            .storage
+            .get(&U256::from_be_bytes(slot.0))
+            .map(|value| B256::from(value.present_value))
+            .unwrap_or(*pre_value);
+        if present_value == *pre_value {
+            return false;
+        }
+        modified = true;
+        if present_value != B256::ZERO {
+            post.storage.insert(*slot, present_value);
+        }
+        *pre_value != B256::ZERO
+    });
+
+    modified.then_some(post)
+}
+
+impl<DB: Database> Inspector<DB> for PrestateTracer {
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.start_frame();
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.end_frame(context);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start_frame();
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end_frame(context);
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, AccountInfo, Bytecode, TransactTo},
+        Evm,
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+    const CONTRACT: Address = address!("2000000000000000000000000000000000000000");
+    const RECIPIENT: Address = address!("3000000000000000000000000000000000000000");
+
+    fn trace(config: PrestateTracerConfig) -> (PrestateTracer, EvmState) {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1_000_000)));
+        // SLOAD(0), SSTORE(1, 1), SSTORE(2, 0)
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0,
+                opcode::SLOAD,
+                opcode::POP,
+                opcode::PUSH1,
+                1,
+                opcode::PUSH1,
+                1,
+                opcode::SSTORE,
+                opcode::PUSH1,
+                0,
+                opcode::PUSH1,
+                2,
+                opcode::SSTORE,
+            ]
+            .into(),
+        );
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(CONTRACT, U256::from(0), U256::from(5))
+            .unwrap();
+        db.insert_account_storage(CONTRACT, U256::from(2), U256::from(7))
+            .unwrap();
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(PrestateTracer::new(config))
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(CONTRACT);
+                tx.value = U256::from(10);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let state = evm.transact().unwrap().state;
+        (evm.context.external, state)
+    }
+
+    fn slot(value: u64) -> B256 {
+        U256::from(value).into()
+    }
+
+    #[test]
+    fn prestate() {
+        let (tracer, state) = trace(PrestateTracerConfig::default());
+        let PrestateFrame::Prestate(pre) = tracer.frame(&state) else {
+            panic!("expected prestate");
+        };
+        assert_eq!(
+            pre[&CALLER],
+            PrestateAccount {
+                balance: Some(U256::from(1_000_000)),
+                ..Default::default()
+            }
+        );
+        let contract = &pre[&CONTRACT];
+        assert_eq!(contract.balance, Some(U256::ZERO));
+        assert_eq!(contract.nonce, Some(1));
+        assert!(contract.code.is_some());
+        assert_eq!(
+            contract.storage,
+            [(slot(0), slot(5)), (slot(1), slot(0)), (slot(2), slot(7))]
+                .into_iter()
+                .collect()
+        );
+        assert!(!pre.contains_key(&RECIPIENT));
+    }
+
+    #[test]
+    fn diff_mode() {
+        let (tracer, state) = trace(PrestateTracerConfig { diff_mode: true });
+        let PrestateFrame::Diff { pre, post } = tracer.frame(&state) else {
+            panic!("expected diff");
+        };
+
+        // Storage contains only changed slots, zero slots are omitted.
+        let contract_pre = &pre[&CONTRACT];
+        assert_eq!(
+            contract_pre.storage,
+            [(slot(2), slot(7))].into_iter().collect()
+        );
+        let contract_post = &post[&CONTRACT];
+        assert_eq!(contract_post.balance, Some(U256::from(10)));
+        assert_eq!(contract_post.nonce, None);
+        assert_eq!(contract_post.code, None);
+        assert_eq!(
+            contract_post.storage,
+            [(slot(1), slot(1))].into_iter().collect()
+        );
+
+        let caller_post = &post[&CALLER];
+        assert_eq!(caller_post.nonce, Some(1));
+        assert!(caller_post.balance.is_some());
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn geth_json() {
+        let (tracer, state) = trace(PrestateTracerConfig { diff_mode: true });
+        let json = serde_json::to_value(tracer.frame(&state)).unwrap();
+        let contract = &json["pre"][CONTRACT.to_string().to_lowercase()];
+        assert_eq!(contract["balance"], "0x0");
+        assert_eq!(contract["nonce"], 1);
+        assert_eq!(
+            contract["storage"][slot(2).to_string()],
+            slot(7).to_string()
+        );
+        assert!(json["post"][CALLER.to_string().to_lowercase()]
+            .get("code")
+            .is_none());
+
+        let config: PrestateTracerConfig = serde_json::from_str(r#"{"diffMode":true}"#).unwrap();
+        assert!(config.diff_mode);
+    }
+}
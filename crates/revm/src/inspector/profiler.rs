@@ -0,0 +1,434 @@
+//! [Inspector] that profiles gas and time of the executed opcodes.
+
+use crate::{
+    interpreter::{
+        opcode::OpCode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInput,
+        EOFCreateOutcome, Gas, InstructionResult, Interpreter,
+    },
+    primitives::{db::Database, hex, Address},
+    EvmContext, Inspector,
+};
+use core::{fmt::Write, time::Duration};
+use std::{collections::BTreeMap, string::String, time::Instant, vec::Vec};
+
+/// Totals of the executed instructions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProfileEntry {
+    /// Number of executed instructions.
+    pub count: u64,
+    /// Gas spent by the instructions, excluding the gas spent in the called contracts.
+    pub gas: u64,
+    /// Wall time of the instructions, excluding the time spent in the called contracts.
+    pub time: Duration,
+}
+
+impl ProfileEntry {
+    fn add(&mut self, count: u64, gas: u64, time: Duration) {
+        self.count += count;
+        self.gas += gas;
+        self.time += time;
+    }
+}
+
+/// Frame name used in the folded stacks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlameGraphKey {
+    /// Contract address and the program counter, `0x..:pc`.
+    #[default]
+    Pc,
+    /// Contract address and the function selector of the call, `0x..:0x12345678`.
+    Selector,
+}
+
+/// Weight of the folded stacks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlameGraphWeight {
+    /// Gas used by the instructions.
+    #[default]
+    Gas,
+    /// Wall time in nanoseconds.
+    Time,
+}
+
+/// Instruction that is executing, finished on the next step or at the end of the call.
+#[derive(Clone, Copy, Debug)]
+struct PendingInstruction {
+    opcode: u8,
+    pc: usize,
+    gas_remaining: u64,
+    start: Instant,
+    /// Gas spent in the calls made by the instruction.
+    child_gas: u64,
+    /// Time spent in the calls made by the instruction.
+    child_time: Duration,
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+    /// Address of the executed code, known at the first step for creates.
+    address: Option<Address>,
+    selector: Option<[u8; 4]>,
+    start: Instant,
+    /// Gas attributed to the instructions and calls of the frame.
+    attributed_gas: u64,
+    pending: Option<PendingInstruction>,
+}
+
+/// Inspector that aggregates count, gas and wall time of the executed instructions per
+/// opcode, per contract and per call depth, and collects folded stacks for flamegraphs.
+///
+/// Gas and time of the instructions exclude the calls they make, so the totals add up to
+/// the execution of the transaction. Gas spent in calls without code, like precompiles, is
+/// attributed to the called contract.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    key: FlameGraphKey,
+    opcodes: BTreeMap<u8, ProfileEntry>,
+    contracts: BTreeMap<Address, ProfileEntry>,
+    depths: BTreeMap<usize, ProfileEntry>,
+    /// Folded stack lines without weight.
+    stacks: BTreeMap<String, ProfileEntry>,
+    frames: Vec<Frame>,
+}
+
+impl Profiler {
+    /// Creates new profiler with folded stacks keyed by the program counter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the frame name of the folded stacks.
+    pub fn with_flamegraph_key(mut self, key: FlameGraphKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Returns totals per opcode.
+    pub fn opcodes(&self) -> &BTreeMap<u8, ProfileEntry> {
+        &self.opcodes
+    }
+
+    /// Returns totals per address of the executed code.
+    pub fn contracts(&self) -> &BTreeMap<Address, ProfileEntry> {
+        &self.contracts
+    }
+
+    /// Returns totals per call depth, starting from zero for the transaction call.
+    pub fn depths(&self) -> &BTreeMap<usize, ProfileEntry> {
+        &self.depths
+    }
+
+    /// Returns the sum of the totals of all instructions.
+    pub fn total(&self) -> ProfileEntry {
+        self.depths
+            .values()
+            .fold(ProfileEntry::default(), |mut total, entry| {
+                total.add(entry.count, entry.gas, entry.time);
+                total
+            })
+    }
+
+    /// Returns the folded stacks, one `frame;frame;OPCODE weight` line per stack, that can
+    /// be rendered by flamegraph tools.
+    pub fn folded_stacks(&self, weight: FlameGraphWeight) -> String {
+        let mut out = String::new();
+        for (stack, entry) in &self.stacks {
+            let weight = match weight {
+                FlameGraphWeight::Gas => entry.gas as u128,
+                FlameGraphWeight::Time => entry.time.as_nanos(),
+            };
+            if weight != 0 {
+                let _ = writeln!(out, "{stack} {weight}");
+            }
+        }
+        out
+    }
+
+    /// Returns the folded stack of the frames, with the program counters of the pending
+    /// instructions.
+    fn stack(&self) -> String {
+        let mut stack = String::new();
+        for (i, frame) in self.frames.iter().enumerate() {
+            if i != 0 {
+                stack.push(';');
+            }
+            let _ = write!(stack, "{}", frame.address.unwrap_or_default());
+            match (self.key, frame.pending, frame.selector) {
+                (FlameGraphKey::Pc, Some(pending), _) => {
+                    let _ = write!(stack, ":{}", pending.pc);
+                }
+                (FlameGraphKey::Selector, _, Some(selector)) => {
+                    let _ = write!(stack, ":0x{}", hex::encode(selector));
+                }
+                _ => {}
+            }
+        }
+        stack
+    }
+
+    /// Records the pending instruction of the top frame, with the gas remaining after it.
+    fn finish_instruction(&mut self, gas_remaining: u64, now: Instant) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let Some(pending) = frame.pending else {
+            return;
+        };
+        let gas = pending
+            .gas_remaining
+            .saturating_sub(gas_remaining)
+            .saturating_sub(pending.child_gas);
+        let time = now
+            .saturating_duration_since(pending.start)
+            .saturating_sub(pending.child_time);
+        frame.attributed_gas += gas + pending.child_gas;
+        let address = frame.address.unwrap_or_default();
+
+        let mut stack = self.stack();
+        let _ = write!(stack, ";{}", OpCode::name_by_op(pending.opcode));
+        self.record(Some(pending.opcode), address, stack, gas, time);
+        if let Some(frame) = self.frames.last_mut() {
+            frame.pending = None;
+        }
+    }
+
+    fn record(
+        &mut self,
+        opcode: Option<u8>,
+        address: Address,
+        stack: String,
+        gas: u64,
+        time: Duration,
+    ) {
+        let count = opcode.is_some() as u64;
+        if let Some(opcode) = opcode {
+            self.opcodes.entry(opcode).or_default().add(1, gas, time);
+        }
+        self.contracts
+            .entry(address)
+            .or_default()
+            .add(count, gas, time);
+        self.depths
+            .entry(self.frames.len().saturating_sub(1))
+            .or_default()
+            .add(count, gas, time);
+        self.stacks.entry(stack).or_default().add(count, gas, time);
+    }
+
+    fn start_frame(&mut self, address: Option<Address>, selector: Option<[u8; 4]>) {
+        self.frames.push(Frame {
+            address,
+            selector,
+            start: Instant::now(),
+            attributed_gas: 0,
+            pending: None,
+        });
+    }
+
+    /// Finishes the top frame and adds the gas and time of it to the calling instruction.
+    fn end_frame(&mut self, result: InstructionResult, gas: &Gas, address: Option<Address>) {
+        let now = Instant::now();
+        // gas is spent in full when the call halts.
+        let spent = if result.is_error() {
+            gas.limit()
+        } else {
+            gas.spent()
+        };
+        self.finish_instruction(gas.limit() - spent, now);
+
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        if frame.address.is_none() {
+            frame.address = address;
+        }
+        let address = frame.address.unwrap_or_default();
+        let start = frame.start;
+        let unattributed = spent.saturating_sub(frame.attributed_gas);
+        if unattributed != 0 {
+            let stack = self.stack();
+            self.record(None, address, stack, unattributed, Duration::ZERO);
+        }
+        self.frames.pop();
+
+        if let Some(pending) = self
+            .frames
+            .last_mut()
+            .and_then(|frame| frame.pending.as_mut())
+        {
+            pending.child_gas += spent;
+            pending.child_time += now.saturating_duration_since(start);
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for Profiler {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        self.finish_instruction(interp.gas.remaining(), Instant::now());
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        frame.address.get_or_insert(interp.contract.target_address);
+        frame.pending = Some(PendingInstruction {
+            opcode: interp.current_opcode(),
+            pc: interp.program_counter(),
+            gas_remaining: interp.gas.remaining(),
+            start: Instant::now(),
+            child_gas: 0,
+            child_time: Duration::ZERO,
+        });
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let selector = inputs.input.get(..4).map(|s| s.try_into().unwrap());
+        self.start_frame(Some(inputs.bytecode_address), selector);
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.end_frame(outcome.result.result, &outcome.result.gas, None);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start_frame(None, None);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.end_frame(outcome.result.result, &outcome.result.gas, outcome.address);
+        outcome
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut EOFCreateInput,
+    ) -> Option<EOFCreateOutcome> {
+        self.start_frame(None, None);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &EOFCreateInput,
+        outcome: EOFCreateOutcome,
+    ) -> EOFCreateOutcome {
+        self.end_frame(
+            outcome.result.result,
+            &outcome.result.gas,
+            Some(outcome.address),
+        );
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        interpreter::opcode,
+        primitives::{address, AccountInfo, Bytecode, Bytes, TransactTo, U256},
+        Evm,
+    };
+
+    #[test]
+    fn profile_nested_call() {
+        let caller = address!("1000000000000000000000000000000000000000");
+        let contract = address!("2000000000000000000000000000000000000000");
+        let callee = address!("3000000000000000000000000000000000000000");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        // SSTORE(0, 1), STOP
+        let callee_code = Bytes::from_static(&[0x60, 0x01, 0x5f, 0x55, 0x00]);
+        db.insert_account_info(
+            callee,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(callee_code)),
+                ..Default::default()
+            },
+        );
+        // CALL(gas, callee, 0, 0, 0, 0, 0), ecrecover precompile STATICCALL, STOP
+        let mut code = vec![0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x73];
+        code.extend_from_slice(callee.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x50]);
+        code.extend_from_slice(&[0x5f, 0x5f, 0x5f, 0x5f, 0x60, 0x01, 0x5a, 0xfa, 0x50, 0x00]);
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(10u128.pow(18)),
+                ..Default::default()
+            },
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(Profiler::new())
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.data = Bytes::from_static(&[0x12, 0x34, 0x56, 0x78]);
+                tx.gas_limit = 1_000_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap().result;
+        let profiler = &evm.context.external;
+
+        // all gas of the execution is attributed.
+        assert_eq!(profiler.total().gas, result.gas_used() - 21_064);
+        assert_eq!(profiler.opcodes()[&opcode::SSTORE].gas, 22_100);
+        assert_eq!(profiler.opcodes()[&opcode::SSTORE].count, 1);
+        // cold account access and the call to ecrecover.
+        assert_eq!(profiler.opcodes()[&opcode::CALL].gas, 2_600);
+        assert_eq!(profiler.contracts()[&callee].count, 4);
+        assert_eq!(profiler.contracts()[&callee].gas, 22_100 + 3 + 2);
+        // ecrecover with empty input costs 3000 gas.
+        assert_eq!(
+            profiler.contracts()[&address!("0000000000000000000000000000000000000001")].gas,
+            3_000
+        );
+        assert_eq!(profiler.depths()[&1].gas, 22_105 + 3_000);
+
+        let folded = profiler.folded_stacks(FlameGraphWeight::Gas);
+        assert!(folded.contains(&format!("{contract}:27;{callee}:3;SSTORE 22100\n")));
+
+        let profiler = Profiler::new().with_flamegraph_key(FlameGraphKey::Selector);
+        let mut evm = evm
+            .modify()
+            .reset_handler_with_external_context(profiler)
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap();
+        let folded = evm.context.external.folded_stacks(FlameGraphWeight::Gas);
+        assert!(folded.contains(&format!("{contract}:0x12345678;{callee};SSTORE 22100\n")));
+    }
+}
@@ -600,7 +600,11 @@ impl JournaledState {
             loaded_not_existing && is_not_touched
         };
 
-        Ok(LoadAccountResult { is_empty, is_cold })
+        Ok(LoadAccountResult {
+            is_empty,
+            is_cold,
+            is_delegate_account_cold: None,
+        })
     }
 
     /// Loads code.
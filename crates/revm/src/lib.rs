@@ -8,8 +8,13 @@ extern crate alloc as std;
 
 // Define modules.
 
+mod access_list;
+#[cfg(feature = "std")]
+mod analysis_cache;
+mod block_executor;
 mod builder;
 mod context;
+mod estimate_gas;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
@@ -22,19 +27,27 @@ mod inspector;
 mod journaled_state;
 #[cfg(feature = "optimism")]
 pub mod optimism;
+mod suspend;
 
 // Export items.
 
+pub use access_list::{create_access_list, AccessListResult, MAX_ACCESS_LIST_ITERATIONS};
+#[cfg(feature = "std")]
+pub use analysis_cache::{AnalysisCache, AnalysisCacheMetrics};
+pub use block_executor::{
+    BlockExecutionError, BlockExecutionOutput, BlockExecutor, Receipt, Withdrawal,
+};
 pub use builder::EvmBuilder;
 pub use context::{
     Context, ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile,
     ContextStatefulPrecompileArc, ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
-    ContextWithHandlerCfg, EvmContext, InnerEvmContext,
+    ContextWithHandlerCfg, EvmContext, InnerEvmContext, MAX_DECODED_BYTECODES,
 };
 pub use db::{
     CacheState, DBBox, State, StateBuilder, StateDBBox, TransitionAccount, TransitionState,
 };
 pub use db::{Database, DatabaseCommit, DatabaseRef, InMemoryDB};
+pub use estimate_gas::{EstimateGasError, GasEstimate};
 pub use evm::{Evm, CALL_STACK_LIMIT};
 pub use frame::{CallFrame, CreateFrame, Frame, FrameData, FrameOrResult, FrameResult};
 pub use handler::Handler;
@@ -42,6 +55,7 @@ pub use inspector::{
     inspector_handle_register, inspector_instruction, inspectors, GetInspector, Inspector,
 };
 pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState};
+pub use suspend::{ExecutionSnapshot, ResumableOutput};
 // export Optimism types, helpers, and constants
 #[cfg(feature = "optimism")]
 pub use optimism::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
@@ -47,6 +47,7 @@ pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000
 ///
 /// For now, we only care about the fields necessary for L1 cost calculation.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct L1BlockInfo {
     /// The base fee of the L1 origin block.
     pub l1_base_fee: U256,
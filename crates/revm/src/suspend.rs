@@ -0,0 +1,312 @@
+//! Suspension of the transaction execution and its resumption.
+
+use crate::{
+    db::Database,
+    interpreter::{InstructionResult, SharedMemory},
+    primitives::{EVMError, EVMResult, Env, HashSet, ResultAndState, SpecId},
+    Evm, Frame, FrameOrResult, JournaledState,
+};
+use std::{boxed::Box, string::ToString, vec::Vec};
+
+/// Snapshot of the suspended transaction execution.
+///
+/// It contains everything that is needed to resume the execution with [Evm::resume], and with
+/// the `serde` feature it can be serialized to resume the execution in another process.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionSnapshot {
+    /// Environment of the transaction.
+    pub env: Box<Env>,
+    /// Specification of the suspended execution, precompiles are loaded for it on resume.
+    pub spec_id: SpecId,
+    /// Call stack, the last frame is the one that was suspended.
+    pub call_stack: Vec<Frame>,
+    /// Memory shared by the frames of the call stack.
+    pub shared_memory: SharedMemory,
+    /// Journaled state with the changes made by the transaction.
+    pub journaled_state: JournaledState,
+    /// Gas refund of the EIP-7702 authorization list.
+    pub eip7702_refund: i64,
+    /// L1 block info loaded for the transaction.
+    #[cfg(feature = "optimism")]
+    pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
+}
+
+/// Output of the transaction execution that can be suspended.
+#[derive(Debug)]
+pub enum ResumableOutput {
+    /// Transaction was executed to the end.
+    Done(ResultAndState),
+    /// Execution was suspended, it can be resumed with [Evm::resume].
+    Suspended(Box<ExecutionSnapshot>),
+}
+
+impl<EXT, DB: Database> Evm<'_, EXT, DB> {
+    /// Transact transaction that can be suspended.
+    ///
+    /// Execution is suspended before an instruction when its [InstructionResult] is set to
+    /// [InstructionResult::Suspend], for example in [Inspector::step](crate::Inspector::step).
+    /// Suspended execution is returned as [ExecutionSnapshot] and state changes are not loaded
+    /// into the database.
+    ///
+    /// This function will validate the transaction.
+    pub fn transact_resumable(&mut self) -> Result<ResumableOutput, EVMError<DB::Error>> {
+        let initial_gas_spend = self
+            .preverify_transaction_inner()
+            .inspect_err(|_| self.clear())?;
+
+        match self.transact_first_frame(initial_gas_spend) {
+            Ok(FrameOrResult::Frame(first_frame)) => {
+                let mut call_stack = Vec::with_capacity(1025);
+                call_stack.push(first_frame);
+                let shared_memory = self.new_shared_memory();
+                self.run_resumable(call_stack, shared_memory)
+            }
+            Ok(FrameOrResult::Result(result)) => {
+                let output = self.transact_output(result);
+                self.end_resumable(output)
+            }
+            Err(e) => self.end_resumable(Err(e)),
+        }
+    }
+
+    /// Resumes the suspended execution of the transaction.
+    ///
+    /// Environment is replaced with the environment of the snapshot. The database must contain
+    /// the same state as when the execution was started, and EVM must have the same
+    /// specification and handler registers so that the execution is identical.
+    ///
+    /// Inspector does not observe the end of the calls that were started before the execution
+    /// was suspended, and the
+    /// [ExecutionBudget](crate::primitives::ExecutionBudget) is started again.
+    pub fn resume(
+        &mut self,
+        snapshot: ExecutionSnapshot,
+    ) -> Result<ResumableOutput, EVMError<DB::Error>> {
+        let ExecutionSnapshot {
+            env,
+            spec_id,
+            mut call_stack,
+            shared_memory,
+            journaled_state,
+            eip7702_refund,
+            #[cfg(feature = "optimism")]
+            l1_block_info,
+        } = snapshot;
+
+        if spec_id != self.spec_id() {
+            return Err(EVMError::Custom(format!(
+                "Execution was suspended with {spec_id:?} spec, but EVM has {:?} spec",
+                self.spec_id()
+            )));
+        }
+        let Some(frame) = call_stack.last_mut() else {
+            return Err(EVMError::Custom(
+                "Execution snapshot has empty call stack".to_string(),
+            ));
+        };
+        frame.interpreter_mut().instruction_result = InstructionResult::Continue;
+
+        let precompiles = self.handler.pre_execution().load_precompiles();
+        let ctx = &mut self.context.evm;
+        ctx.env = env;
+        ctx.set_precompiles(precompiles);
+        // Journal contains the warm precompile addresses.
+        ctx.journaled_state = journaled_state;
+        ctx.eip7702_refund = eip7702_refund;
+        #[cfg(feature = "optimism")]
+        {
+            ctx.l1_block_info = l1_block_info;
+        }
+
+        self.run_resumable(call_stack, shared_memory)
+    }
+
+    /// Runs the call stack and returns the snapshot if the execution was suspended.
+    fn run_resumable(
+        &mut self,
+        mut call_stack: Vec<Frame>,
+        mut shared_memory: SharedMemory,
+    ) -> Result<ResumableOutput, EVMError<DB::Error>> {
+        let output = match self.run_call_stack(&mut call_stack, &mut shared_memory) {
+            Ok(Some(result)) => self.transact_output(result),
+            Ok(None) => {
+                let spec_id = self.spec_id();
+                let ctx = &mut self.context.evm;
+                let snapshot = ExecutionSnapshot {
+                    env: ctx.env.clone(),
+                    spec_id,
+                    call_stack,
+                    shared_memory,
+                    journaled_state: core::mem::replace(
+                        &mut ctx.journaled_state,
+                        JournaledState::new(spec_id, HashSet::new()),
+                    ),
+                    eip7702_refund: ctx.eip7702_refund,
+                    #[cfg(feature = "optimism")]
+                    l1_block_info: ctx.l1_block_info.take(),
+                };
+                self.clear();
+                return Ok(ResumableOutput::Suspended(Box::new(snapshot)));
+            }
+            Err(e) => Err(e),
+        };
+        self.end_resumable(output)
+    }
+
+    /// Calls the end and clear handles of post execution.
+    fn end_resumable(
+        &mut self,
+        output: EVMResult<DB::Error>,
+    ) -> Result<ResumableOutput, EVMError<DB::Error>> {
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+        output.map(ResumableOutput::Done)
+    }
+}
+
+#[cfg(all(test, feature = "serde-json"))]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        interpreter::{CallInputs, CallOutcome, Interpreter},
+        primitives::{address, hex, AccountInfo, Address, Bytecode, TransactTo, U256},
+        EvmContext, Inspector,
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000001");
+    const CONTRACT: Address = address!("2000000000000000000000000000000000000002");
+    const CALLEE: Address = address!("3000000000000000000000000000000000000003");
+
+    /// Suspends the execution before every `every`-th step.
+    struct SuspendEvery {
+        every: u64,
+        steps: u64,
+        /// Targets of the ended calls.
+        ended_calls: Vec<Address>,
+    }
+
+    impl<DB: Database> Inspector<DB> for SuspendEvery {
+        fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+            self.steps += 1;
+            if self.steps.is_multiple_of(self.every) {
+                interp.instruction_result = InstructionResult::Suspend;
+            }
+        }
+
+        fn call_end(
+            &mut self,
+            _context: &mut EvmContext<DB>,
+            inputs: &CallInputs,
+            outcome: CallOutcome,
+        ) -> CallOutcome {
+            self.ended_calls.push(inputs.target_address);
+            outcome
+        }
+    }
+
+    fn db() -> CacheDB<EmptyDB> {
+        // sstore(1, 42), mstore(0, 42), return(0, 32)
+        let callee = hex!("602a600155602a60005260206000f3");
+        // call(gas, CALLEE, 0, 0, 0, 0, 32), pop, sstore(0, mload(0)), log0(0, 32), stop
+        let contract = [
+            &hex!("60206000600060006000")[..],
+            &[0x73],
+            CALLEE.as_slice(),
+            &hex!("5af15060005160005560206000a000"),
+        ]
+        .concat();
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in [(CONTRACT, contract), (CALLEE, callee.to_vec())] {
+            let code = Bytecode::new_raw(code.into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code),
+            );
+        }
+        db
+    }
+
+    fn evm(every: u64) -> Evm<'static, SuspendEvery, CacheDB<EmptyDB>> {
+        Evm::builder()
+            .with_db(db())
+            .with_external_context(SuspendEvery {
+                every,
+                steps: 0,
+                ended_calls: Vec::new(),
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(CONTRACT);
+                tx.gas_limit = 1_000_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build()
+    }
+
+    #[test]
+    fn resume_in_new_evm() {
+        let expected = match evm(u64::MAX).transact_resumable().unwrap() {
+            ResumableOutput::Done(result_and_state) => result_and_state,
+            ResumableOutput::Suspended(_) => panic!("execution should not be suspended"),
+        };
+        assert!(expected.result.is_success());
+        assert_eq!(
+            expected.state[&CONTRACT].storage[&U256::ZERO].present_value,
+            U256::from(42)
+        );
+        assert_eq!(expected.result.logs().len(), 1);
+
+        // Suspended step is repeated on resume, so every step can't be suspended.
+        for every in 2..=10 {
+            let mut output = evm(every).transact_resumable().unwrap();
+            let mut suspensions = 0;
+            let result_and_state = loop {
+                match output {
+                    ResumableOutput::Done(result_and_state) => break result_and_state,
+                    ResumableOutput::Suspended(snapshot) => {
+                        suspensions += 1;
+                        let json = serde_json::to_string(&snapshot).unwrap();
+                        let snapshot: ExecutionSnapshot = serde_json::from_str(&json).unwrap();
+                        output = evm(every).resume(snapshot).unwrap();
+                    }
+                }
+            };
+            assert!(suspensions > 0);
+            assert_eq!(result_and_state, expected);
+        }
+    }
+
+    #[test]
+    fn inputs_are_cleared_on_suspension() {
+        // Suspended inside of the call to the callee.
+        let ResumableOutput::Suspended(snapshot) = evm(10).transact_resumable().unwrap() else {
+            panic!("execution should be suspended");
+        };
+
+        // Abandoned execution that was suspended before the call.
+        let mut evm = evm(3);
+        assert!(matches!(
+            evm.transact_resumable().unwrap(),
+            ResumableOutput::Suspended(_)
+        ));
+        evm.context.external.every = u64::MAX;
+        let ResumableOutput::Done(result_and_state) = evm.resume(*snapshot).unwrap() else {
+            panic!("execution should not be suspended");
+        };
+        assert!(result_and_state.result.is_success());
+        // Both calls were started before the suspension.
+        assert!(evm.context.external.ended_calls.is_empty());
+
+        evm.transact_resumable().unwrap();
+        assert_eq!(evm.context.external.ended_calls, [CALLEE, CONTRACT]);
+    }
+
+    #[test]
+    fn run_the_loop_is_not_suspended() {
+        assert!(matches!(evm(1).transact(), Err(EVMError::Suspended)));
+    }
+}
@@ -2,7 +2,7 @@ use alloy_sol_types::sol;
 use alloy_sol_types::SolCall;
 use ethers_providers::{Http, Provider};
 use revm::{
-    db::{CacheDB, EmptyDB, EthersDB},
+    db::{CacheDB, EmptyDB, EthersDB, WrapDatabaseAsync},
     primitives::{address, ExecutionResult, Output, TransactTo, U256},
     Database, Evm,
 };
@@ -42,8 +42,9 @@ async fn main() -> anyhow::Result<()> {
     // encode abi into Bytes
     let encoded = getReservesCall::new(()).abi_encode();
 
-    // initialize new EthersDB
-    let mut ethersdb = EthersDB::new(Arc::clone(&client), None).unwrap();
+    // initialize new EthersDB based on the latest block
+    let ethersdb = EthersDB::latest(Arc::clone(&client)).await?;
+    let mut ethersdb = WrapDatabaseAsync::new(ethersdb)?;
 
     // query basic properties of an account incl bytecode
     let acc_info = ethersdb.basic(pool_address).unwrap().unwrap();
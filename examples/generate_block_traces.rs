@@ -4,7 +4,7 @@ use ethers_core::types::BlockId;
 use ethers_providers::Middleware;
 use ethers_providers::{Http, Provider};
 use indicatif::ProgressBar;
-use revm::db::{CacheDB, EthersDB, StateBuilder};
+use revm::db::{CacheDB, EthersDB, StateBuilder, WrapDatabaseAsync};
 use revm::inspectors::TracerEip3155;
 use revm::primitives::{Address, TransactTo, U256};
 use revm::{inspector_handle_register, Evm};
@@ -71,9 +71,9 @@ async fn main() -> anyhow::Result<()> {
 
     // Use the previous block state as the db with caching
     let prev_id: BlockId = previous_block_number.into();
-    // SAFETY: This cannot fail since this is in the top-level tokio runtime
-    let state_db = EthersDB::new(Arc::clone(&client), Some(prev_id)).expect("panic");
-    let cache_db: CacheDB<EthersDB<Provider<Http>>> = CacheDB::new(state_db);
+    let state_db =
+        WrapDatabaseAsync::new(EthersDB::with_block_number(Arc::clone(&client), prev_id))?;
+    let cache_db: CacheDB<WrapDatabaseAsync<EthersDB<Provider<Http>>>> = CacheDB::new(state_db);
     let mut state = StateBuilder::new_with_database(cache_db).build();
     let mut evm = Evm::builder()
         .with_db(&mut state)
@@ -0,0 +1,33 @@
+//! Runs the state tests with the JIT compilation backend and compares the results and the
+//! state roots with the interpreter.
+//!
+//! Usage: `cargo run -p revm-aot --release --example jit_statetest -- <PATH>...`
+
+use revm::Evm;
+use revm_aot::{aot_handle_register, AotBackend, AotConfig};
+use revme::cmd::statetest::runner::{find_all_json_tests, run, set_differential_execution};
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Compile every bytecode when it is executed for the first time.
+    let backend = AotBackend::new(AotConfig {
+        hot_threshold: 0,
+        ..Default::default()
+    })?;
+    set_differential_execution(Box::new(move |state, env, spec_id| {
+        Evm::builder()
+            .with_spec_id(spec_id)
+            .with_db(state)
+            .with_env(Box::new(env.clone()))
+            .with_external_context(backend.clone())
+            .append_handler_register(aot_handle_register)
+            .build()
+            .transact_commit()
+    }));
+
+    for path in std::env::args().skip(1).map(PathBuf::from) {
+        println!("\nRunning tests in {}...", path.display());
+        run(find_all_json_tests(&path), false, false, false, false)?;
+    }
+    Ok(())
+}
@@ -5,7 +5,7 @@ use alloy_transport_http::Http;
 use anyhow::{anyhow, Result};
 use reqwest::Client;
 use revm::{
-    db::{AlloyDB, CacheDB},
+    db::{AlloyDB, CacheDB, WrapDatabaseAsync},
     primitives::{
         address, keccak256, AccountInfo, Address, Bytes, ExecutionResult, Output, TransactTo, U256,
     },
@@ -14,7 +14,8 @@ use revm::{
 use std::ops::Div;
 use std::sync::Arc;
 
-type AlloyCacheDB = CacheDB<AlloyDB<Http<Client>, Ethereum, Arc<RootProvider<Http<Client>>>>>;
+type AlloyCacheDB =
+    CacheDB<WrapDatabaseAsync<AlloyDB<Http<Client>, Ethereum, Arc<RootProvider<Http<Client>>>>>>;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -24,7 +25,8 @@ async fn main() -> Result<()> {
             .unwrap(),
     );
     let client = Arc::new(client);
-    let mut cache_db = CacheDB::new(AlloyDB::new(client, BlockId::default()));
+    let alloy_db = WrapDatabaseAsync::new(AlloyDB::new(client, BlockId::default()))?;
+    let mut cache_db = CacheDB::new(alloy_db);
 
     // Random empty account
     let account = address!("18B06aaF27d44B756FCF16Ca20C1f183EB49111f");